@@ -0,0 +1,81 @@
+//! Plain text descriptions of grids meant for screen readers.
+
+use crate::{Grid, SymbolSet, EMPTY_CELL};
+
+/// Describes the grid row by row in words, e.g. "Row 1: 4, blank, blank, 8".
+pub fn describe_grid(grid: &Grid) -> String {
+    describe_grid_with_symbols(grid, &SymbolSet::digits())
+}
+
+/// Like [describe_grid] but reading out the values as `symbols`, so that it matches the
+/// puzzle as printed.
+pub fn describe_grid_with_symbols(grid: &Grid, symbols: &SymbolSet) -> String {
+    let mut lines = Vec::with_capacity(10);
+    let filled = (0..81)
+        .filter(|&i| grid.get(i % 9, i / 9) != EMPTY_CELL)
+        .count();
+    lines.push(format!("Grid with {} of 81 cells filled.", filled));
+    for y in 0..9 {
+        let cells: Vec<String> = (0..9)
+            .map(|x| describe_value(grid, x, y, symbols))
+            .collect();
+        lines.push(format!("Row {}: {}.", y + 1, cells.join(", ")));
+    }
+    lines.join("\n")
+}
+
+/// Describes a single cell, e.g. "Row 2, column 5: blank".
+pub fn describe_cell(grid: &Grid, x: usize, y: usize) -> String {
+    describe_cell_with_symbols(grid, x, y, &SymbolSet::digits())
+}
+
+pub fn describe_cell_with_symbols(grid: &Grid, x: usize, y: usize, symbols: &SymbolSet) -> String {
+    format!(
+        "Row {}, column {}: {}",
+        y + 1,
+        x + 1,
+        describe_value(grid, x, y, symbols)
+    )
+}
+
+fn describe_value(grid: &Grid, x: usize, y: usize, symbols: &SymbolSet) -> String {
+    match grid.get(x, y) {
+        EMPTY_CELL => "blank".to_string(),
+        value => symbols.symbol(value).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describe() {
+        let mut grid = Grid::new(&[0u8; 81]);
+        grid.set(4, 0, 0);
+        grid.set(7, 2, 0);
+        let text = describe_grid(&grid);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "Grid with 2 of 81 cells filled.");
+        assert_eq!(
+            lines[1],
+            "Row 1: 4, blank, 7, blank, blank, blank, blank, blank, blank."
+        );
+        assert_eq!(lines.len(), 10);
+        assert_eq!(describe_cell(&grid, 2, 0), "Row 1, column 3: 7");
+    }
+
+    #[test]
+    fn describe_with_symbols() {
+        let mut grid = Grid::new(&[0u8; 81]);
+        grid.set(4, 0, 0);
+        grid.set(7, 2, 0);
+        let letters = SymbolSet::letters();
+        let text = describe_grid_with_symbols(&grid, &letters);
+        assert!(text.contains("Row 1: D, blank, G, blank,"));
+        assert_eq!(
+            describe_cell_with_symbols(&grid, 2, 0, &letters),
+            "Row 1, column 3: G"
+        );
+    }
+}
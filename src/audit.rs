@@ -0,0 +1,230 @@
+//! Checking every puzzle of a collection before it is published: each must be readable,
+//! follow the rules and have exactly one solution.
+
+use std::fmt::Display;
+use std::fmt::Error;
+use std::fmt::Formatter;
+use std::fmt::Write;
+
+use rayon::prelude::*;
+
+use crate::batch::grid_lines;
+use crate::json::{self, Json};
+use crate::record::RecordFormat;
+use crate::{Grid, ParseOptions, Rules, Solver};
+
+/// What the audit found for one puzzle, from best to worst.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Unique,
+    MultipleSolutions,
+    NoSolution,
+    /// The puzzle could not be read or breaks the rules, with the reason.
+    Invalid(String),
+}
+
+impl Verdict {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Verdict::Unique => "unique",
+            Verdict::MultipleSolutions => "multiple",
+            Verdict::NoSolution => "unsolvable",
+            Verdict::Invalid(_) => "invalid",
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        *self == Verdict::Unique
+    }
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            Verdict::Unique => write!(f, "unique solution"),
+            Verdict::MultipleSolutions => write!(f, "several solutions"),
+            Verdict::NoSolution => write!(f, "no solution"),
+            Verdict::Invalid(reason) => write!(f, "invalid: {}", reason),
+        }
+    }
+}
+
+/// The verdict on one puzzle of the collection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEntry {
+    /// Position of the puzzle in the collection, counting from 1.
+    pub number: usize,
+    /// The 1-based line of the puzzle, [None] for JSON collections.
+    pub line: Option<usize>,
+    /// The puzzle as written in the collection.
+    pub text: String,
+    pub verdict: Verdict,
+}
+
+/// Result of [audit] with an entry for every puzzle in the order of the collection.
+#[derive(Debug, Clone, Default)]
+pub struct AuditReport {
+    pub entries: Vec<AuditEntry>,
+}
+
+impl AuditReport {
+    /// Entries whose puzzle does not have exactly one solution.
+    pub fn problems(&self) -> impl Iterator<Item = &AuditEntry> {
+        self.entries.iter().filter(|e| !e.verdict.is_ok())
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.problems().next().is_none()
+    }
+
+    /// How many puzzles got a verdict with this name, see [Verdict::name].
+    pub fn count(&self, name: &str) -> usize {
+        self.entries
+            .iter()
+            .filter(|e| e.verdict.name() == name)
+            .count()
+    }
+
+    /// One row per puzzle with the columns number, line, puzzle, verdict and reason.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("number,line,puzzle,verdict,reason\n");
+        for entry in &self.entries {
+            let reason = match &entry.verdict {
+                Verdict::Invalid(reason) => format!("\"{}\"", reason.replace('"', "\"\"")),
+                _ => String::new(),
+            };
+            let _ = writeln!(
+                out,
+                "{},{},\"{}\",{},{}",
+                entry.number,
+                entry.line.map_or(String::new(), |n| n.to_string()),
+                entry.text.replace('"', "\"\""),
+                entry.verdict.name(),
+                reason
+            );
+        }
+        out
+    }
+}
+
+/// The problems followed by the number of puzzles with each verdict.
+impl Display for AuditReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        for entry in self.problems() {
+            match entry.line {
+                Some(line) => write!(f, "Puzzle {} (line {})", entry.number, line)?,
+                None => write!(f, "Puzzle {}", entry.number)?,
+            }
+            writeln!(f, ": {}", entry.verdict)?;
+        }
+        writeln!(f, "Audited: {}", self.entries.len())?;
+        for (label, name) in [
+            ("Unique", "unique"),
+            ("Multiple solutions", "multiple"),
+            ("No solution", "unsolvable"),
+            ("Invalid", "invalid"),
+        ] {
+            writeln!(f, "{}: {}", label, self.count(name))?;
+        }
+        Ok(())
+    }
+}
+
+/// Audits every puzzle of an SDM, JSON or line collection on all threads. Puzzles which cannot
+/// be read get an [Verdict::Invalid] verdict, so only a JSON collection that is not valid JSON
+/// fails as a whole.
+pub fn audit(text: &str, rules: &Rules) -> Result<AuditReport, String> {
+    let puzzles: Vec<(Option<usize>, String)> = match RecordFormat::detect(text) {
+        RecordFormat::Sdm => grid_lines(text)
+            .map(|(n, line)| (Some(n), line.trim().to_string()))
+            .collect(),
+        RecordFormat::Json => {
+            let items = match json::parse(text)? {
+                Json::Array(items) => items,
+                object => vec![object],
+            };
+            items
+                .iter()
+                .map(|item| {
+                    let grid = item.get("grid").and_then(Json::as_str).unwrap_or_default();
+                    (None, grid.to_string())
+                })
+                .collect()
+        }
+    };
+    let solver = Solver::new().with_rules(rules.clone());
+    let entries = puzzles
+        .into_par_iter()
+        .enumerate()
+        .map(|(n, (line, text))| AuditEntry {
+            number: n + 1,
+            line,
+            verdict: judge(&text, &solver),
+            text,
+        })
+        .collect();
+    Ok(AuditReport { entries })
+}
+
+fn judge(text: &str, solver: &Solver) -> Verdict {
+    let options = ParseOptions::strict().single_line(true).validate(false);
+    let grid: Grid = match options.parse(text) {
+        Ok(grid) => grid,
+        Err(e) => return Verdict::Invalid(e.to_string()),
+    };
+    if let Err(e) = grid.validate_with_rules(solver.rules()) {
+        return Verdict::Invalid(e.to_string());
+    }
+    match solver.count_solutions(grid, 2) {
+        0 => Verdict::NoSolution,
+        1 => Verdict::Unique,
+        _ => Verdict::MultipleSolutions,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn audits_each_puzzle() {
+        let unique =
+            "000000010400000000020000000000050407008000300001090000300400200050100000000806000";
+        let several = format!("0{}", &unique[1..]).replace('4', "0");
+        let duplicate = format!("11{}", &unique[2..]);
+        // Any solution with a wrong clue would be a second solution of the unique puzzle
+        let puzzle = crate::parse_grid(unique).unwrap();
+        let solution = crate::solve_recursive(puzzle).unwrap();
+        let mut wrong = puzzle;
+        let candidates = crate::get_candidates(&puzzle, 0, 0);
+        let value = candidates
+            .into_iter()
+            .find(|&v| v != solution.get(0, 0))
+            .unwrap();
+        wrong.set(value, 0, 0);
+        let unsolvable = wrong.to_line();
+        let text = format!(
+            "# title: test\n{}\n{}\n\n{}\n{}\n12345\n",
+            unique, several, duplicate, unsolvable
+        );
+        let report = audit(&text, &Rules::classic()).unwrap();
+        let verdicts: Vec<(usize, Option<usize>, &str)> = report
+            .entries
+            .iter()
+            .map(|e| (e.number, e.line, e.verdict.name()))
+            .collect();
+        assert_eq!(
+            verdicts,
+            [
+                (1, Some(2), "unique"),
+                (2, Some(3), "multiple"),
+                (3, Some(5), "invalid"),
+                (4, Some(6), "unsolvable"),
+                (5, Some(7), "invalid"),
+            ]
+        );
+        assert!(!report.is_ok());
+        assert_eq!(report.to_csv().lines().count(), 6);
+        assert!(report.to_string().contains("Invalid: 2"));
+    }
+}
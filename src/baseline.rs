@@ -0,0 +1,231 @@
+//! Per-puzzle timings of a benchmark run saved as a baseline, and the comparison of a later
+//! run against it to tell a real slowdown from timing noise.
+
+use std::fmt::Display;
+use std::fmt::Error;
+use std::fmt::Formatter;
+use std::fmt::Write;
+use std::time::Duration;
+
+use crate::compare::ComparisonReport;
+use crate::{parse_grid_checked, Grid};
+
+/// Significance level below which [BaselineComparison::is_regression] reports a slowdown.
+pub const SIGNIFICANCE: f64 = 0.05;
+
+/// How long the solver under test took on each puzzle of a run.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Baseline {
+    pub timings: Vec<(Grid, Duration)>,
+}
+
+impl Baseline {
+    /// The timings of configuration B, the solver under test.
+    pub fn from_report(puzzles: &[Grid], report: &ComparisonReport) -> Baseline {
+        Baseline {
+            timings: report
+                .puzzles
+                .iter()
+                .map(|p| (puzzles[p.index], p.time_b))
+                .collect(),
+        }
+    }
+
+    /// One row per puzzle with a header: index, puzzle and time in nanoseconds.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("index,puzzle,time_ns\n");
+        for (index, (grid, time)) in self.timings.iter().enumerate() {
+            let _ = writeln!(out, "{},{},{}", index, grid.to_line(), time.as_nanos());
+        }
+        out
+    }
+
+    /// Reads what [Baseline::to_csv] wrote.
+    pub fn parse(text: &str) -> Result<Baseline, String> {
+        let mut timings = Vec::new();
+        for (n, line) in text.lines().enumerate().skip(1) {
+            let error = |e: String| format!("Line {}: {}", n + 1, e);
+            let fields: Vec<&str> = line.split(',').collect();
+            let [_, puzzle, time] = fields[..] else {
+                return Err(error(format!(
+                    "Expected 3 fields but found {}",
+                    fields.len()
+                )));
+            };
+            let grid = parse_grid_checked(puzzle).map_err(|e| error(e.to_string()))?;
+            let nanos: u64 = time
+                .parse()
+                .map_err(|e| error(format!("Invalid time: {}", e)))?;
+            timings.push((grid, Duration::from_nanos(nanos)));
+        }
+        Ok(Baseline { timings })
+    }
+
+    /// Compares the run `current` on the same puzzles against this baseline.
+    pub fn compare(&self, current: &Baseline) -> Result<BaselineComparison, String> {
+        if self.timings.len() != current.timings.len() {
+            return Err(format!(
+                "The baseline has {} puzzles but the run has {}",
+                self.timings.len(),
+                current.timings.len()
+            ));
+        }
+        if let Some(index) =
+            (0..self.timings.len()).find(|&i| self.timings[i].0 != current.timings[i].0)
+        {
+            return Err(format!("Puzzle #{} differs from the baseline", index));
+        }
+        // Ratios are compared on a log scale so twice as slow and twice as fast cancel out
+        let logs: Vec<f64> = self
+            .timings
+            .iter()
+            .zip(&current.timings)
+            .map(|((_, before), (_, after))| {
+                let nanos = |d: &Duration| d.as_nanos().max(1) as f64;
+                (nanos(after) / nanos(before)).ln()
+            })
+            .collect();
+        let ratio = match logs.len() {
+            0 => 1.0,
+            n => (logs.iter().sum::<f64>() / n as f64).exp(),
+        };
+        Ok(BaselineComparison {
+            puzzles: logs.len(),
+            ratio,
+            p_value: signed_rank_p_value(&logs),
+        })
+    }
+}
+
+/// Result of [Baseline::compare].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BaselineComparison {
+    pub puzzles: usize,
+    /// Geometric mean of the time of each puzzle relative to the baseline. Above 1 means slower.
+    pub ratio: f64,
+    /// Two-sided p-value of the Wilcoxon signed-rank test, the chance of a difference at least
+    /// this consistent if nothing changed. Uses the normal approximation, which needs a dozen
+    /// or so puzzles to be meaningful.
+    pub p_value: f64,
+}
+
+impl BaselineComparison {
+    pub fn is_significant(&self) -> bool {
+        self.p_value < SIGNIFICANCE
+    }
+
+    /// Whether the run is significantly slower than the baseline.
+    pub fn is_regression(&self) -> bool {
+        self.ratio > 1.0 && self.is_significant()
+    }
+}
+
+impl Display for BaselineComparison {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        let (factor, direction) = if self.ratio >= 1.0 {
+            (self.ratio, "slower")
+        } else {
+            (1.0 / self.ratio, "faster")
+        };
+        write!(
+            f,
+            "{:.2}x {} than the baseline over {} puzzles (p = {:.4}, {})",
+            factor,
+            direction,
+            self.puzzles,
+            self.p_value,
+            if self.is_significant() {
+                "significant"
+            } else {
+                "not significant"
+            }
+        )
+    }
+}
+
+// Wilcoxon signed-rank test of the differences being centred on zero
+fn signed_rank_p_value(differences: &[f64]) -> f64 {
+    let mut nonzero: Vec<f64> = differences.iter().copied().filter(|d| *d != 0.0).collect();
+    let n = nonzero.len();
+    if n == 0 {
+        return 1.0;
+    }
+    nonzero.sort_by(|a, b| a.abs().total_cmp(&b.abs()));
+    // Tied magnitudes share the mean of their ranks
+    let mut w = 0.0;
+    let mut ties = 0.0;
+    let mut start = 0;
+    while start < n {
+        let mut end = start + 1;
+        while end < n && nonzero[end].abs() == nonzero[start].abs() {
+            end += 1;
+        }
+        let rank = (start + end + 1) as f64 / 2.0;
+        let tied = (end - start) as f64;
+        ties += tied * tied * tied - tied;
+        w += nonzero[start..end]
+            .iter()
+            .map(|d| d.signum() * rank)
+            .sum::<f64>();
+        start = end;
+    }
+    let n = n as f64;
+    let variance = n * (n + 1.0) * (2.0 * n + 1.0) / 6.0 - ties / 12.0;
+    if variance <= 0.0 {
+        return 1.0;
+    }
+    let z = w.abs() / variance.sqrt();
+    erfc(z / std::f64::consts::SQRT_2).min(1.0)
+}
+
+// Complementary error function for x >= 0, accurate to about 1e-7
+// (Numerical Recipes, erfcc)
+fn erfc(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.5 * x);
+    let poly = -x * x - 1.265_512_23
+        + t * (1.000_023_68
+            + t * (0.374_091_96
+                + t * (0.096_784_18
+                    + t * (-0.186_288_06
+                        + t * (0.278_868_07
+                            + t * (-1.135_203_98
+                                + t * (1.488_515_87 + t * (-0.822_152_23 + t * 0.170_872_77))))))));
+    t * poly.exp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_grid;
+
+    #[test]
+    fn detects_regressions() {
+        let grid = parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap();
+        let run = |micros: &dyn Fn(u64) -> u64| Baseline {
+            timings: (0..20)
+                .map(|i| (grid, Duration::from_micros(micros(i))))
+                .collect(),
+        };
+        let baseline = run(&|i| 100 + i * 10);
+        assert_eq!(Baseline::parse(&baseline.to_csv()), Ok(baseline.clone()));
+
+        let slower = baseline.compare(&run(&|i| 150 + i * 15)).unwrap();
+        assert!((slower.ratio - 1.5).abs() < 1e-9);
+        assert!(slower.is_regression());
+        assert!(slower.to_string().starts_with("1.50x slower"));
+
+        // Noise going both ways is no regression
+        let noisy = baseline
+            .compare(&run(&|i| {
+                if i % 2 == 0 {
+                    90 + i * 10
+                } else {
+                    110 + i * 10
+                }
+            }))
+            .unwrap();
+        assert!(!noisy.is_significant());
+
+        assert!(baseline.compare(&Baseline::default()).is_err());
+    }
+}
@@ -0,0 +1,330 @@
+//! Solving datasets of many puzzles, one 81-character puzzle per line.
+
+use std::fmt::Display;
+use std::fmt::Error;
+use std::fmt::Formatter;
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+use rayon::prelude::*;
+
+use crate::{parse_grid_checked_with_symbols, Grid, SearchOrder, Solver, SymbolSet};
+
+#[derive(Debug, Clone, Copy)]
+pub struct BatchResult {
+    /// Position of the puzzle in the batch.
+    pub index: usize,
+    pub puzzle: Grid,
+    pub solution: Option<Grid>,
+    pub time: Duration,
+}
+
+/// Result of [solve_batch], in the order the puzzles were given.
+#[derive(Debug, Clone, Default)]
+pub struct BatchReport {
+    pub results: Vec<BatchResult>,
+    /// Wall clock time for the whole batch, which is less than [BatchReport::total_time]
+    /// when puzzles are solved on several threads.
+    pub elapsed: Duration,
+}
+
+impl BatchReport {
+    pub fn solved(&self) -> usize {
+        self.results.iter().filter(|r| r.solution.is_some()).count()
+    }
+
+    /// Sum of the time spent on each puzzle.
+    pub fn total_time(&self) -> Duration {
+        self.results.iter().map(|r| r.time).sum()
+    }
+
+    pub fn mean_time(&self) -> Duration {
+        match self.results.len() {
+            0 => Duration::ZERO,
+            n => self.total_time() / n as u32,
+        }
+    }
+
+    /// The solutions one per line, with an empty line for every unsolvable puzzle.
+    pub fn to_lines(&self) -> String {
+        let mut out = String::new();
+        for result in &self.results {
+            if let Some(solution) = &result.solution {
+                out.push_str(&solution.to_line());
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// One row per puzzle with a header: index, puzzle, solution (empty if unsolvable) and time in microseconds.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("index,puzzle,solution,time_us\n");
+        for result in &self.results {
+            let _ = writeln!(
+                out,
+                "{},{},{},{}",
+                result.index,
+                result.puzzle.to_line(),
+                result.solution.map(Grid::to_line).unwrap_or_default(),
+                result.time.as_micros()
+            );
+        }
+        out
+    }
+}
+
+impl Display for BatchReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        writeln!(f, "Puzzles: {}", self.results.len())?;
+        writeln!(f, "Solved: {}", self.solved())?;
+        writeln!(f, "Total time [ms]: {}", self.total_time().as_millis())?;
+        writeln!(
+            f,
+            "Mean time [ms]: {:.3}",
+            self.mean_time().as_secs_f64() * 1000.0
+        )?;
+        writeln!(f, "Time elapsed [ms]: {}", self.elapsed.as_millis())
+    }
+}
+
+/// The lines holding a grid along with their 1-based line number, skipping blank lines and lines starting with '#'.
+pub(crate) fn grid_lines(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(n, line)| (n + 1, line))
+}
+
+/// Parses one puzzle per line, skipping blank lines and lines starting with '#'.
+pub fn parse_batch(text: &str, symbols: &SymbolSet) -> Result<Vec<Grid>, String> {
+    grid_lines(text)
+        .map(|(n, line)| {
+            parse_grid_checked_with_symbols(line, symbols).map_err(|e| format!("Line {}: {}", n, e))
+        })
+        .collect()
+}
+
+pub fn solve_batch(puzzles: impl Iterator<Item = Grid>) -> BatchReport {
+    solve_batch_with(&Solver::new(), puzzles)
+}
+
+/// Solves the puzzles on all threads, each puzzle solved sequentially on one of them.
+/// Spreading whole puzzles over the threads scales much better than parallelizing
+/// within a solve, so [Solver::parallel] is ignored.
+pub fn solve_batch_with(solver: &Solver, puzzles: impl Iterator<Item = Grid>) -> BatchReport {
+    let solver = solver.clone().parallel(false);
+    let puzzles: Vec<Grid> = puzzles.collect();
+    let start = Instant::now();
+    let results = puzzles
+        .par_iter()
+        .enumerate()
+        .map(|(index, &puzzle)| {
+            let start = Instant::now();
+            let solution = solver.solve(puzzle);
+            BatchResult {
+                index,
+                puzzle,
+                solution,
+                time: start.elapsed(),
+            }
+        })
+        .collect();
+    BatchReport {
+        results,
+        elapsed: start.elapsed(),
+    }
+}
+
+/// The search settings [tune_batch] chooses between.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchConfig {
+    pub order: SearchOrder,
+    pub propagate: bool,
+}
+
+impl SearchConfig {
+    pub const ALL: [SearchConfig; 4] = [
+        SearchConfig {
+            order: SearchOrder::FewestCandidates,
+            propagate: true,
+        },
+        SearchConfig {
+            order: SearchOrder::FewestCandidates,
+            propagate: false,
+        },
+        SearchConfig {
+            order: SearchOrder::Lexicographic,
+            propagate: true,
+        },
+        SearchConfig {
+            order: SearchOrder::Lexicographic,
+            propagate: false,
+        },
+    ];
+
+    /// The solver with these settings, keeping its rules.
+    pub fn apply(&self, solver: &Solver) -> Solver {
+        solver
+            .clone()
+            .with_order(self.order)
+            .propagate(self.propagate)
+    }
+}
+
+impl Display for SearchConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        let order = match self.order {
+            SearchOrder::FewestCandidates => "fewest",
+            SearchOrder::Lexicographic => "lexicographic",
+        };
+        let propagation = if self.propagate { "with" } else { "without" };
+        write!(f, "{} order {} propagation", order, propagation)
+    }
+}
+
+/// What [tune_batch] tried on the sample and what it chose.
+#[derive(Debug, Clone)]
+pub struct Tuning {
+    /// Number of puzzles each configuration solved.
+    pub sample: usize,
+    /// Total solving time of each configuration on the sample in the order of
+    /// [SearchConfig::ALL], [None] if it fell behind the fastest one so far and was stopped.
+    pub trials: Vec<(SearchConfig, Option<Duration>)>,
+    pub chosen: SearchConfig,
+}
+
+impl Display for Tuning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        writeln!(f, "Tuned on {} puzzles:", self.sample)?;
+        for (config, time) in &self.trials {
+            match time {
+                Some(time) => writeln!(f, "  {}: {:.3} ms", config, time.as_secs_f64() * 1000.0)?,
+                None => writeln!(f, "  {}: stopped, slower", config)?,
+            }
+        }
+        writeln!(f, "Chosen: {}", self.chosen)
+    }
+}
+
+/// Experimental: solves the first `sample` puzzles with every [SearchConfig] and the rest with
+/// the one that took the least total time, across all threads like [solve_batch_with]. A
+/// configuration is stopped as soon as it has taken longer than the fastest one so far, since
+/// the slow ones can take thousands of times longer on hard puzzles. The report covers all
+/// puzzles, those of the sample with the results of the chosen configuration. How well this
+/// pays off depends on the sample being typical of the batch.
+pub fn tune_batch(
+    solver: &Solver,
+    puzzles: impl Iterator<Item = Grid>,
+    sample: usize,
+) -> (Tuning, BatchReport) {
+    let puzzles: Vec<Grid> = puzzles.collect();
+    let (head, rest) = puzzles.split_at(sample.min(puzzles.len()));
+    let start = Instant::now();
+    let mut trials = Vec::new();
+    let mut best: Option<(SearchConfig, Vec<BatchResult>, Duration)> = None;
+    for config in SearchConfig::ALL {
+        let limit = best.as_ref().map(|(_, _, time)| *time);
+        let results = time_sample(&config.apply(solver), head, limit);
+        let total = results
+            .as_ref()
+            .map(|results| results.iter().map(|r| r.time).sum());
+        trials.push((config, total));
+        if let (Some(results), Some(total)) = (results, total) {
+            if !matches!(limit, Some(limit) if total >= limit) {
+                best = Some((config, results, total));
+            }
+        }
+    }
+    let (chosen, mut results, _) = best.expect("The first configuration has no limit");
+    let remaining = solve_batch_with(&chosen.apply(solver), rest.iter().copied());
+    results.extend(remaining.results.into_iter().map(|result| BatchResult {
+        index: result.index + head.len(),
+        ..result
+    }));
+    let tuning = Tuning {
+        sample: head.len(),
+        trials,
+        chosen,
+    };
+    let report = BatchReport {
+        results,
+        elapsed: start.elapsed(),
+    };
+    (tuning, report)
+}
+
+// Solves the puzzles one after another, giving up once they took longer than `limit` in total
+fn time_sample(
+    solver: &Solver,
+    puzzles: &[Grid],
+    limit: Option<Duration>,
+) -> Option<Vec<BatchResult>> {
+    let deadline = limit.map(|limit| Instant::now() + limit);
+    let mut results = Vec::new();
+    for (index, &puzzle) in puzzles.iter().enumerate() {
+        let start = Instant::now();
+        let solution = solve_until(solver, puzzle, deadline)?;
+        results.push(BatchResult {
+            index,
+            puzzle,
+            solution,
+            time: start.elapsed(),
+        });
+    }
+    Some(results)
+}
+
+// Like Solver::solve but gives up with None once the deadline has passed
+fn solve_until(solver: &Solver, grid: Grid, deadline: Option<Instant>) -> Option<Option<Grid>> {
+    match deadline {
+        Some(deadline) => solver.solve_until(grid, |expanded| {
+            expanded & 1023 == 0 && Instant::now() > deadline
+        }),
+        None => Some(solver.solve(grid)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_grid, solve_recursive};
+
+    #[test]
+    fn solves_batch() {
+        let easy = parse_grid(include_str!("../sample_files/easy.sudoku"))
+            .unwrap()
+            .to_line();
+        let unsolvable = format!("{:.<81}", "12345678.........9");
+        let text = format!("# Two puzzles\n{}\n\n{}\n", easy, unsolvable);
+        let puzzles = parse_batch(&text, &SymbolSet::digits()).unwrap();
+        assert_eq!(puzzles.len(), 2);
+
+        let report = solve_batch(puzzles.iter().copied());
+        assert_eq!(report.solved(), 1);
+        assert_eq!(report.results[0].solution, solve_recursive(puzzles[0]));
+        assert_eq!(report.results[1].solution, None);
+        assert_eq!(report.to_lines().lines().nth(1), Some(""));
+        let csv = report.to_csv();
+        assert_eq!(csv.lines().count(), 3);
+        assert!(csv.lines().nth(2).unwrap().starts_with("1,12345678."));
+
+        assert_eq!(
+            parse_batch("1234", &SymbolSet::digits()),
+            Err("Line 1: Expected 81 cells but found 4".to_string())
+        );
+    }
+
+    #[test]
+    fn tuning_covers_whole_batch() {
+        let easy = parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap();
+        let puzzles = vec![easy; 5];
+        let (tuning, report) = tune_batch(&Solver::new(), puzzles.into_iter(), 2);
+        assert_eq!(tuning.sample, 2);
+        assert_eq!(tuning.trials.len(), SearchConfig::ALL.len());
+        assert!(SearchConfig::ALL.contains(&tuning.chosen));
+        assert_eq!(report.solved(), 5);
+        let indices: Vec<usize> = report.results.iter().map(|r| r.index).collect();
+        assert_eq!(indices, [0, 1, 2, 3, 4]);
+    }
+}
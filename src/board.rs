@@ -0,0 +1,553 @@
+//! Boards of any size n²×n², e.g. 16x16 hexadoku or 25x25.
+//!
+//! [Grid] stays the compact representation for classic 9x9 Sudoku, while [Board] trades
+//! memory for a box size chosen at runtime.
+
+use std::fmt::Display;
+use std::fmt::Error;
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+use rayon::prelude::*;
+
+use crate::{CellValue, Grid, EMPTY_CELL};
+
+/// Largest supported box size, giving 64x64 boards so that candidates fit a `u64`.
+pub const MAX_BOX_SIZE: usize = 8;
+
+/// Symbols for the values 1.., enough for a 36x36 board. Larger boards can only be displayed and parsed
+/// as whitespace separated numbers.
+const SYMBOLS: &str = "123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ@";
+/// Symbols for the values 1.. counting from 0, e.g. 0-F on a 16x16 board.
+const ZERO_BASED_SYMBOLS: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// How the values of a board are written, following the usual hexadoku conventions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BoardSymbols {
+    /// One character per cell from 1-9 followed by A-Z, e.g. 1-G on a 16x16 board, and '.'
+    /// for empty cells. Boards too large for the symbols use [BoardSymbols::Numbers].
+    #[default]
+    Alphanumeric,
+    /// One character per cell from 0-9 followed by A-Z, e.g. 0-F on a 16x16 board, and '.'
+    /// for empty cells.
+    ZeroBased,
+    /// Decimal numbers 1.. separated by whitespace, commas or '|', with 0 or '.' for empty cells.
+    Numbers,
+}
+
+impl BoardSymbols {
+    pub const ALL: [BoardSymbols; 3] = [
+        BoardSymbols::Alphanumeric,
+        BoardSymbols::ZeroBased,
+        BoardSymbols::Numbers,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BoardSymbols::Alphanumeric => "alphanumeric",
+            BoardSymbols::ZeroBased => "zero-based",
+            BoardSymbols::Numbers => "numbers",
+        }
+    }
+
+    // The symbols as characters if there are enough of them for the board
+    fn chars(&self, side: usize) -> Option<&'static str> {
+        let symbols = match self {
+            BoardSymbols::Alphanumeric => SYMBOLS,
+            BoardSymbols::ZeroBased => ZERO_BASED_SYMBOLS,
+            BoardSymbols::Numbers => return None,
+        };
+        (side <= symbols.len()).then_some(symbols)
+    }
+
+    fn symbol(&self, value: CellValue, side: usize) -> String {
+        match (self.chars(side), value) {
+            (Some(_), EMPTY_CELL) => ".".to_string(),
+            (Some(symbols), v) => symbols.chars().nth(v as usize - 1).unwrap().to_string(),
+            (None, v) => v.to_string(),
+        }
+    }
+
+    fn parse(&self, text: &str, side: usize) -> Result<Vec<CellValue>, String> {
+        match self.chars(side) {
+            Some(symbols) => text
+                .chars()
+                .filter(|c| !c.is_whitespace() && !matches!(c, '|' | '+' | '-'))
+                .map(|c| match c {
+                    '.' => Ok(EMPTY_CELL),
+                    c => symbols
+                        .chars()
+                        .position(|s| s == c.to_ascii_uppercase())
+                        .map(|i| i as CellValue + 1)
+                        .ok_or_else(|| format!("Invalid character '{}'", c)),
+                })
+                .collect(),
+            None => text
+                .split(|c: char| c.is_whitespace() || matches!(c, ',' | '|'))
+                // Skip the empty words between separators and the lines of the layout
+                .filter(|word| !word.chars().all(|c| matches!(c, '+' | '-')))
+                .map(|word| match word {
+                    "." => Ok(EMPTY_CELL),
+                    word => word
+                        .parse::<CellValue>()
+                        .map_err(|e| format!("Invalid number '{}': {}", word, e)),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Display for BoardSymbols {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for BoardSymbols {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        BoardSymbols::ALL
+            .iter()
+            .find(|b| b.name().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| format!("Unknown board symbols '{}'", s))
+    }
+}
+
+/// Represents a set of the values 1..=side of a board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WideValueSet(u64);
+
+impl WideValueSet {
+    pub fn empty() -> WideValueSet {
+        WideValueSet(0)
+    }
+
+    /// The values 1..=side.
+    pub fn full(side: usize) -> WideValueSet {
+        debug_assert!(side <= 64);
+        WideValueSet(u64::MAX >> (64 - side))
+    }
+
+    pub fn contains(&self, value: CellValue) -> bool {
+        value != EMPTY_CELL && self.0 & (1 << (value - 1)) != 0
+    }
+
+    pub fn count(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn get_first(&self) -> Option<CellValue> {
+        match self.0 {
+            0 => None,
+            bits => Some(bits.trailing_zeros() as CellValue + 1),
+        }
+    }
+
+    pub fn add(&mut self, value: CellValue) {
+        if value != EMPTY_CELL {
+            self.0 |= 1 << (value - 1);
+        }
+    }
+
+    pub fn remove(&mut self, value: CellValue) {
+        if value != EMPTY_CELL {
+            self.0 &= !(1 << (value - 1));
+        }
+    }
+}
+
+impl Iterator for WideValueSet {
+    type Item = CellValue;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.get_first()?;
+        self.remove(value);
+        Some(value)
+    }
+}
+
+/// A board of `box_size`² × `box_size`² cells. Values run from 1 to `box_size`².
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Board {
+    box_size: usize,
+    cells: Vec<CellValue>,
+}
+
+impl Board {
+    /// An empty board.
+    pub fn new(box_size: usize) -> Board {
+        assert!(
+            (1..=MAX_BOX_SIZE).contains(&box_size),
+            "Box size must be between 1 and {}",
+            MAX_BOX_SIZE
+        );
+        let side = box_size * box_size;
+        Board {
+            box_size,
+            cells: vec![EMPTY_CELL; side * side],
+        }
+    }
+
+    pub fn box_size(&self) -> usize {
+        self.box_size
+    }
+
+    /// Number of cells along a row, which is also the largest value.
+    pub fn side(&self) -> usize {
+        self.box_size * self.box_size
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> CellValue {
+        self.cells[y * self.side() + x]
+    }
+
+    pub fn set(&mut self, value: CellValue, x: usize, y: usize) {
+        debug_assert!(value as usize <= self.side());
+        let side = self.side();
+        self.cells[y * side + x] = value;
+    }
+
+    /// The equivalent compact grid if this is a 9x9 board.
+    pub fn to_grid(&self) -> Option<Grid> {
+        (self.box_size == 3).then(|| Grid::new(&self.cells))
+    }
+
+    /// Parses `box_size`² × `box_size`² cells written with the symbols 1-9 followed by A-Z,
+    /// or '.' for an empty cell. Whitespace and the '|', '+' and '-' of the layout are ignored.
+    /// Boards with more than 36 values are written as whitespace separated numbers with 0 for empty cells.
+    pub fn parse(text: &str, box_size: usize) -> Result<Board, String> {
+        Board::parse_with(text, box_size, BoardSymbols::Alphanumeric)
+    }
+
+    /// Like [Board::parse] but reading the values written with `symbols`.
+    pub fn parse_with(text: &str, box_size: usize, symbols: BoardSymbols) -> Result<Board, String> {
+        if !(1..=MAX_BOX_SIZE).contains(&box_size) {
+            return Err(format!("Box size must be between 1 and {}", MAX_BOX_SIZE));
+        }
+        let mut board = Board::new(box_size);
+        let side = board.side();
+        let values = symbols.parse(text, side)?;
+        if values.len() != side * side {
+            return Err(format!(
+                "Expected {} cells but found {}",
+                side * side,
+                values.len()
+            ));
+        }
+        if let Some(&value) = values.iter().find(|&&v| v as usize > side) {
+            return Err(format!(
+                "Value {} is too large for a {1}x{1} board",
+                value, side
+            ));
+        }
+        board.cells = values;
+        Ok(board)
+    }
+
+    /// Displays the board in the layout of [Grid] using `symbols`, e.g. with 4x4 boxes for
+    /// hexadoku.
+    pub fn display_with(&self, symbols: BoardSymbols) -> BoardDisplay<'_> {
+        BoardDisplay {
+            board: self,
+            symbols,
+        }
+    }
+}
+
+impl From<Grid> for Board {
+    fn from(grid: Grid) -> Board {
+        let mut board = Board::new(3);
+        for y in 0..9 {
+            for x in 0..9 {
+                board.set(grid.get(x, y), x, y);
+            }
+        }
+        board
+    }
+}
+
+impl FromStr for Board {
+    type Err = String;
+
+    /// Parses a board, taking the box size from the number of cells.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cells = s
+            .chars()
+            .filter(|c| !c.is_whitespace() && !matches!(c, '|' | '+' | '-'))
+            .count();
+        let box_size = (1..=MAX_BOX_SIZE)
+            .find(|&n| n.pow(4) == cells)
+            .ok_or_else(|| format!("{} cells do not make a square board", cells))?;
+        Board::parse(s, box_size)
+    }
+}
+
+/// Prints the board in the same layout as [Grid].
+impl Display for Board {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        self.display_with(BoardSymbols::Alphanumeric).fmt(f)
+    }
+}
+
+/// Displays a [Board] with chosen [BoardSymbols], see [Board::display_with].
+pub struct BoardDisplay<'a> {
+    board: &'a Board,
+    symbols: BoardSymbols,
+}
+
+impl Display for BoardDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        let board = self.board;
+        let n = board.box_size;
+        let side = board.side();
+        let symbol = |value: CellValue| self.symbols.symbol(value, side);
+        let width = symbol(side as CellValue).len();
+        let separator = vec!["-".repeat((width + 1) * n); n].join("+");
+        for y in 0..side {
+            if y % n == 0 {
+                writeln!(f, "+{}+", separator)?;
+            }
+            for x in 0..side {
+                if x % n == 0 {
+                    write!(f, "|")?;
+                }
+                write!(f, "{:>width$} ", symbol(board.get(x, y)), width = width)?;
+            }
+            writeln!(f, "|")?;
+        }
+        writeln!(f, "+{}+", separator)
+    }
+}
+
+/// The units and peers of every cell of a board size, shared by all solve states.
+struct Geometry {
+    side: usize,
+    units: Vec<Vec<usize>>,
+    peers: Vec<Vec<usize>>,
+}
+
+impl Geometry {
+    fn new(box_size: usize) -> Geometry {
+        let side = box_size * box_size;
+        let mut units: Vec<Vec<usize>> = Vec::with_capacity(3 * side);
+        for i in 0..side {
+            units.push((0..side).map(|j| i * side + j).collect());
+            units.push((0..side).map(|j| j * side + i).collect());
+            let (sx, sy) = ((i % box_size) * box_size, (i / box_size) * box_size);
+            units.push(
+                (0..side)
+                    .map(|j| (sy + j / box_size) * side + sx + j % box_size)
+                    .collect(),
+            );
+        }
+        let peers = (0..side * side)
+            .map(|i| {
+                let (x, y) = (i % side, i / side);
+                (0..side * side)
+                    .filter(|&j| {
+                        let (px, py) = (j % side, j / side);
+                        j != i
+                            && (px == x
+                                || py == y
+                                || (px / box_size == x / box_size && py / box_size == y / box_size))
+                    })
+                    .collect()
+            })
+            .collect();
+        Geometry { side, units, peers }
+    }
+}
+
+#[derive(Clone)]
+struct BoardState<'a> {
+    cells: Vec<CellValue>,
+    candidates: Vec<WideValueSet>,
+    geometry: &'a Geometry,
+}
+
+impl<'a> BoardState<'a> {
+    fn new(board: &Board, geometry: &'a Geometry) -> Option<Self> {
+        let mut state = BoardState {
+            cells: vec![EMPTY_CELL; board.cells.len()],
+            candidates: vec![WideValueSet::full(geometry.side); board.cells.len()],
+            geometry,
+        };
+        for (i, &value) in board.cells.iter().enumerate() {
+            // Earlier givens may already have forced this one
+            if value != EMPTY_CELL && state.cells[i] != value && !state.assign(i, value) {
+                return None;
+            }
+        }
+        Some(state)
+    }
+
+    /// Places `value` and removes it from the peers, placing any cell left with one candidate.
+    /// Returns false on a contradiction.
+    fn assign(&mut self, i: usize, value: CellValue) -> bool {
+        if !self.candidates[i].contains(value) {
+            return false;
+        }
+        self.cells[i] = value;
+        self.candidates[i] = WideValueSet::empty();
+        // Remove from every peer before propagating, so a propagated single cannot take the value again
+        for &p in &self.geometry.peers[i] {
+            if self.candidates[p].contains(value) {
+                self.candidates[p].remove(value);
+                if self.cells[p] == EMPTY_CELL && self.candidates[p].count() == 0 {
+                    return false;
+                }
+            }
+        }
+        for &p in &self.geometry.peers[i] {
+            if self.cells[p] == EMPTY_CELL && self.candidates[p].count() == 1 {
+                let single = self.candidates[p].get_first().unwrap();
+                if !self.assign(p, single) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Where to branch next: a value with only one place in a unit if there is one, otherwise
+    /// the empty cell with the fewest candidates. [None] if the board is full, and no values
+    /// if the board is stuck.
+    fn next_choice(&self) -> Option<(usize, Vec<CellValue>)> {
+        let i = (0..self.cells.len())
+            .filter(|&i| self.cells[i] == EMPTY_CELL)
+            .min_by_key(|&i| self.candidates[i].count())?;
+        if self.candidates[i].count() <= 1 {
+            return Some((i, self.candidates[i].collect()));
+        }
+        // Hidden singles are found by looking at where each value can still go
+        for unit in &self.geometry.units {
+            let mut placed = WideValueSet::empty();
+            let mut once = WideValueSet::empty();
+            let mut more = WideValueSet::empty();
+            for &c in unit {
+                placed.add(self.cells[c]);
+                more.0 |= once.0 & self.candidates[c].0;
+                once.0 |= self.candidates[c].0;
+            }
+            let missing = WideValueSet(WideValueSet::full(self.geometry.side).0 & !placed.0);
+            if missing.0 & !once.0 != 0 {
+                return Some((i, Vec::new()));
+            }
+            if let Some(value) = WideValueSet(once.0 & !more.0).get_first() {
+                let c = unit.iter().find(|&&c| self.candidates[c].contains(value))?;
+                return Some((*c, vec![value]));
+            }
+        }
+        Some((i, self.candidates[i].collect()))
+    }
+
+    fn branch(&self, i: usize, value: CellValue) -> Option<Self> {
+        let mut branch = self.clone();
+        branch.assign(i, value).then_some(branch)
+    }
+}
+
+fn solve_board_internal(state: BoardState) -> Option<BoardState> {
+    let (i, values) = match state.next_choice() {
+        None => return Some(state),
+        Some(choice) => choice,
+    };
+    values
+        .into_iter()
+        .filter_map(|value| state.branch(i, value))
+        .find_map(solve_board_internal)
+}
+
+fn solve_board_internal_par(state: BoardState) -> Option<BoardState> {
+    let (i, values) = match state.next_choice() {
+        None => return Some(state),
+        Some(choice) => choice,
+    };
+    values
+        .par_iter()
+        .filter_map(|&value| state.branch(i, value))
+        .find_map_first(solve_board_internal_par)
+}
+
+fn solve_board_with(board: &Board, search: fn(BoardState) -> Option<BoardState>) -> Option<Board> {
+    let geometry = Geometry::new(board.box_size);
+    let state = BoardState::new(board, &geometry)?;
+    let solved = search(state)?;
+    Some(Board {
+        box_size: board.box_size,
+        cells: solved.cells,
+    })
+}
+
+pub fn solve_board(board: &Board) -> Option<Board> {
+    solve_board_with(board, solve_board_internal)
+}
+
+pub fn solve_board_par(board: &Board) -> Option<Board> {
+    solve_board_with(board, solve_board_internal_par)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_grid, solve_recursive};
+
+    fn is_valid_solution(board: &Board) -> bool {
+        let geometry = Geometry::new(board.box_size());
+        (0..board.cells.len()).all(|i| {
+            board.cells[i] != EMPTY_CELL
+                && geometry.peers[i]
+                    .iter()
+                    .all(|&p| board.cells[p] != board.cells[i])
+        })
+    }
+
+    #[test]
+    fn solves_classic_board() {
+        let grid = parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap();
+        let board = Board::from(grid);
+        let solved = solve_board(&board).unwrap();
+        assert_eq!(solved.to_grid(), solve_recursive(grid));
+        assert_eq!(solve_board_par(&board), Some(solved));
+    }
+
+    #[test]
+    fn solves_hexadoku() {
+        // A valid 16x16 solution by shifting rows, with every other cell cleared
+        let mut board = Board::new(4);
+        for y in 0..16 {
+            for x in 0..16 {
+                if (x + y) % 2 == 0 {
+                    board.set(((4 * (y % 4) + y / 4 + x) % 16) as CellValue + 1, x, y);
+                }
+            }
+        }
+        let text = board.to_string();
+        assert!(text.contains("G"));
+        assert_eq!(text.parse::<Board>(), Ok(board.clone()));
+
+        let solved = solve_board(&board).unwrap();
+        assert!(is_valid_solution(&solved));
+        for i in 0..board.cells.len() {
+            assert!(board.cells[i] == EMPTY_CELL || board.cells[i] == solved.cells[i]);
+        }
+
+        for symbols in BoardSymbols::ALL {
+            let text = board.display_with(symbols).to_string();
+            assert_eq!(Board::parse_with(&text, 4, symbols), Ok(board.clone()));
+        }
+        let first = "0 1 2 3 | 4 5 6 7 | 8 9 A B | C D E F";
+        let parsed = Board::parse_with(&first.repeat(16), 4, BoardSymbols::ZeroBased).unwrap();
+        assert_eq!((parsed.get(0, 0), parsed.get(15, 0)), (1, 16));
+        let numbers = "1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,.,".repeat(16);
+        let parsed = Board::parse_with(&numbers, 4, BoardSymbols::Numbers).unwrap();
+        assert_eq!((parsed.get(9, 3), parsed.get(15, 3)), (10, EMPTY_CELL));
+        assert!(Board::parse("1", 0).is_err());
+        assert!(Board::parse("1", MAX_BOX_SIZE + 1).is_err());
+
+        board.set(board.get(0, 0), 2, 0);
+        assert_eq!(solve_board(&board), None);
+    }
+}
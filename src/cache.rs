@@ -0,0 +1,218 @@
+//! A cache of solutions and ratings shared between threads, keyed by the
+//! [canonical](crate::transform::canonical) form
+//! of the puzzle so a relabelled or rotated copy of a popular puzzle is a hit too.
+//! Only valid for classic rules, like the transforms it relies on.
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use crate::logic::{grade, Difficulty};
+use crate::transform::{canonical_transform, Transform};
+use crate::{Grid, Solver};
+
+// What is known about a canonical puzzle, in canonical orientation
+#[derive(Debug, Clone, Copy, Default)]
+struct Entry {
+    solution: Option<Option<Grid>>,
+    rating: Option<Difficulty>,
+}
+
+/// Least recently used map, evicting the oldest entry once full.
+#[derive(Debug)]
+struct Lru<K, V> {
+    capacity: usize,
+    // Every entry along with when it was last used
+    entries: HashMap<K, (V, u64)>,
+    by_use: BTreeMap<u64, K>,
+    clock: u64,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Lru<K, V> {
+    fn new(capacity: usize) -> Self {
+        Lru {
+            capacity,
+            entries: HashMap::new(),
+            by_use: BTreeMap::new(),
+            clock: 0,
+        }
+    }
+
+    fn get(&mut self, key: &K) -> Option<V> {
+        self.clock += 1;
+        let (value, used) = self.entries.get_mut(key)?;
+        self.by_use.remove(used);
+        *used = self.clock;
+        self.by_use.insert(self.clock, key.clone());
+        Some(value.clone())
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.clock += 1;
+        if let Some((_, used)) = self.entries.insert(key.clone(), (value, self.clock)) {
+            self.by_use.remove(&used);
+        }
+        self.by_use.insert(self.clock, key);
+        while self.entries.len() > self.capacity {
+            if let Some((_, oldest)) = self.by_use.pop_first() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Maps {
+    entries: Lru<Grid, Entry>,
+    // Grids asked for recently along with their canonical transform, which is slower to
+    // find than most solutions
+    transforms: Lru<Grid, Transform>,
+}
+
+/// Holds up to `capacity` puzzles. Solving and rating happen outside the lock, so threads
+/// asking for different puzzles do not wait on each other.
+#[derive(Debug)]
+pub struct PuzzleCache {
+    solver: Solver,
+    maps: Mutex<Maps>,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl PuzzleCache {
+    pub fn new(capacity: usize) -> PuzzleCache {
+        PuzzleCache {
+            solver: Solver::new(),
+            maps: Mutex::new(Maps {
+                entries: Lru::new(capacity),
+                transforms: Lru::new(capacity),
+            }),
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    /// Solves with `solver` instead of the default, which must use classic rules.
+    pub fn with_solver(mut self, solver: Solver) -> Self {
+        self.solver = solver;
+        self
+    }
+
+    /// The solution of `grid`, or [None] if it has none.
+    pub fn solve(&self, grid: &Grid) -> Option<Grid> {
+        let (key, transform) = self.key(grid);
+        let solution = match self.entry(&key).solution {
+            Some(solution) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                solution
+            }
+            None => {
+                let solution = self.solver.solve(key);
+                self.update(key, |e| e.solution = Some(solution));
+                solution
+            }
+        };
+        solution.map(|s| transform.inverse().apply(&s))
+    }
+
+    /// The [grade] of `grid`, which equivalent puzzles share.
+    pub fn rate(&self, grid: &Grid) -> Difficulty {
+        let (key, _) = self.key(grid);
+        match self.entry(&key).rating {
+            Some(rating) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                rating
+            }
+            None => {
+                let rating = grade(&key);
+                self.update(key, |e| e.rating = Some(rating));
+                rating
+            }
+        }
+    }
+
+    /// Requests answered from the cache.
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Requests which needed solving or rating.
+    pub fn misses(&self) -> usize {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().entries.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Maps> {
+        // A panic while holding the lock cannot leave the maps half updated
+        self.maps.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // The canonical form of `grid` along with the transform into it
+    fn key(&self, grid: &Grid) -> (Grid, Transform) {
+        let known = self.lock().transforms.get(grid);
+        let transform = known.unwrap_or_else(|| {
+            let transform = canonical_transform(grid);
+            self.lock().transforms.insert(*grid, transform);
+            transform
+        });
+        (transform.apply(grid), transform)
+    }
+
+    fn entry(&self, key: &Grid) -> Entry {
+        let entry = self.lock().entries.get(key).unwrap_or_default();
+        entry
+    }
+
+    // Counts a miss and records what was worked out
+    fn update(&self, key: Grid, change: impl FnOnce(&mut Entry)) {
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let mut maps = self.lock();
+        let mut entry = maps.entries.get(&key).unwrap_or_default();
+        change(&mut entry);
+        maps.entries.insert(key, entry);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_grid;
+    use crate::transform::augment;
+    use rayon::prelude::*;
+
+    #[test]
+    fn equivalent_puzzles_hit() {
+        let puzzle = parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap();
+        let variant = augment(&puzzle, 1, 4)[0];
+        let cache = PuzzleCache::new(1);
+        assert_eq!(cache.solve(&puzzle), Solver::new().solve(puzzle));
+        assert_eq!(cache.solve(&variant), Solver::new().solve(variant));
+        assert_eq!(cache.rate(&variant), grade(&puzzle));
+        assert_eq!((cache.hits(), cache.misses()), (1, 2));
+
+        // The only slot goes to the newer puzzle
+        let hard = parse_grid(include_str!("../sample_files/hard.sudoku")).unwrap();
+        cache.solve(&hard);
+        assert_eq!(cache.len(), 1);
+        cache.rate(&puzzle);
+        assert_eq!(cache.misses(), 4);
+
+        // Shared between threads
+        let rated: Vec<Difficulty> = [puzzle, variant]
+            .par_iter()
+            .map(|grid| cache.rate(grid))
+            .collect();
+        assert_eq!(rated[0], rated[1]);
+    }
+}
@@ -0,0 +1,258 @@
+//! Certificates of uniqueness, which anyone can check by following them without searching.
+//!
+//! A certificate is a case split tree. At every leaf, naked and hidden singles either fill
+//! the grid or run into a contradiction. At every branch, the cases cover all candidates of
+//! the cell left after singles. Exactly one leaf may fill the grid, so the puzzle has exactly
+//! one solution.
+
+use std::fmt::Display;
+use std::fmt::Error;
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+use crate::{propagate_with, CellValue, Grid, PropagationDepth, Rules, Solver, NUM_CELLS};
+
+/// A case split tree. Written as `*` for a leaf and `r1c3{4:*,7:*}` for a branch on row 1,
+/// column 3 with the cases 4 and 7.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Proof {
+    /// Singles settle the grid: either it is full or some cell or unit has no options left.
+    Leaf,
+    /// One case per candidate of the cell (x, y).
+    Branch {
+        cell: (usize, usize),
+        cases: Vec<(CellValue, Proof)>,
+    },
+}
+
+impl Proof {
+    /// Number of leaves, a measure of how much guessing the puzzle takes.
+    pub fn leaves(&self) -> usize {
+        match self {
+            Proof::Leaf => 1,
+            Proof::Branch { cases, .. } => cases.iter().map(|(_, p)| p.leaves()).sum(),
+        }
+    }
+}
+
+impl Display for Proof {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            Proof::Leaf => write!(f, "*"),
+            Proof::Branch {
+                cell: (x, y),
+                cases,
+            } => {
+                write!(f, "r{}c{}{{", y + 1, x + 1)?;
+                for (n, (value, proof)) in cases.iter().enumerate() {
+                    if n > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}:{}", value, proof)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+impl FromStr for Proof {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = s.chars().filter(|c| !c.is_whitespace()).collect();
+        let mut pos = 0;
+        let proof = parse_proof(&chars, &mut pos)?;
+        if pos != chars.len() {
+            return Err(format!("Unexpected '{}' at {}", chars[pos], pos + 1));
+        }
+        Ok(proof)
+    }
+}
+
+fn parse_proof(chars: &[char], pos: &mut usize) -> Result<Proof, String> {
+    let expect = |pos: &mut usize, c: char| {
+        if chars.get(*pos) == Some(&c) {
+            *pos += 1;
+            Ok(())
+        } else {
+            Err(format!("Expected '{}' at {}", c, *pos + 1))
+        }
+    };
+    let digit = |pos: &mut usize| match chars.get(*pos).and_then(|c| c.to_digit(10)) {
+        Some(d @ 1..=9) => {
+            *pos += 1;
+            Ok(d as usize)
+        }
+        _ => Err(format!("Expected a digit 1-9 at {}", *pos + 1)),
+    };
+    if chars.get(*pos) == Some(&'*') {
+        *pos += 1;
+        return Ok(Proof::Leaf);
+    }
+    expect(pos, 'r')?;
+    let y = digit(pos)? - 1;
+    expect(pos, 'c')?;
+    let x = digit(pos)? - 1;
+    expect(pos, '{')?;
+    let mut cases = Vec::new();
+    loop {
+        let value = digit(pos)? as CellValue;
+        expect(pos, ':')?;
+        cases.push((value, parse_proof(chars, pos)?));
+        if chars.get(*pos) == Some(&',') {
+            *pos += 1;
+        } else {
+            break;
+        }
+    }
+    expect(pos, '}')?;
+    Ok(Proof::Branch {
+        cell: (x, y),
+        cases,
+    })
+}
+
+/// The solution of a puzzle along with a proof that it is the only one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Certificate {
+    pub solution: Grid,
+    pub proof: Proof,
+}
+
+impl Certificate {
+    /// Checks that the certificate proves `solution` to be the only solution of `grid`.
+    pub fn verify(&self, grid: &Grid, rules: &Rules) -> Result<(), String> {
+        let mut solutions = Vec::new();
+        check(grid, rules, &self.proof, &mut solutions)?;
+        match solutions.as_slice() {
+            [solution] if *solution == self.solution => Ok(()),
+            [_] => Err("The proof leads to a different solution".to_string()),
+            [] => Err("The proof shows the puzzle has no solution".to_string()),
+            _ => Err(format!("The proof leads to {} solutions", solutions.len())),
+        }
+    }
+}
+
+/// A certificate for the unique solution of `grid`, or [None] if it has no or several solutions.
+pub fn certify_unique(grid: &Grid, rules: &Rules) -> Option<Certificate> {
+    // The case split tree is far slower than the solver at finding a second solution
+    if Solver::new()
+        .with_rules(rules.clone())
+        .count_solutions(*grid, 2)
+        != 1
+    {
+        return None;
+    }
+    let mut solutions = Vec::new();
+    let proof = prove(grid, rules, &mut solutions)?;
+    match solutions.as_slice() {
+        [solution] => Some(Certificate {
+            solution: *solution,
+            proof,
+        }),
+        _ => None,
+    }
+}
+
+// Builds the case split tree, giving up once a second solution shows up
+fn prove(grid: &Grid, rules: &Rules, solutions: &mut Vec<Grid>) -> Option<Proof> {
+    let state = propagate_with(grid, rules, PropagationDepth::Singles);
+    if state.contradiction.is_some() {
+        return Some(Proof::Leaf);
+    }
+    if state.is_solved() {
+        solutions.push(state.grid);
+        return (solutions.len() == 1).then_some(Proof::Leaf);
+    }
+    let i = (0..NUM_CELLS)
+        .filter(|&i| state.candidates(i % 9, i / 9).count() > 0)
+        .min_by_key(|&i| state.candidates(i % 9, i / 9).count())?;
+    let (x, y) = (i % 9, i / 9);
+    let mut cases = Vec::new();
+    for value in state.candidates(x, y) {
+        let mut next = state.grid;
+        next.set(value, x, y);
+        cases.push((value, prove(&next, rules, solutions)?));
+    }
+    Some(Proof::Branch {
+        cell: (x, y),
+        cases,
+    })
+}
+
+fn check(
+    grid: &Grid,
+    rules: &Rules,
+    proof: &Proof,
+    solutions: &mut Vec<Grid>,
+) -> Result<(), String> {
+    let state = propagate_with(grid, rules, PropagationDepth::Singles);
+    match proof {
+        Proof::Leaf if state.contradiction.is_some() => Ok(()),
+        Proof::Leaf if state.is_solved() => {
+            solutions.push(state.grid);
+            Ok(())
+        }
+        Proof::Leaf => Err(format!(
+            "Singles do not settle the grid at a leaf:\n{}",
+            state.grid
+        )),
+        Proof::Branch { .. } if state.contradiction.is_some() || state.is_solved() => {
+            Err("Branch on a grid which singles settle".to_string())
+        }
+        Proof::Branch {
+            cell: (x, y),
+            cases,
+        } => {
+            let candidates = state.candidates(*x, *y);
+            let values = cases.iter().map(|(v, _)| *v);
+            if candidates.count() == 0 || !values.clone().eq(candidates) {
+                return Err(format!(
+                    "The cases at row {}, column {} do not match its candidates",
+                    y + 1,
+                    x + 1
+                ));
+            }
+            for (value, proof) in cases {
+                let mut next = state.grid;
+                next.set(*value, *x, *y);
+                check(&next, rules, proof, solutions)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_grid;
+
+    #[test]
+    fn certifies_and_verifies() {
+        let rules = Rules::classic();
+        // AI Escargot
+        let grid = parse_grid(
+            "1....7.9..3..2...8..96..5....53..9...1..8...26....4...3......1..4......7..7...3..",
+        )
+        .unwrap();
+        let certificate = certify_unique(&grid, &rules).unwrap();
+        assert_eq!(certificate.verify(&grid, &rules), Ok(()));
+        assert!(certificate.proof.leaves() > 1);
+
+        let text = certificate.proof.to_string();
+        assert_eq!(text.parse(), Ok(certificate.proof.clone()));
+
+        // Dropping a case leaves the claim unproven
+        let mut broken = certificate.clone();
+        if let Proof::Branch { cases, .. } = &mut broken.proof {
+            cases.pop();
+        }
+        assert!(broken.verify(&grid, &rules).is_err());
+
+        let mut open = grid;
+        open.set(0, 0, 0);
+        assert_eq!(certify_unique(&open, &rules), None);
+    }
+}
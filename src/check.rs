@@ -0,0 +1,457 @@
+//! Checking a player's entries against the solution of a puzzle.
+
+use crate::{
+    get_candidates_with_rules, get_index, CellValue, Grid, Rules, Solver, Unit, EMPTY_CELL,
+    NUM_CELLS,
+};
+
+/// How strictly player entries are judged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckMode {
+    /// An entry is accepted when it matches the solution.
+    Lenient,
+    /// An entry is accepted when it matches the solution and can be deduced from the other entries.
+    Strict,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellStatus {
+    /// The cell is a clue of the puzzle.
+    Given,
+    /// The player has not filled the cell.
+    Empty,
+    /// The entry matches the solution. `justified` tells whether the entry follows from the
+    /// givens and the other correct entries as a naked or hidden single.
+    Correct { justified: bool },
+    /// The entry differs from the solution, or overwrites a given.
+    Incorrect,
+}
+
+impl CellStatus {
+    pub fn is_accepted(&self, mode: CheckMode) -> bool {
+        match (self, mode) {
+            (CellStatus::Incorrect, _) => false,
+            (CellStatus::Correct { justified }, CheckMode::Strict) => *justified,
+            _ => true,
+        }
+    }
+}
+
+/// Result of [Checker::check] with a status for every cell.
+#[derive(Debug, Clone)]
+pub struct CheckReport {
+    cells: [CellStatus; NUM_CELLS],
+}
+
+impl CheckReport {
+    pub fn get(&self, x: usize, y: usize) -> CellStatus {
+        self.cells[get_index(x, y)]
+    }
+
+    /// Coordinates of the entries not accepted under `mode`.
+    pub fn rejected(&self, mode: CheckMode) -> Vec<(usize, usize)> {
+        (0..NUM_CELLS)
+            .filter(|&i| !self.cells[i].is_accepted(mode))
+            .map(|i| (i % 9, i / 9))
+            .collect()
+    }
+
+    pub fn is_accepted(&self, mode: CheckMode) -> bool {
+        self.cells.iter().all(|c| c.is_accepted(mode))
+    }
+}
+
+/// A single action of a player. Pencil marks start out as all values for every empty cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Move {
+    Place {
+        x: usize,
+        y: usize,
+        value: CellValue,
+    },
+    Clear {
+        x: usize,
+        y: usize,
+    },
+    /// Removes a pencil mark.
+    Eliminate {
+        x: usize,
+        y: usize,
+        value: CellValue,
+    },
+    /// Adds back a pencil mark.
+    Restore {
+        x: usize,
+        y: usize,
+        value: CellValue,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MistakeKind {
+    /// A value was placed which is not in the solution. `correct` is the solution value.
+    WrongPlacement { correct: CellValue },
+    /// The solution value of the cell was removed from its pencil marks.
+    EliminatedSolution { correct: CellValue },
+}
+
+/// The earliest move whose effect is still present in the player's state and contradicts the solution.
+/// Undoing `moves[move_index..]` brings the player back to a consistent state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mistake {
+    pub move_index: usize,
+    pub kind: MistakeKind,
+}
+
+/// A peer holding the same value as an entry, in a unit they share.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntryConflict {
+    pub value: CellValue,
+    pub unit: Unit,
+    /// The peer (x, y).
+    pub cell: (usize, usize),
+}
+
+impl EntryConflict {
+    /// E.g. "row 3, column 7 already holds 5 in box 6".
+    pub fn describe(&self, rules: &Rules) -> String {
+        format!(
+            "row {}, column {} already holds {} in {}",
+            self.cell.1 + 1,
+            self.cell.0 + 1,
+            self.value,
+            self.unit.describe(rules)
+        )
+    }
+}
+
+pub fn entry_conflicts(entries: &Grid, x: usize, y: usize) -> Vec<EntryConflict> {
+    entry_conflicts_with_rules(entries, x, y, &Rules::classic())
+}
+
+/// Why the entry at (x, y) breaks the rules: every peer holding the same value, once for each
+/// unit they share. Empty if the cell is empty or its value clashes with nothing.
+pub fn entry_conflicts_with_rules(
+    entries: &Grid,
+    x: usize,
+    y: usize,
+    rules: &Rules,
+) -> Vec<EntryConflict> {
+    let value = entries.get(x, y);
+    if value == EMPTY_CELL {
+        return Vec::new();
+    }
+    let mut conflicts = Vec::new();
+    for unit in rules.units_at(get_index(x, y)) {
+        for i in unit.cells(rules) {
+            let cell = (i % 9, i / 9);
+            if cell != (x, y) && entries.get(cell.0, cell.1) == value {
+                conflicts.push(EntryConflict { value, unit, cell });
+            }
+        }
+    }
+    conflicts
+}
+
+/// Holds a puzzle together with its solution so that partial player grids can be checked repeatedly.
+#[derive(Debug, Clone)]
+pub struct Checker {
+    puzzle: Grid,
+    solution: Grid,
+    rules: Rules,
+}
+
+impl Checker {
+    /// Fails unless the puzzle has exactly one solution, since with several of them a right
+    /// entry could differ from the one solution kept.
+    pub fn new(puzzle: Grid) -> Result<Checker, String> {
+        Self::with_rules(puzzle, Rules::classic())
+    }
+
+    pub fn with_rules(puzzle: Grid, rules: Rules) -> Result<Checker, String> {
+        let solutions = Solver::new().with_rules(rules.clone()).solve_all(puzzle, 2);
+        match solutions[..] {
+            [solution] => Ok(Checker {
+                puzzle,
+                solution,
+                rules,
+            }),
+            [] => Err("The puzzle has no solution".to_string()),
+            _ => Err("The puzzle has several solutions".to_string()),
+        }
+    }
+
+    pub fn puzzle(&self) -> &Grid {
+        &self.puzzle
+    }
+
+    pub fn solution(&self) -> &Grid {
+        &self.solution
+    }
+
+    /// Checks a player grid. The grid holds the givens as well as the player's entries.
+    pub fn check(&self, entries: &Grid) -> CheckReport {
+        // Justification only builds on what is known to be right.
+        let mut known = self.puzzle;
+        for i in 0..NUM_CELLS {
+            let (x, y) = (i % 9, i / 9);
+            if entries.get(x, y) == self.solution.get(x, y) {
+                known.set(entries.get(x, y), x, y);
+            }
+        }
+
+        let mut cells = [CellStatus::Empty; NUM_CELLS];
+        for i in 0..NUM_CELLS {
+            let (x, y) = (i % 9, i / 9);
+            let value = entries.get(x, y);
+            cells[i] = if self.puzzle.get(x, y) != EMPTY_CELL {
+                if value == self.puzzle.get(x, y) {
+                    CellStatus::Given
+                } else {
+                    CellStatus::Incorrect
+                }
+            } else if value == EMPTY_CELL {
+                CellStatus::Empty
+            } else if value != self.solution.get(x, y) {
+                CellStatus::Incorrect
+            } else {
+                CellStatus::Correct {
+                    justified: self.is_justified(&known, value, x, y),
+                }
+            };
+        }
+        CheckReport { cells }
+    }
+
+    /// The peers clashing with the entry at (x, y), see [entry_conflicts_with_rules].
+    pub fn conflicts(&self, entries: &Grid, x: usize, y: usize) -> Vec<EntryConflict> {
+        entry_conflicts_with_rules(entries, x, y, &self.rules)
+    }
+
+    /// Replays the moves and finds the earliest mistake that has not since been undone, if any.
+    /// Moves on given cells are mistakes only if they contradict the given.
+    pub fn find_rollback_point(&self, moves: &[Move]) -> Option<Mistake> {
+        // The move responsible for the current wrong state of each cell
+        let mut placed: [Option<(usize, MistakeKind)>; NUM_CELLS] = [None; NUM_CELLS];
+        let mut eliminated: [Option<(usize, MistakeKind)>; NUM_CELLS] = [None; NUM_CELLS];
+        for (move_index, &m) in moves.iter().enumerate() {
+            match m {
+                Move::Place { x, y, value } => {
+                    let correct = self.solution.get(x, y);
+                    placed[get_index(x, y)] = (value != correct)
+                        .then_some((move_index, MistakeKind::WrongPlacement { correct }));
+                }
+                Move::Clear { x, y } => {
+                    let correct = self.puzzle.get(x, y);
+                    placed[get_index(x, y)] = (correct != EMPTY_CELL)
+                        .then_some((move_index, MistakeKind::WrongPlacement { correct }));
+                }
+                Move::Eliminate { x, y, value } => {
+                    let correct = self.solution.get(x, y);
+                    let index = get_index(x, y);
+                    if value == correct && eliminated[index].is_none() {
+                        eliminated[index] =
+                            Some((move_index, MistakeKind::EliminatedSolution { correct }));
+                    }
+                }
+                Move::Restore { x, y, value } => {
+                    if value == self.solution.get(x, y) {
+                        eliminated[get_index(x, y)] = None;
+                    }
+                }
+            }
+        }
+        placed
+            .iter()
+            .chain(eliminated.iter())
+            .flatten()
+            .min_by_key(|(move_index, _)| *move_index)
+            .map(|&(move_index, kind)| Mistake { move_index, kind })
+    }
+
+    /// Whether `value` at (x, y) is a naked or hidden single in `known` with that cell cleared.
+    fn is_justified(&self, known: &Grid, value: CellValue, x: usize, y: usize) -> bool {
+        let mut grid = *known;
+        grid.set(EMPTY_CELL, x, y);
+        let candidates = get_candidates_with_rules(&grid, x, y, &self.rules);
+        if candidates.count() == 1 && candidates.contains(value) {
+            return true;
+        }
+        let index = get_index(x, y);
+        self.rules.units_at(index).any(|unit| {
+            unit.cells(&self.rules)
+                .iter()
+                .filter(|&&i| i != index)
+                .all(|&i| {
+                    let (cx, cy) = (i % 9, i / 9);
+                    grid.get(cx, cy) != value
+                        && !get_candidates_with_rules(&grid, cx, cy, &self.rules).contains(value)
+                })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_grid;
+
+    #[rustfmt::skip]
+    const PUZZLE: &str = "
+    . . 3 |. 2 . |6 . .
+    9 . . |3 . 5 |. . 1
+    . . 1 |8 . 6 |4 . .
+    ------+------+------
+    . . 8 |1 . 2 |9 . .
+    7 . . |. . . |. . 8
+    . . 6 |7 . 8 |2 . .
+    ------+------+------
+    . . 2 |6 . 9 |5 . .
+    8 . . |2 . 3 |. . 9
+    . . 5 |. 1 . |3 . .
+";
+
+    #[test]
+    fn check_entries() {
+        let puzzle = parse_grid(PUZZLE).unwrap();
+        let checker = Checker::new(puzzle).unwrap();
+        let solution = *checker.solution();
+
+        let mut entries = puzzle;
+        // Overwritten given
+        entries.set(4, 2, 0);
+        // Correct entry that is not yet a single
+        entries.set(solution.get(0, 0), 0, 0);
+        // Wrong entry
+        let wrong = if solution.get(1, 0) == 1 { 2 } else { 1 };
+        entries.set(wrong, 1, 0);
+
+        let report = checker.check(&entries);
+        assert_eq!(report.get(2, 0), CellStatus::Incorrect);
+        assert_eq!(report.get(1, 0), CellStatus::Incorrect);
+        assert_eq!(report.get(0, 0), CellStatus::Correct { justified: false });
+        assert_eq!(report.get(3, 0), CellStatus::Empty);
+        assert_eq!(report.get(4, 0), CellStatus::Given);
+        assert_eq!(report.rejected(CheckMode::Lenient), vec![(1, 0), (2, 0)]);
+        assert_eq!(
+            report.rejected(CheckMode::Strict),
+            vec![(0, 0), (1, 0), (2, 0)]
+        );
+    }
+
+    #[test]
+    fn needs_unique_solution() {
+        let puzzle = parse_grid(PUZZLE).unwrap();
+        let solution = *Checker::new(puzzle).unwrap().solution();
+        // Clearing a band of the solution leaves several ways to fill it
+        let mut open = solution;
+        for i in 0..27 {
+            open.set(EMPTY_CELL, i % 9, i / 9);
+        }
+        assert_eq!(
+            Checker::new(open).unwrap_err(),
+            "The puzzle has several solutions"
+        );
+        let mut broken = puzzle;
+        let (x, y) = (0..NUM_CELLS)
+            .map(|i| (i % 9, i / 9))
+            .find(|&(x, y)| puzzle.get(x, y) == EMPTY_CELL)
+            .unwrap();
+        let wrong = (1..=9).find(|&v| v != solution.get(x, y)).unwrap();
+        broken.set(wrong, x, y);
+        assert!(Checker::new(broken).is_err());
+    }
+
+    #[test]
+    fn rollback_point() {
+        let puzzle = parse_grid(PUZZLE).unwrap();
+        let checker = Checker::new(puzzle).unwrap();
+        let solution = *checker.solution();
+        let right = |x, y| solution.get(x, y);
+        let wrong = |x, y| if solution.get(x, y) == 1 { 2 } else { 1 };
+
+        let moves = [
+            Move::Place {
+                x: 0,
+                y: 0,
+                value: right(0, 0),
+            },
+            Move::Eliminate {
+                x: 1,
+                y: 0,
+                value: wrong(1, 0),
+            },
+            Move::Place {
+                x: 3,
+                y: 0,
+                value: wrong(3, 0),
+            },
+            Move::Eliminate {
+                x: 5,
+                y: 0,
+                value: right(5, 0),
+            },
+            Move::Place {
+                x: 7,
+                y: 0,
+                value: wrong(7, 0),
+            },
+        ];
+        assert_eq!(checker.find_rollback_point(&moves[..2]), None);
+        assert_eq!(
+            checker.find_rollback_point(&moves),
+            Some(Mistake {
+                move_index: 2,
+                kind: MistakeKind::WrongPlacement {
+                    correct: right(3, 0)
+                }
+            })
+        );
+
+        // Fixing the wrong placement moves the rollback point to the elimination
+        let mut fixed = moves.to_vec();
+        fixed.push(Move::Clear { x: 3, y: 0 });
+        assert_eq!(checker.find_rollback_point(&fixed).unwrap().move_index, 3);
+        fixed.push(Move::Restore {
+            x: 5,
+            y: 0,
+            value: right(5, 0),
+        });
+        assert_eq!(checker.find_rollback_point(&fixed).unwrap().move_index, 4);
+    }
+
+    #[test]
+    fn conflicts_name_peers_and_units() {
+        let puzzle = parse_grid(PUZZLE).unwrap();
+        let checker = Checker::new(puzzle).unwrap();
+        let mut entries = puzzle;
+        // Clashes with the given 3 in row 1, column 3 through both the row and the box
+        entries.set(3, 0, 0);
+        let conflicts = checker.conflicts(&entries, 0, 0);
+        assert_eq!(
+            conflicts,
+            vec![
+                EntryConflict {
+                    value: 3,
+                    unit: Unit::Row(0),
+                    cell: (2, 0)
+                },
+                EntryConflict {
+                    value: 3,
+                    unit: Unit::Box(0),
+                    cell: (2, 0)
+                },
+            ]
+        );
+        assert!(checker.conflicts(&entries, 1, 0).is_empty());
+        assert!(checker.conflicts(&puzzle, 2, 0).is_empty());
+    }
+
+    #[test]
+    fn last_cell_is_justified() {
+        let puzzle = parse_grid(PUZZLE).unwrap();
+        let checker = Checker::new(puzzle).unwrap();
+        let report = checker.check(checker.solution());
+        assert!(report.is_accepted(CheckMode::Strict));
+    }
+}
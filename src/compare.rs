@@ -0,0 +1,168 @@
+//! Regression harness running two solver configurations over the same puzzles.
+
+use std::fmt::Display;
+use std::fmt::Error;
+use std::fmt::Formatter;
+use std::time::{Duration, Instant};
+
+use crate::{Grid, Solver};
+
+/// What a solver found for a puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Unsolvable,
+    Unique(Grid),
+    Multiple,
+}
+
+impl Outcome {
+    fn of(solver: &Solver, grid: Grid) -> Outcome {
+        let solutions = solver.solve_all(grid, 2);
+        match solutions.len() {
+            0 => Outcome::Unsolvable,
+            1 => Outcome::Unique(solutions[0]),
+            _ => Outcome::Multiple,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Outcome::Unsolvable => "unsolvable",
+            Outcome::Unique(_) => "unique",
+            Outcome::Multiple => "multiple",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PuzzleComparison {
+    /// Position of the puzzle in the dataset.
+    pub index: usize,
+    pub outcome_a: Outcome,
+    pub outcome_b: Outcome,
+    pub time_a: Duration,
+    pub time_b: Duration,
+}
+
+impl PuzzleComparison {
+    pub fn agrees(&self) -> bool {
+        self.outcome_a == self.outcome_b
+    }
+}
+
+/// Result of [compare_solvers].
+#[derive(Debug, Clone, Default)]
+pub struct ComparisonReport {
+    pub puzzles: Vec<PuzzleComparison>,
+}
+
+impl ComparisonReport {
+    pub fn disagreements(&self) -> impl Iterator<Item = &PuzzleComparison> {
+        self.puzzles.iter().filter(|p| !p.agrees())
+    }
+
+    pub fn all_agree(&self) -> bool {
+        self.disagreements().next().is_none()
+    }
+
+    pub fn total_time_a(&self) -> Duration {
+        self.puzzles.iter().map(|p| p.time_a).sum()
+    }
+
+    pub fn total_time_b(&self) -> Duration {
+        self.puzzles.iter().map(|p| p.time_b).sum()
+    }
+
+    /// How many times faster configuration B was than A in total. Above 1 means B is faster.
+    pub fn speedup(&self) -> f64 {
+        self.total_time_a().as_secs_f64() / self.total_time_b().as_secs_f64().max(f64::EPSILON)
+    }
+
+    /// The puzzles where the relative time difference is the largest, slowest-for-B first.
+    pub fn largest_regressions(&self, count: usize) -> Vec<&PuzzleComparison> {
+        let ratio = |p: &PuzzleComparison| {
+            p.time_b.as_secs_f64() / p.time_a.as_secs_f64().max(f64::EPSILON)
+        };
+        let mut sorted: Vec<&PuzzleComparison> = self.puzzles.iter().collect();
+        sorted.sort_by(|p, q| ratio(q).total_cmp(&ratio(p)));
+        sorted.truncate(count);
+        sorted
+    }
+}
+
+impl Display for ComparisonReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        writeln!(f, "Puzzles: {}", self.puzzles.len())?;
+        writeln!(f, "Disagreements: {}", self.disagreements().count())?;
+        for p in self.disagreements() {
+            writeln!(
+                f,
+                "  #{}: A {} / B {}",
+                p.index,
+                p.outcome_a.name(),
+                p.outcome_b.name()
+            )?;
+        }
+        writeln!(f, "Total time A [ms]: {}", self.total_time_a().as_millis())?;
+        writeln!(f, "Total time B [ms]: {}", self.total_time_b().as_millis())?;
+        writeln!(f, "Speedup of B over A: {:.2}x", self.speedup())
+    }
+}
+
+/// Runs both solvers on every puzzle and records whether they agree on solvability,
+/// uniqueness and the solution itself. Timings cover finding up to two solutions, which
+/// is what deciding uniqueness takes. Puzzles are run one at a time to keep timings comparable.
+pub fn compare_solvers(a: &Solver, b: &Solver, puzzles: &[Grid]) -> ComparisonReport {
+    let timed = |solver: &Solver, grid: Grid| {
+        let start = Instant::now();
+        let outcome = Outcome::of(solver, grid);
+        (outcome, start.elapsed())
+    };
+    let puzzles = puzzles
+        .iter()
+        .enumerate()
+        .map(|(index, &grid)| {
+            let (outcome_a, time_a) = timed(a, grid);
+            let (outcome_b, time_b) = timed(b, grid);
+            PuzzleComparison {
+                index,
+                outcome_a,
+                outcome_b,
+                time_a,
+                time_b,
+            }
+        })
+        .collect();
+    ComparisonReport { puzzles }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_grid, Rules, Variant};
+
+    #[test]
+    fn compare_configurations() {
+        // The top right cell of the second puzzle has no candidates
+        let unsolvable = format!("{:.<81}", "12345678.........9");
+        let puzzles = [
+            parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap(),
+            parse_grid(&unsolvable).unwrap(),
+        ];
+
+        let fewest = Solver::new();
+        let report = compare_solvers(&fewest, &fewest.clone().parallel(true), &puzzles);
+        assert!(report.all_agree());
+        let report = compare_solvers(&fewest.clone().propagate(false), &fewest, &puzzles);
+        assert!(report.all_agree());
+        assert_eq!(report.puzzles[0].outcome_a.name(), "unique");
+        assert_eq!(report.puzzles[1].outcome_a, Outcome::Unsolvable);
+
+        let position = Solver::new().with_rules(Rules::new(&[Variant::Position]));
+        let report = compare_solvers(&fewest, &position, &puzzles[1..]);
+        assert!(report.all_agree());
+        let report = compare_solvers(&fewest, &position, &puzzles[..1]);
+        assert_eq!(report.disagreements().count(), 1);
+        assert!(report.to_string().contains("Disagreements: 1"));
+    }
+}
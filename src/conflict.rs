@@ -0,0 +1,196 @@
+//! Explanations of why a puzzle has no solution, naming the units involved so a setter
+//! knows which part of the rules to look at.
+
+use crate::logic::LogicSolver;
+use crate::{solve_recursive_with_rules, CellValue, Grid, GridError, Rules, Unit, EMPTY_CELL};
+
+/// A dead end reached by logical deductions from the givens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Contradiction {
+    /// The givens already repeat a value in a unit.
+    Duplicate { unit: Unit, value: CellValue },
+    /// Every value is ruled out for the cell (x, y).
+    NoCandidates { cell: (usize, usize) },
+    /// No cell of the unit can hold the value.
+    NoPlace { unit: Unit, value: CellValue },
+}
+
+/// Why a puzzle has no solution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    /// The first contradiction found by the logical solver. [None] if it got stuck before
+    /// reaching one, in which case the contradiction is only found by guessing.
+    pub contradiction: Option<Contradiction>,
+    /// Units whose placed values rule out the options at the contradiction,
+    /// starting with the unit of the contradiction itself.
+    pub units: Vec<Unit>,
+    /// Whether the puzzle has a solution under the classic rules, so the variants are to blame.
+    pub classic_solvable: bool,
+}
+
+impl Conflict {
+    /// The conflict as English sentences, one per line.
+    pub fn text(&self, rules: &Rules) -> String {
+        let names = |units: &[Unit]| match units {
+            [] => "eliminations".to_string(),
+            _ => units
+                .iter()
+                .map(|u| u.describe(rules))
+                .collect::<Vec<String>>()
+                .join(", "),
+        };
+        let mut lines = Vec::new();
+        match self.contradiction {
+            Some(Contradiction::Duplicate { unit, value }) => lines.push(format!(
+                "{} appears twice in {}.",
+                value,
+                unit.describe(rules)
+            )),
+            Some(Contradiction::NoCandidates { cell: (x, y) }) => lines.push(format!(
+                "No value fits row {}, column {}, ruled out by {}.",
+                y + 1,
+                x + 1,
+                names(&self.units)
+            )),
+            Some(Contradiction::NoPlace { unit, value }) => lines.push(format!(
+                "{} has no place in {}, ruled out by {}.",
+                value,
+                unit.describe(rules),
+                names(&self.units[1..])
+            )),
+            None => lines.push("The contradiction needs guessing to find.".to_string()),
+        }
+        if self.classic_solvable {
+            let extra: Vec<Unit> = self
+                .units
+                .iter()
+                .copied()
+                .filter(|u| matches!(u, Unit::Extra(_)))
+                .collect();
+            lines.push(if extra.is_empty() {
+                "The puzzle is solvable without the variant rules.".to_string()
+            } else {
+                format!(
+                    "The puzzle is solvable without the variant rules; check {}.",
+                    names(&extra)
+                )
+            });
+        }
+        lines.join("\n")
+    }
+}
+
+/// Explains why `grid` has no solution under `rules`, or [None] if it has one.
+pub fn explain_conflict(grid: &Grid, rules: &Rules) -> Option<Conflict> {
+    if solve_recursive_with_rules(*grid, rules).is_some() {
+        return None;
+    }
+    let (contradiction, units) = match grid.validate_with_rules(rules) {
+        Err(GridError::Duplicate { value, unit, .. }) => {
+            (Some(Contradiction::Duplicate { unit, value }), vec![unit])
+        }
+        _ => find_contradiction(grid, rules),
+    };
+    let classic_solvable =
+        !rules.is_classic() && solve_recursive_with_rules(*grid, &Rules::classic()).is_some();
+    Some(Conflict {
+        contradiction,
+        units,
+        classic_solvable,
+    })
+}
+
+// Applies logical steps until a cell or unit runs out of options
+fn find_contradiction(grid: &Grid, rules: &Rules) -> (Option<Contradiction>, Vec<Unit>) {
+    let mut solver = LogicSolver::new(grid, rules);
+    loop {
+        if let Some((contradiction, units)) = check(&solver, rules) {
+            return (Some(contradiction), units);
+        }
+        match solver.next_step() {
+            Some(step) => solver.apply(&step),
+            None => return (None, Vec::new()),
+        }
+    }
+}
+
+/// A contradiction in the current state of `solver` along with the units whose placed values
+/// rule out the options.
+pub(crate) fn check(solver: &LogicSolver, rules: &Rules) -> Option<(Contradiction, Vec<Unit>)> {
+    let grid = solver.grid();
+    let empty = |i: usize| grid.get(i % 9, i / 9) == EMPTY_CELL;
+    let holds = |unit: &Unit, value: CellValue| {
+        unit.cells(rules)
+            .iter()
+            .any(|&i| grid.get(i % 9, i / 9) == value)
+    };
+
+    if let Some(i) = (0..81).find(|&i| empty(i) && solver.candidates(i % 9, i / 9).count() == 0) {
+        let units = rules
+            .units_at(i)
+            .filter(|u| (1..=9).any(|value| holds(u, value)))
+            .collect();
+        let contradiction = Contradiction::NoCandidates {
+            cell: (i % 9, i / 9),
+        };
+        return Some((contradiction, units));
+    }
+
+    for unit in rules.units() {
+        let cells = unit.cells(rules);
+        for value in 1..=9 {
+            let possible = cells
+                .iter()
+                .any(|&i| solver.candidates(i % 9, i / 9).contains(value));
+            if possible || holds(&unit, value) {
+                continue;
+            }
+            let mut units = vec![unit];
+            for &i in cells.iter().filter(|&&i| empty(i)) {
+                for other in rules.units_at(i).filter(|u| holds(u, value)) {
+                    if !units.contains(&other) {
+                        units.push(other);
+                    }
+                }
+            }
+            return Some((Contradiction::NoPlace { unit, value }, units));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_grid, Variant, NUM_CELLS};
+
+    #[test]
+    fn names_the_extra_unit() {
+        let mut values = [EMPTY_CELL; NUM_CELLS];
+        // Row 2 and column 2 leave only 9 for r2c2, which position region 5 rules out
+        for (n, x) in (3..9).enumerate() {
+            values[9 + x] = n as CellValue + 1;
+        }
+        values[4 * 9 + 1] = 7;
+        values[5 * 9 + 1] = 8;
+        values[4 * 9 + 4] = 9;
+        let grid = Grid::new(&values);
+        let rules = Rules::new(&[Variant::Position]);
+
+        let conflict = explain_conflict(&grid, &rules).unwrap();
+        assert_eq!(
+            conflict.contradiction,
+            Some(Contradiction::NoCandidates { cell: (1, 1) })
+        );
+        assert_eq!(conflict.units, [Unit::Row(1), Unit::Col(1), Unit::Extra(4)]);
+        assert!(conflict.classic_solvable);
+        assert_eq!(
+            conflict.text(&rules),
+            "No value fits row 2, column 2, ruled out by row 2, column 2, position region 5.\n\
+             The puzzle is solvable without the variant rules; check position region 5."
+        );
+
+        let easy = parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap();
+        assert_eq!(explain_conflict(&easy, &Rules::classic()), None);
+    }
+}
@@ -0,0 +1,245 @@
+//! Converting whole puzzle files between formats, one record at a time.
+
+use std::fmt::Display;
+use std::fmt::Error;
+use std::fmt::Formatter;
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+
+use crate::format::{parse_sdk, to_sdk};
+use crate::record::{self, json_record, sdm_record, PuzzleRecord, SdmReader};
+use crate::{ParseOptions, SymbolSet};
+
+/// The file formats [convert] reads and writes. Only SDM and JSON keep the metadata of
+/// a record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConvertFormat {
+    /// Records with `# key: value` headers, see [record::to_sdm].
+    Sdm,
+    /// An array of record objects, see [record::to_json].
+    Json,
+    /// One puzzle of 81 cells per line.
+    Line,
+    /// Boxed grids separated by blank lines.
+    Grid,
+    /// A single SadMan Sudoku puzzle.
+    Sdk,
+}
+
+impl ConvertFormat {
+    pub const ALL: [ConvertFormat; 5] = [
+        ConvertFormat::Sdm,
+        ConvertFormat::Json,
+        ConvertFormat::Line,
+        ConvertFormat::Grid,
+        ConvertFormat::Sdk,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConvertFormat::Sdm => "sdm",
+            ConvertFormat::Json => "json",
+            ConvertFormat::Line => "line",
+            ConvertFormat::Grid => "grid",
+            ConvertFormat::Sdk => "sdk",
+        }
+    }
+
+    /// The format named by the extension of the path, with .txt read as lines.
+    pub fn for_path(path: &str) -> Option<ConvertFormat> {
+        let extension = path.rsplit_once('.')?.1;
+        if extension.eq_ignore_ascii_case("txt") {
+            return Some(ConvertFormat::Line);
+        }
+        extension.parse().ok()
+    }
+}
+
+impl Display for ConvertFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for ConvertFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ConvertFormat::ALL
+            .iter()
+            .find(|f| f.name().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| format!("Unknown format '{}'", s))
+    }
+}
+
+/// Reads every record of `input` and writes it to `output`, returning how many there were.
+/// SDM, line and grid input is streamed, so only one record is held in memory; JSON and
+/// SDK input is read whole.
+pub fn convert(
+    input: impl BufRead,
+    mut output: impl Write,
+    from: ConvertFormat,
+    to: ConvertFormat,
+) -> Result<usize, String> {
+    let mut count = 0;
+    if to == ConvertFormat::Json {
+        write_all(&mut output, "[")?;
+    }
+    let mut records = read_records(input, from).peekable();
+    while let Some(record) = records.next() {
+        let record = record?;
+        // Fail before writing anything rather than leave a partial file behind
+        if to == ConvertFormat::Sdk && records.peek().is_some() {
+            return Err("SDK files hold a single puzzle".to_string());
+        }
+        let text = match to {
+            ConvertFormat::Sdm => sdm_record(&record, count == 0),
+            ConvertFormat::Json => {
+                format!(
+                    "{}{}",
+                    if count == 0 { "\n" } else { ",\n" },
+                    json_record(&record)
+                )
+            }
+            ConvertFormat::Line => format!("{}\n", record.grid.to_line()),
+            ConvertFormat::Grid if count == 0 => record.grid.to_string(),
+            ConvertFormat::Grid => format!("\n{}", record.grid),
+            ConvertFormat::Sdk => to_sdk(&record.grid, &SymbolSet::digits()),
+        };
+        write_all(&mut output, &text)?;
+        count += 1;
+    }
+    if to == ConvertFormat::Json {
+        write_all(&mut output, if count == 0 { "]\n" } else { "\n]\n" })?;
+    }
+    output.flush().map_err(|e| e.to_string())?;
+    Ok(count)
+}
+
+fn write_all(output: &mut impl Write, text: &str) -> Result<(), String> {
+    output.write_all(text.as_bytes()).map_err(|e| e.to_string())
+}
+
+fn read_records<'a>(
+    mut input: impl BufRead + 'a,
+    from: ConvertFormat,
+) -> Box<dyn Iterator<Item = Result<PuzzleRecord, String>> + 'a> {
+    match from {
+        // Comments are skipped in line files, so SDM covers them
+        ConvertFormat::Sdm | ConvertFormat::Line => Box::new(SdmReader::new(input)),
+        ConvertFormat::Grid => Box::new(GridBlocks {
+            lines: Box::new(input.lines()),
+            pending: Vec::new(),
+        }),
+        ConvertFormat::Json | ConvertFormat::Sdk => {
+            let mut text = String::new();
+            if let Err(e) = input.read_to_string(&mut text) {
+                return Box::new(std::iter::once(Err(e.to_string())));
+            }
+            let records = match from {
+                ConvertFormat::Json => record::parse_json(&text),
+                _ => parse_sdk(&text, &ParseOptions::strict()).map(|g| vec![PuzzleRecord::new(g)]),
+            };
+            match records {
+                Ok(records) => Box::new(records.into_iter().map(Ok)),
+                Err(e) => Box::new(std::iter::once(Err(e))),
+            }
+        }
+    }
+}
+
+// Reads the grids of one blank line separated block at a time
+struct GridBlocks<'a> {
+    lines: Box<dyn Iterator<Item = std::io::Result<String>> + 'a>,
+    pending: Vec<PuzzleRecord>,
+}
+
+impl Iterator for GridBlocks<'_> {
+    type Item = Result<PuzzleRecord, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() {
+            let mut block = String::new();
+            for line in self.lines.by_ref() {
+                match line {
+                    Ok(line) if line.trim().is_empty() && !block.is_empty() => break,
+                    Ok(line) if line.trim().is_empty() => {}
+                    Ok(line) => {
+                        block.push_str(&line);
+                        block.push('\n');
+                    }
+                    Err(e) => return Some(Err(e.to_string())),
+                }
+            }
+            if block.is_empty() {
+                return None;
+            }
+            match ParseOptions::strict().parse_all(&block) {
+                Ok(grids) => {
+                    self.pending = grids.into_iter().rev().map(PuzzleRecord::new).collect()
+                }
+                Err(e) => return Some(Err(e.to_string())),
+            }
+        }
+        self.pending.pop().map(Ok)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_grid;
+    use crate::record::parse_records;
+
+    #[test]
+    fn convert_round_trip() {
+        let puzzle = parse_grid(
+            "000000010400000000020000000000050407008000300001090000300400200050100000000806000",
+        )
+        .unwrap();
+        let mut record = PuzzleRecord::new(puzzle);
+        record.source = Some("test".to_string());
+        let sdm = record::to_sdm(&[record.clone(), PuzzleRecord::new(puzzle)]);
+
+        let mut json = Vec::new();
+        let count = convert(
+            sdm.as_bytes(),
+            &mut json,
+            ConvertFormat::Sdm,
+            ConvertFormat::Json,
+        )
+        .unwrap();
+        assert_eq!(count, 2);
+        let json = String::from_utf8(json).unwrap();
+        assert_eq!(parse_records(&json).unwrap()[0], record);
+
+        let mut grids = Vec::new();
+        convert(
+            json.as_bytes(),
+            &mut grids,
+            ConvertFormat::Json,
+            ConvertFormat::Grid,
+        )
+        .unwrap();
+        let mut lines = Vec::new();
+        convert(
+            grids.as_slice(),
+            &mut lines,
+            ConvertFormat::Grid,
+            ConvertFormat::Line,
+        )
+        .unwrap();
+        let line = format!("{}\n", puzzle.to_line());
+        assert_eq!(String::from_utf8(lines).unwrap(), line.repeat(2));
+        let mut sdk = Vec::new();
+        assert!(convert(
+            sdm.as_bytes(),
+            &mut sdk,
+            ConvertFormat::Sdm,
+            ConvertFormat::Sdk
+        )
+        .is_err());
+        assert!(sdk.is_empty());
+    }
+}
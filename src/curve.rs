@@ -0,0 +1,160 @@
+//! How the difficulty evolves over the course of a logical solve.
+
+use std::fmt::Display;
+use std::fmt::Error;
+use std::fmt::Formatter;
+
+use crate::logic::{Deduction, LogicSolver, Technique};
+use crate::{Grid, Rules, Solver, EMPTY_CELL, NUM_CELLS};
+
+/// What it took to fill one more cell. Eliminations leading up to a placement count towards it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CurveStep {
+    Technique(Technique),
+    /// No known technique applied, so a cell had to be guessed from the solution.
+    Guess,
+}
+
+impl CurveStep {
+    /// Guesses rank above every technique.
+    pub const GUESS_TIER: u8 = 9;
+
+    pub fn tier(&self) -> u8 {
+        match self {
+            CurveStep::Technique(technique) => technique.tier(),
+            CurveStep::Guess => Self::GUESS_TIER,
+        }
+    }
+}
+
+/// The tier needed for every placement of a logical solve, in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DifficultyCurve {
+    pub steps: Vec<CurveStep>,
+}
+
+impl DifficultyCurve {
+    pub fn tiers(&self) -> impl Iterator<Item = u8> + '_ {
+        self.steps.iter().map(|s| s.tier())
+    }
+
+    /// The hardest tier needed anywhere in the solve.
+    pub fn peak(&self) -> u8 {
+        self.tiers().max().unwrap_or(0)
+    }
+
+    /// Number of steps that needed the peak tier.
+    pub fn peak_steps(&self) -> usize {
+        let peak = self.peak();
+        self.tiers().filter(|&t| t == peak).count()
+    }
+
+    pub fn mean_tier(&self) -> f64 {
+        if self.steps.is_empty() {
+            return 0.0;
+        }
+        self.tiers().map(f64::from).sum::<f64>() / self.steps.len() as f64
+    }
+
+    /// Whether the difficulty comes from a few steps far above the rest, rather than
+    /// being spread out over the solve: at most `max_steps` steps need the peak tier,
+    /// and all other steps are at least two tiers easier.
+    pub fn is_bottleneck(&self, max_steps: usize) -> bool {
+        let peak = self.peak();
+        let peak_steps = self.peak_steps();
+        peak_steps <= max_steps
+            && peak_steps < self.steps.len()
+            && self.tiers().filter(|&t| t != peak).all(|t| t + 2 <= peak)
+    }
+}
+
+/// Prints the tier of every step on one line, e.g. "1121119111".
+impl Display for DifficultyCurve {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        for tier in self.tiers() {
+            write!(f, "{}", tier)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn difficulty_curve(grid: &Grid) -> Option<DifficultyCurve> {
+    difficulty_curve_with_rules(grid, &Rules::classic())
+}
+
+/// Solves the grid step by step with the logical techniques, guessing from the solution when stuck.
+/// Returns [None] if the grid has no solution.
+pub fn difficulty_curve_with_rules(grid: &Grid, rules: &Rules) -> Option<DifficultyCurve> {
+    let solution = Solver::new().with_rules(rules.clone()).solve(*grid)?;
+    let mut solver = LogicSolver::new(grid, rules);
+    let mut steps = Vec::new();
+    // Hardest elimination since the last placement
+    let mut pending: Option<Technique> = None;
+    loop {
+        if let Some(step) = solver.next_step() {
+            solver.apply(&step);
+            let technique = pending.map_or(step.technique, |p| p.max(step.technique));
+            match step.deduction {
+                Deduction::Place(_) => {
+                    steps.push(CurveStep::Technique(technique));
+                    pending = None;
+                }
+                Deduction::Eliminate(_) => pending = Some(technique),
+            }
+            continue;
+        }
+        // Guess where it is cheapest: the empty cell with the fewest candidates
+        let guess = (0..NUM_CELLS)
+            .filter(|&i| solver.grid().get(i % 9, i / 9) == EMPTY_CELL)
+            .min_by_key(|&i| solver.candidates(i % 9, i / 9).count());
+        match guess {
+            Some(i) => {
+                let (x, y) = (i % 9, i / 9);
+                solver.place(x, y, solution.get(x, y));
+                steps.push(CurveStep::Guess);
+                pending = None;
+            }
+            None => break,
+        }
+    }
+    Some(DifficultyCurve { steps })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_grid;
+
+    #[test]
+    fn curve_of_easy_and_hard() {
+        let easy = parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap();
+        let curve = difficulty_curve(&easy).unwrap();
+        assert_eq!(curve.steps.len(), 81 - 17);
+        assert_eq!(curve.to_string().len(), curve.steps.len());
+
+        // Gets stuck after 13 placements
+        let hard =
+            "1984........85...3......4....2..9..78...7.9...1.6.2.........25..46.2..79.....4...";
+        let curve = difficulty_curve(&parse_grid(hard).unwrap()).unwrap();
+        assert_eq!(curve.peak(), CurveStep::GUESS_TIER);
+        assert_eq!(curve.steps[13], CurveStep::Guess);
+        assert!(curve.mean_tier() < CurveStep::GUESS_TIER as f64);
+    }
+
+    #[test]
+    fn bottleneck() {
+        let tech = |t| CurveStep::Technique(t);
+        let spike = DifficultyCurve {
+            steps: vec![
+                tech(Technique::HiddenSingle),
+                CurveStep::Guess,
+                tech(Technique::NakedSingle),
+            ],
+        };
+        assert!(spike.is_bottleneck(1));
+        let flat = DifficultyCurve {
+            steps: vec![CurveStep::Guess, CurveStep::Guess, CurveStep::Guess],
+        };
+        assert!(!flat.is_bottleneck(1));
+    }
+}
@@ -0,0 +1,172 @@
+//! Export of (puzzle, solution, rating) examples as flat numeric rows for machine learning,
+//! either as CSV or as a NumPy .npy array of bytes.
+
+use std::fmt::Write;
+use std::str::FromStr;
+
+use rand::Rng;
+use rayon::prelude::*;
+
+use crate::generator::generate_with_solution;
+use crate::logic::{grade_with_rules, Difficulty};
+use crate::{solve_recursive_with_rules, Grid, Rules, EMPTY_CELL, NUM_CELLS};
+
+/// How a cell becomes columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    /// One column per cell holding 0 for empty or the value 1-9.
+    #[default]
+    Digits,
+    /// Ten columns per puzzle cell, one per value and the first for empty, and nine per
+    /// solution cell, with a 1 in the column of the value and 0 elsewhere.
+    OneHot,
+}
+
+impl FromStr for Encoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "digits" => Ok(Encoding::Digits),
+            "one-hot" => Ok(Encoding::OneHot),
+            _ => Err(format!("Unknown encoding '{}'", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Example {
+    pub puzzle: Grid,
+    pub solution: Grid,
+    pub rating: Difficulty,
+}
+
+/// Solves and rates the puzzles, skipping those without a solution.
+pub fn examples(puzzles: &[Grid], rules: &Rules) -> Vec<Example> {
+    puzzles
+        .par_iter()
+        .filter_map(|puzzle| {
+            Some(Example {
+                puzzle: *puzzle,
+                solution: solve_recursive_with_rules(*puzzle, rules)?,
+                rating: grade_with_rules(puzzle, rules),
+            })
+        })
+        .collect()
+}
+
+/// `n` generated examples of the given difficulty.
+pub fn generate_examples(n: usize, difficulty: Difficulty, rng: &mut impl Rng) -> Vec<Example> {
+    (0..n)
+        .map(|_| {
+            let generated = generate_with_solution(difficulty, rng);
+            Example {
+                puzzle: generated.puzzle,
+                solution: generated.solution,
+                rating: difficulty,
+            }
+        })
+        .collect()
+}
+
+/// Column names: puzzle cells `p0`.., solution cells `s0`.. and `rating`, the index of the
+/// difficulty in [Difficulty::ALL]. One-hot columns get the value as suffix, e.g. `p0_3`.
+pub fn columns(encoding: Encoding) -> Vec<String> {
+    let mut columns = Vec::new();
+    for (prefix, values) in [("p", 0..=9), ("s", 1..=9)] {
+        for i in 0..NUM_CELLS {
+            match encoding {
+                Encoding::Digits => columns.push(format!("{}{}", prefix, i)),
+                Encoding::OneHot => {
+                    columns.extend(values.clone().map(|v| format!("{}{}_{}", prefix, i, v)))
+                }
+            }
+        }
+    }
+    columns.push("rating".to_string());
+    columns
+}
+
+/// The example as one row in the order of [columns].
+pub fn encode(example: &Example, encoding: Encoding) -> Vec<u8> {
+    let mut row = Vec::with_capacity(columns(encoding).len());
+    for (grid, first) in [(&example.puzzle, EMPTY_CELL), (&example.solution, 1)] {
+        for i in 0..NUM_CELLS {
+            let value = grid.get(i % 9, i / 9);
+            match encoding {
+                Encoding::Digits => row.push(value),
+                Encoding::OneHot => row.extend((first..=9).map(|v| u8::from(v == value))),
+            }
+        }
+    }
+    let rating = Difficulty::ALL.iter().position(|&d| d == example.rating);
+    row.push(rating.unwrap_or_default() as u8);
+    row
+}
+
+pub fn to_csv(examples: &[Example], encoding: Encoding) -> String {
+    let mut out = columns(encoding).join(",");
+    out.push('\n');
+    for example in examples {
+        let row: Vec<String> = encode(example, encoding)
+            .iter()
+            .map(|v| v.to_string())
+            .collect();
+        let _ = writeln!(out, "{}", row.join(","));
+    }
+    out
+}
+
+/// A version 1.0 .npy file holding an unsigned byte array of one row per example,
+/// for `numpy.load`.
+pub fn to_npy(examples: &[Example], encoding: Encoding) -> Vec<u8> {
+    let width = columns(encoding).len();
+    let mut header = format!(
+        "{{'descr': '|u1', 'fortran_order': False, 'shape': ({}, {}), }}",
+        examples.len(),
+        width
+    );
+    // Magic, version and header length take 10 bytes, and the data starts 64 byte aligned
+    let padding = 64 - (10 + header.len() + 1) % 64;
+    header.push_str(&" ".repeat(padding % 64));
+    header.push('\n');
+
+    let mut out = b"\x93NUMPY\x01\x00".to_vec();
+    out.extend_from_slice(&(header.len() as u16).to_le_bytes());
+    out.extend_from_slice(header.as_bytes());
+    for example in examples {
+        out.extend(encode(example, encoding));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_grid;
+
+    #[test]
+    fn encodes_rows() {
+        let puzzle = parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap();
+        let examples = examples(&[puzzle], &Rules::classic());
+        assert_eq!(examples[0].rating, Difficulty::Medium);
+
+        let digits = encode(&examples[0], Encoding::Digits);
+        assert_eq!(digits.len(), 2 * 81 + 1);
+        assert_eq!(&digits[..2], &[4, 0]);
+        assert_eq!(digits[162], 1);
+        let one_hot = encode(&examples[0], Encoding::OneHot);
+        assert_eq!(one_hot.len(), columns(Encoding::OneHot).len());
+        assert_eq!(&one_hot[..11], &[0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 1]);
+
+        let csv = to_csv(&examples, Encoding::Digits);
+        assert!(csv.starts_with("p0,p1,"));
+        assert!(csv.lines().nth(1).unwrap().starts_with("4,0,"));
+
+        let npy = to_npy(&examples, Encoding::Digits);
+        let data_start = npy.len() - 163;
+        assert_eq!(data_start % 64, 0);
+        assert_eq!(&npy[data_start..], digits.as_slice());
+        assert!(String::from_utf8_lossy(&npy[..data_start]).contains("'shape': (1, 163)"));
+    }
+}
@@ -0,0 +1,107 @@
+//! Errors for rejected grid input, and warnings for input that was read but not as written.
+
+use std::fmt::Display;
+use std::fmt::Error;
+use std::fmt::Formatter;
+
+use crate::{CellValue, Unit};
+
+/// Why a grid was rejected. Positions are 1-based to match what a user sees in their editor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GridError {
+    /// The input did not contain exactly 81 cells.
+    WrongCellCount { found: usize },
+    /// The cells were spread over several lines where a single line was required.
+    MultipleLines { found: usize },
+    /// A character that is neither a cell symbol nor part of the grid layout.
+    InvalidCharacter {
+        character: char,
+        line: usize,
+        column: usize,
+    },
+    /// The same value appears twice in a unit. Cells are (x, y) and 0-based like the rest of the API.
+    Duplicate {
+        value: CellValue,
+        unit: Unit,
+        first: (usize, usize),
+        second: (usize, usize),
+    },
+}
+
+impl Display for GridError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            GridError::WrongCellCount { found } => {
+                write!(f, "Expected 81 cells but found {}", found)
+            }
+            GridError::MultipleLines { found } => {
+                write!(f, "Expected one line but found {}", found)
+            }
+            GridError::InvalidCharacter {
+                character,
+                line,
+                column,
+            } => write!(
+                f,
+                "Invalid character '{}' at line {}, column {}",
+                character, line, column
+            ),
+            GridError::Duplicate {
+                value,
+                unit,
+                first,
+                second,
+            } => write!(
+                f,
+                "Duplicate {} in {}: row {}, column {} and row {}, column {}",
+                value,
+                unit,
+                first.1 + 1,
+                first.0 + 1,
+                second.1 + 1,
+                second.0 + 1
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+/// Something ignored while reading a grid that was read anyway, which may mean the data is
+/// not what its author intended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseWarning {
+    /// More than 81 cells were found and only the first 81 were read.
+    ExtraCells { found: usize },
+    /// Characters which are neither cells nor layout were skipped on a line. Positions are
+    /// 1-based and point at the first one.
+    SkippedCharacters {
+        count: usize,
+        first: char,
+        line: usize,
+        column: usize,
+    },
+}
+
+impl Display for ParseWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            ParseWarning::ExtraCells { found } => write!(
+                f,
+                "Found {} cells, the {} after the first 81 were ignored",
+                found,
+                found - 81
+            ),
+            ParseWarning::SkippedCharacters {
+                count,
+                first,
+                line,
+                column,
+            } => write!(
+                f,
+                "Skipped {} unknown character(s) on line {}, the first being '{}' at column {}",
+                count, line, first, column
+            ),
+        }
+    }
+}
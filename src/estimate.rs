@@ -0,0 +1,86 @@
+//! Quick difficulty estimates from features of a single propagation pass, for triaging
+//! more puzzles than [crate::logic::grade] can rate in time.
+
+use crate::logic::Difficulty;
+use crate::{propagate_with, Grid, PropagationDepth, Rules, EMPTY_CELL, NUM_CELLS};
+
+/// What the estimate is based on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Features {
+    pub clues: usize,
+    /// Cells filled by naked and hidden singles.
+    pub singles: usize,
+    /// Mean number of candidates of the cells still empty after the singles.
+    pub mean_candidates: f64,
+    /// Empty cells with exactly two candidates after the singles.
+    pub bivalue: usize,
+}
+
+pub fn features(grid: &Grid, rules: &Rules) -> Features {
+    let filled = |g: &Grid| {
+        (0..NUM_CELLS)
+            .filter(|&i| g.get(i % 9, i / 9) != EMPTY_CELL)
+            .count()
+    };
+    let result = propagate_with(grid, rules, PropagationDepth::Singles);
+    let counts: Vec<usize> = (0..NUM_CELLS)
+        .filter(|&i| result.grid.get(i % 9, i / 9) == EMPTY_CELL)
+        .map(|i| result.candidates(i % 9, i / 9).count() as usize)
+        .collect();
+    let clues = filled(grid);
+    Features {
+        clues,
+        singles: filled(&result.grid) - clues,
+        mean_candidates: match counts.len() {
+            0 => 0.0,
+            n => counts.iter().sum::<usize>() as f64 / n as f64,
+        },
+        bivalue: counts.iter().filter(|&&c| c == 2).count(),
+    }
+}
+
+pub fn estimate_difficulty(grid: &Grid) -> Difficulty {
+    estimate_difficulty_with_rules(grid, &Rules::classic())
+}
+
+/// Guesses the difficulty [crate::logic::grade_with_rules] would give. Puzzles the singles
+/// solve are always [Difficulty::Easy]; for the others about two in three generated puzzles
+/// get the right bucket, the rest mostly a neighbouring one.
+pub fn estimate_difficulty_with_rules(grid: &Grid, rules: &Rules) -> Difficulty {
+    let features = features(grid, rules);
+    if features.clues + features.singles == NUM_CELLS {
+        return Difficulty::Easy;
+    }
+    // Fitted on generated puzzles: fewer clues and fewer cells down to two candidates
+    // make for harder puzzles
+    let load = features.clues as f64 + features.bivalue as f64 / 4.0;
+    if load > 28.0 {
+        Difficulty::Medium
+    } else if load >= 24.25 {
+        Difficulty::Hard
+    } else {
+        Difficulty::Expert
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::generate;
+    use crate::parse_grid;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn estimates_from_features() {
+        let easy = generate(Difficulty::Easy, &mut StdRng::seed_from_u64(1));
+        assert_eq!(estimate_difficulty(&easy), Difficulty::Easy);
+
+        let grid = parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap();
+        let features = features(&grid, &Rules::classic());
+        assert_eq!(features.clues, 17);
+        assert!(features.clues + features.singles < NUM_CELLS);
+        assert!(features.mean_candidates >= 2.0);
+        assert_ne!(estimate_difficulty(&grid), Difficulty::Easy);
+    }
+}
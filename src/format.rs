@@ -0,0 +1,370 @@
+//! Reading and writing grids in the formats of other Sudoku tools.
+
+use std::fmt::Display;
+use std::fmt::Error;
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+use crate::json::{self, quote, Json};
+use crate::{Grid, ParseOptions, ParseWarning, Rules, SymbolSet, Variant, EMPTY_CELL, NUM_CELLS};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    /// The boxed layout of [Display], read leniently from any layout.
+    #[default]
+    Grid,
+    /// 81 cells on a single line, see [Grid::to_line].
+    Line,
+    /// SadMan Sudoku files: `#` metadata lines, an optional `[Puzzle]` header and 9 rows of 9 cells.
+    Sdk,
+    /// An object with the givens and optionally the solution, both as 81 character strings.
+    Json,
+    /// The JSON of f-puzzles, which SudokuPad also reads, see [parse_fpuzzles].
+    FPuzzles,
+}
+
+impl Format {
+    pub const ALL: [Format; 5] = [
+        Format::Grid,
+        Format::Line,
+        Format::Sdk,
+        Format::Json,
+        Format::FPuzzles,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::Grid => "grid",
+            Format::Line => "line",
+            Format::Sdk => "sdk",
+            Format::Json => "json",
+            Format::FPuzzles => "fpuzzles",
+        }
+    }
+
+    /// Reads the givens of a puzzle.
+    pub fn parse(&self, text: &str, options: &ParseOptions) -> Result<Grid, String> {
+        self.parse_with_warnings(text, options)
+            .map(|(grid, _)| grid)
+    }
+
+    /// Like [Format::parse], also returning what the options let through, see
+    /// [ParseOptions::parse_with_warnings].
+    pub fn parse_with_warnings(
+        &self,
+        text: &str,
+        options: &ParseOptions,
+    ) -> Result<(Grid, Vec<ParseWarning>), String> {
+        match self {
+            Format::Grid => parse(text, options),
+            Format::Line => parse(text, &options.clone().single_line(true)),
+            Format::Sdk => parse(&sdk_rows(text), options),
+            Format::Json => {
+                json_grids(text, options).map(|(givens, _, warnings)| (givens, warnings))
+            }
+            Format::FPuzzles => parse_fpuzzles(text, options).map(|(givens, _)| (givens, vec![])),
+        }
+    }
+
+    /// Writes the puzzle, or its solution if given. Only JSON keeps both.
+    pub fn write(&self, puzzle: &Grid, solution: Option<&Grid>, symbols: &SymbolSet) -> String {
+        let grid = solution.unwrap_or(puzzle);
+        match self {
+            Format::Grid => grid.display_with(symbols).to_string(),
+            Format::Line => format!("{}\n", grid.to_line_with(symbols)),
+            Format::Sdk => to_sdk(grid, symbols),
+            Format::Json => to_json(puzzle, solution, symbols),
+            Format::FPuzzles => to_fpuzzles(puzzle, solution),
+        }
+    }
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Format::ALL
+            .iter()
+            .find(|f| f.name().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| format!("Unknown format '{}'", s))
+    }
+}
+
+fn parse(text: &str, options: &ParseOptions) -> Result<(Grid, Vec<ParseWarning>), String> {
+    options.parse_with_warnings(text).map_err(|e| e.to_string())
+}
+
+pub fn to_sdk(grid: &Grid, symbols: &SymbolSet) -> String {
+    let line = grid.to_line_with(symbols);
+    let chars: Vec<char> = line.chars().collect();
+    chars
+        .chunks(9)
+        .map(|row| format!("{}\n", row.iter().collect::<String>()))
+        .collect()
+}
+
+/// Reads the `[Puzzle]` section of an .sdk file, or the whole file if it has no sections.
+pub fn parse_sdk(text: &str, options: &ParseOptions) -> Result<Grid, String> {
+    parse(&sdk_rows(text), options).map(|(grid, _)| grid)
+}
+
+fn sdk_rows(text: &str) -> String {
+    let mut rows = Vec::new();
+    let mut in_puzzle = true;
+    for line in text.lines().map(str::trim) {
+        if let Some(section) = line.strip_prefix('[') {
+            in_puzzle = section.trim_end_matches(']').eq_ignore_ascii_case("puzzle");
+        } else if in_puzzle && !line.is_empty() && !line.starts_with('#') {
+            rows.push(line);
+        }
+    }
+    rows.join("\n")
+}
+
+/// The givens, and the solution if given, as a JSON object.
+pub fn to_json(puzzle: &Grid, solution: Option<&Grid>, symbols: &SymbolSet) -> String {
+    let mut out = format!("{{\n  \"givens\": {}", quote(&puzzle.to_line_with(symbols)));
+    if let Some(solution) = solution {
+        out.push_str(&format!(
+            ",\n  \"solution\": {}",
+            quote(&solution.to_line_with(symbols))
+        ));
+    }
+    out.push_str("\n}\n");
+    out
+}
+
+/// Reads the givens and the solution, if any, of a JSON object. The solution may be
+/// partial, but has to keep every given.
+pub fn parse_json(text: &str, options: &ParseOptions) -> Result<(Grid, Option<Grid>), String> {
+    json_grids(text, options).map(|(givens, solution, _)| (givens, solution))
+}
+
+// The givens and solution along with the warnings from reading both
+fn json_grids(
+    text: &str,
+    options: &ParseOptions,
+) -> Result<(Grid, Option<Grid>, Vec<ParseWarning>), String> {
+    let json = json::parse(text)?;
+    let mut warnings = Vec::new();
+    let mut field = |key: &str| -> Result<Option<Grid>, String> {
+        match json.get(key) {
+            None | Some(Json::Null) => Ok(None),
+            Some(Json::String(s)) => {
+                let (grid, found) = parse(s, options).map_err(|e| format!("\"{}\": {}", key, e))?;
+                warnings.extend(found);
+                Ok(Some(grid))
+            }
+            Some(_) => Err(format!("\"{}\" must be a string", key)),
+        }
+    };
+    let givens = field("givens")?.ok_or("missing \"givens\"")?;
+    let solution = field("solution")?;
+    if let Some(solution) = &solution {
+        let changed = (0..NUM_CELLS).find(|&i| {
+            let given = givens.get(i % 9, i / 9);
+            given != EMPTY_CELL && solution.get(i % 9, i / 9) != given
+        });
+        if let Some(i) = changed {
+            return Err(format!(
+                "The solution changes the given at row {}, column {}",
+                i / 9 + 1,
+                i % 9 + 1
+            ));
+        }
+    }
+    Ok((givens, solution, warnings))
+}
+
+/// The givens as an f-puzzles object, with the solution if given.
+pub fn to_fpuzzles(puzzle: &Grid, solution: Option<&Grid>) -> String {
+    to_fpuzzles_with_rules(puzzle, solution, &Rules::classic())
+}
+
+/// Like [to_fpuzzles], with the constraints of the variants of `rules` and their
+/// [Rules::summary] as the rules text.
+pub fn to_fpuzzles_with_rules(puzzle: &Grid, solution: Option<&Grid>, rules: &Rules) -> String {
+    let rows: Vec<String> = (0..9)
+        .map(|y| {
+            let cells: Vec<String> = (0..9)
+                .map(|x| match puzzle.get(x, y) {
+                    EMPTY_CELL => "{}".to_string(),
+                    value => format!("{{\"value\":{},\"given\":true}}", value),
+                })
+                .collect();
+            format!("[{}]", cells.join(","))
+        })
+        .collect();
+    let mut out = format!(
+        "{{\"size\":9,\"ruleset\":{},\"grid\":[{}]",
+        quote(&rules.summary()),
+        rows.join(",")
+    );
+    for variant in rules.variants() {
+        match variant {
+            Variant::Position => out.push_str(",\"disjointgroups\":true"),
+        }
+    }
+    if let Some(solution) = solution {
+        let values: Vec<String> = (0..NUM_CELLS)
+            .map(|i| solution.get(i % 9, i / 9).to_string())
+            .collect();
+        out.push_str(&format!(",\"solution\":[{}]", values.join(",")));
+    }
+    out.push_str("}\n");
+    out
+}
+
+// Fields of an f-puzzles object which say nothing about the rules
+const FPUZZLES_METADATA: [&str; 6] = ["size", "grid", "title", "author", "ruleset", "solution"];
+
+/// Reads the givens of an f-puzzles object along with the variants its constraints call
+/// for. Values entered without being given are left out. Any constraint the solver does not
+/// support is an error rather than being dropped, since the puzzle would not be the same
+/// without it; only disjoint groups, which make Position Sudoku, are supported. Links shared
+/// from f-puzzles hold this JSON compressed, so it has to be exported uncompressed.
+pub fn parse_fpuzzles(text: &str, options: &ParseOptions) -> Result<(Grid, Vec<Variant>), String> {
+    let json = json::parse(text)?;
+    let Json::Object(fields) = &json else {
+        return Err("Expected an f-puzzles object".to_string());
+    };
+    match json.get("size") {
+        Some(Json::Number(size)) if *size == 9.0 => {}
+        Some(Json::Number(size)) => {
+            return Err(format!("Only 9x9 puzzles are supported, not {0}x{0}", size))
+        }
+        _ => return Err("missing \"size\"".to_string()),
+    }
+    let rows = match json.get("grid") {
+        Some(Json::Array(rows)) if rows.len() == 9 => rows,
+        _ => return Err("\"grid\" must hold 9 rows".to_string()),
+    };
+
+    let mut variants = Vec::new();
+    let mut unsupported = Vec::new();
+    for (key, value) in fields {
+        let present = match value {
+            Json::Null | Json::Bool(false) => false,
+            Json::Array(items) => !items.is_empty(),
+            _ => true,
+        };
+        if !present || FPUZZLES_METADATA.contains(&key.as_str()) {
+            continue;
+        }
+        match key.as_str() {
+            "disjointgroups" => variants.push(Variant::Position),
+            _ => unsupported.push(key.as_str()),
+        }
+    }
+
+    let mut line = String::with_capacity(NUM_CELLS);
+    for (y, row) in rows.iter().enumerate() {
+        let cells = match row {
+            Json::Array(cells) if cells.len() == 9 => cells,
+            _ => return Err(format!("Row {} of \"grid\" must hold 9 cells", y + 1)),
+        };
+        for (x, cell) in cells.iter().enumerate() {
+            if cell.get("region").is_some() && !unsupported.contains(&"region") {
+                // Cells moved to another region make a jigsaw
+                unsupported.push("region");
+            }
+            let given = cell.get("given") == Some(&Json::Bool(true));
+            match cell.get("value") {
+                Some(Json::Number(value))
+                    if given && value.fract() == 0.0 && (1.0..=9.0).contains(value) =>
+                {
+                    line.push_str(&value.to_string())
+                }
+                Some(_) if given => {
+                    return Err(format!(
+                        "The given at row {}, column {} must be a value from 1 to 9",
+                        y + 1,
+                        x + 1
+                    ))
+                }
+                _ => line.push('.'),
+            }
+        }
+    }
+    if !unsupported.is_empty() {
+        return Err(format!(
+            "Unsupported f-puzzles constraints: {}",
+            unsupported.join(", ")
+        ));
+    }
+    let options = options
+        .clone()
+        .symbols(SymbolSet::digits())
+        .single_line(true);
+    let (grid, _) = parse(&line, &options)?;
+    Ok((grid, variants))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_grid, solve_recursive};
+
+    #[test]
+    fn round_trip() {
+        let puzzle = parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap();
+        let solution = solve_recursive(puzzle).unwrap();
+        let symbols = SymbolSet::digits();
+        let options = ParseOptions::strict();
+        for format in Format::ALL {
+            let text = format.write(&puzzle, None, &symbols);
+            assert_eq!(format.parse(&text, &options), Ok(puzzle), "{}", format);
+        }
+
+        let json = Format::Json.write(&puzzle, Some(&solution), &symbols);
+        assert_eq!(parse_json(&json, &options), Ok((puzzle, Some(solution))));
+        let mut changed = solution;
+        let i = (0..NUM_CELLS)
+            .find(|&i| puzzle.get(i % 9, i / 9) != EMPTY_CELL)
+            .unwrap();
+        changed.set(EMPTY_CELL, i % 9, i / 9);
+        let json = Format::Json.write(&puzzle, Some(&changed), &symbols);
+        assert!(parse_json(&json, &options).is_err());
+
+        let sdk = format!(
+            "#AJohn Doe\n[Puzzle]\n{}[State]\n{}",
+            to_sdk(&puzzle, &symbols),
+            to_sdk(&solution, &symbols)
+        );
+        assert_eq!(parse_sdk(&sdk, &options), Ok(puzzle));
+        assert!(Format::Line
+            .parse(&to_sdk(&puzzle, &symbols), &options)
+            .is_err());
+    }
+
+    #[test]
+    fn fpuzzles_constraints() {
+        let puzzle = parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap();
+        let options = ParseOptions::strict();
+        let classic = to_fpuzzles(&puzzle, None);
+        assert_eq!(parse_fpuzzles(&classic, &options), Ok((puzzle, vec![])));
+
+        let rules = Rules::new(&[Variant::Position]);
+        let position = to_fpuzzles_with_rules(&puzzle, None, &rules);
+        assert_eq!(
+            parse_fpuzzles(&position, &options),
+            Ok((puzzle, vec![Variant::Position]))
+        );
+        let object = classic.trim_end().trim_end_matches('}');
+        let killer = format!(
+            "{},\"diagonal+\":true,\"killercage\":[{{\"cells\":[\"R1C1\"]}}]}}",
+            object
+        );
+        assert_eq!(
+            parse_fpuzzles(&killer, &options),
+            Err("Unsupported f-puzzles constraints: diagonal+, killercage".to_string())
+        );
+    }
+}
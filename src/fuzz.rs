@@ -0,0 +1,113 @@
+//! An entry point for fuzzers: no input may make the crate panic, only be rejected.
+
+use crate::board::{Board, BoardSymbols, MAX_BOX_SIZE};
+use crate::format::Format;
+use crate::record::parse_records;
+use crate::snapshot::CandidateSnapshot;
+use crate::template::Template;
+use crate::{Grid, ParseOptions, Rules, Solver, Variant, EMPTY_CELL, NUM_CELLS};
+
+/// States the solver may expand per grid. Real puzzles take far fewer, so this only cuts off
+/// pathological grids, e.g. nearly empty ones hiding a contradiction.
+const MAX_EXPANDED: u64 = 2_000;
+
+/// Feeds `data` to the parsers of every format and board size, then solves every grid they accept within a
+/// bounded number of steps and checks the solution against it, with and without the variants.
+/// Panics only on a bug in the crate, so it can back any fuzzer, e.g. with cargo-fuzz:
+/// `fuzz_target!(|data: &[u8]| sudoku::fuzz::fuzz_parse_and_solve(data));`
+pub fn fuzz_parse_and_solve(data: &[u8]) {
+    let text = String::from_utf8_lossy(data);
+    let mut grids: Vec<Grid> = Vec::new();
+    for options in [
+        ParseOptions::strict(),
+        ParseOptions::lenient().truncate(true),
+        ParseOptions::strict().single_line(true).validate(false),
+    ] {
+        grids.extend(options.parse(&text));
+        grids.extend(options.parse_all(&text).into_iter().flatten());
+        for format in Format::ALL {
+            grids.extend(format.parse(&text, &options));
+        }
+    }
+    if let Ok(records) = parse_records(&text) {
+        grids.extend(records.iter().map(|r| r.grid));
+    }
+    for box_size in 1..=MAX_BOX_SIZE + 1 {
+        for symbols in BoardSymbols::ALL {
+            let _ = Board::parse_with(&text, box_size, symbols);
+        }
+    }
+    let _ = text.parse::<Template>();
+    let _ = text.parse::<CandidateSnapshot>();
+    // Most formats read the same grid from the same text
+    grids.sort_by_key(|grid| grid.to_line());
+    grids.dedup();
+
+    for rules in [Rules::classic(), Rules::new(&Variant::ALL)] {
+        let solver = Solver::new().with_rules(rules.clone());
+        for grid in grids
+            .iter()
+            .filter(|g| g.validate_with_rules(&rules).is_ok())
+        {
+            if let Some(Some(solution)) = solver.solve_until(*grid, |n| n >= MAX_EXPANDED) {
+                check_solution(grid, &solution, &rules);
+            }
+        }
+    }
+}
+
+fn check_solution(puzzle: &Grid, solution: &Grid, rules: &Rules) {
+    assert!(
+        solution.validate_with_rules(rules).is_ok(),
+        "Solution breaks the rules:\n{}",
+        solution
+    );
+    for i in 0..NUM_CELLS {
+        let (x, y) = (i % 9, i / 9);
+        let given = puzzle.get(x, y);
+        assert_ne!(solution.get(x, y), EMPTY_CELL, "Solution has empty cells");
+        assert!(
+            given == EMPTY_CELL || solution.get(x, y) == given,
+            "Solution changes the given at row {}, column {}",
+            y + 1,
+            x + 1
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn survives_odd_input() {
+        let easy = include_str!("../sample_files/easy.sudoku");
+        let inputs = [
+            easy.as_bytes().to_vec(),
+            include_bytes!("../sample_files/hard_suite.sdm").to_vec(),
+            format!("{}\n{}", easy, easy).into_bytes(),
+            "{\"size\":9,\"grid\":[[{\"value\":99,\"given\":true}]]}".into(),
+            "[{\"grid\":\"1\"}]".into(),
+            ".".repeat(81).into_bytes(),
+            vec![0xff, 0xfe, b'1', b'\n', b'|'],
+            "[".repeat(200_000).into_bytes(),
+        ];
+        for input in &inputs {
+            fuzz_parse_and_solve(input);
+        }
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..200 {
+            let len = rng.gen_range(0..200);
+            let bytes: Vec<u8> = (0..len)
+                .map(|_| {
+                    *b"123456789.0 |-+\n#{}[]\":,x"
+                        .get(rng.gen_range(0..27))
+                        .unwrap_or(&0xc3)
+                })
+                .collect();
+            fuzz_parse_and_solve(&bytes);
+        }
+    }
+}
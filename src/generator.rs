@@ -0,0 +1,289 @@
+//! Generation of puzzles with a unique solution.
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rayon::prelude::*;
+
+use crate::logic::grade;
+use crate::template::Template;
+use crate::unavoidable::{hits_all, unavoidable_sets, UnavoidableSet};
+
+pub use crate::logic::Difficulty;
+use crate::{
+    count_solutions, get_candidates, has_unique_solution, solve_recursive, CellValue, Grid, Rules,
+    Unit, EMPTY_CELL, NUM_CELLS,
+};
+
+/// Keeps digging past the target clue count until the puzzle reaches the difficulty.
+/// Expert keeps removing until no more clues can go.
+fn target_clues(difficulty: Difficulty) -> usize {
+    match difficulty {
+        Difficulty::Easy => 36,
+        Difficulty::Medium => 30,
+        Difficulty::Hard | Difficulty::Expert => 0,
+    }
+}
+
+/// A generated puzzle along with its unique solution.
+#[derive(Debug, Clone, Copy)]
+pub struct Generated {
+    pub puzzle: Grid,
+    pub solution: Grid,
+}
+
+/// Generates a puzzle with a unique solution which [grade] rates as `difficulty`.
+pub fn generate(difficulty: Difficulty, rng: &mut impl Rng) -> Grid {
+    generate_with_solution(difficulty, rng).puzzle
+}
+
+pub fn generate_with_solution(difficulty: Difficulty, rng: &mut impl Rng) -> Generated {
+    // Most solutions can be dug into a puzzle of any difficulty, but the random order
+    // of removal sometimes overshoots or undershoots, so start over until one matches.
+    loop {
+        let solution = random_solution(rng);
+        let puzzle = dig(&solution, difficulty, rng);
+        if grade(&puzzle) == difficulty {
+            return Generated { puzzle, solution };
+        }
+    }
+}
+
+/// Generates a puzzle with a unique solution whose clues are exactly the cells of `template`,
+/// or explains why none was found. Fails at once for templates rejected by [Template::check].
+pub fn generate_from_template(
+    template: &Template,
+    rng: &mut impl Rng,
+) -> Result<Generated, String> {
+    template.check()?;
+    // Random values at the template cells almost never give a unique solution, so change one
+    // clue at a time, keeping changes which do not add solutions, and restart when stuck.
+    const RESTARTS: usize = 10;
+    const STEPS: usize = 2000;
+    const CAP: usize = 100;
+    let cells: Vec<usize> = template.cells().collect();
+    for _ in 0..RESTARTS {
+        let mut puzzle = random_solution(rng);
+        for i in (0..NUM_CELLS).filter(|&i| !template.contains(i % 9, i / 9)) {
+            puzzle.set(EMPTY_CELL, i % 9, i / 9);
+        }
+        let mut solutions = count_solutions(&puzzle, CAP);
+        for _ in 0..STEPS {
+            if solutions == 1 {
+                let solution = solve_recursive(puzzle).expect("The puzzle has a solution");
+                return Ok(Generated { puzzle, solution });
+            }
+            let i = *cells.choose(rng).expect("Checked templates have clues");
+            let (x, y) = (i % 9, i / 9);
+            let old = puzzle.get(x, y);
+            puzzle.set(EMPTY_CELL, x, y);
+            let value = rng.gen_range(1..=9);
+            if value != old && get_candidates(&puzzle, x, y).contains(value) {
+                puzzle.set(value, x, y);
+                let count = count_solutions(&puzzle, CAP);
+                if (1..=solutions).contains(&count) {
+                    solutions = count;
+                    continue;
+                }
+            }
+            puzzle.set(old, x, y);
+        }
+    }
+    Err(format!(
+        "Found no unique puzzle for the template in {} attempts",
+        RESTARTS
+    ))
+}
+
+/// A random complete grid.
+pub fn random_solution(rng: &mut impl Rng) -> Grid {
+    // The three boxes on the diagonal do not constrain each other, so fill them
+    // with random permutations and let the solver complete the rest.
+    let mut grid = Grid::new(&[EMPTY_CELL; NUM_CELLS]);
+    let mut values: Vec<CellValue> = (1..=9).collect();
+    for b in [0, 4, 8] {
+        values.shuffle(rng);
+        for (i, &value) in Unit::Box(b).cells(&Rules::classic()).iter().zip(&values) {
+            grid.set(value, i % 9, i / 9);
+        }
+    }
+    solve_recursive(grid).expect("Diagonal boxes can always be completed")
+}
+
+/// Removes clues from `solution` in random order as long as the solution stays unique and the
+/// puzzle no harder than `difficulty`. Stops once the puzzle is down to the target clues and
+/// graded `difficulty`, or no clue can be removed.
+fn dig(solution: &Grid, difficulty: Difficulty, rng: &mut impl Rng) -> Grid {
+    // A puzzle missing every clue of an unavoidable set has several solutions,
+    // which is much cheaper to check than running the solver.
+    let sets = unavoidable_sets(solution, 12);
+    let mut cells: Vec<usize> = (0..NUM_CELLS).collect();
+    cells.shuffle(rng);
+
+    if difficulty == Difficulty::Expert {
+        // Removes the same clues as the loop below, only faster
+        return minimize_in_order(solution, cells, &sets, true);
+    }
+
+    let mut puzzle = *solution;
+    let mut clues = NUM_CELLS;
+    for i in cells {
+        if clues <= target_clues(difficulty) && grade(&puzzle) == difficulty {
+            break;
+        }
+        let (x, y) = (i % 9, i / 9);
+        let value = puzzle.get(x, y);
+        puzzle.set(EMPTY_CELL, x, y);
+        let acceptable = hits_all(&puzzle, &sets)
+            && (difficulty == Difficulty::Expert || grade(&puzzle) <= difficulty)
+            && has_unique_solution(&puzzle);
+        if acceptable {
+            clues -= 1;
+        } else {
+            puzzle.set(value, x, y);
+        }
+    }
+    puzzle
+}
+
+/// Removes clues in random order as long as the solution stays unique, leaving a minimal
+/// puzzle: removing any of its clues would give it several solutions. Fails if `puzzle` does
+/// not have a unique solution to begin with.
+pub fn minimize(puzzle: &Grid, rng: &mut impl Rng) -> Result<Grid, String> {
+    minimize_internal(puzzle, rng, false)
+}
+
+/// Like [minimize], checking removals on all threads. Given the same random state, both remove
+/// the same clues.
+pub fn minimize_par(puzzle: &Grid, rng: &mut impl Rng) -> Result<Grid, String> {
+    minimize_internal(puzzle, rng, true)
+}
+
+fn minimize_internal(puzzle: &Grid, rng: &mut impl Rng, parallel: bool) -> Result<Grid, String> {
+    let solutions = crate::solve_all(*puzzle, 2);
+    let [solution] = solutions[..] else {
+        return Err("Only a puzzle with a unique solution can be minimized".to_string());
+    };
+    let sets = unavoidable_sets(&solution, 12);
+    let mut clues: Vec<usize> = (0..NUM_CELLS)
+        .filter(|&i| puzzle.get(i % 9, i / 9) != EMPTY_CELL)
+        .collect();
+    clues.shuffle(rng);
+    Ok(minimize_in_order(puzzle, clues, &sets, parallel))
+}
+
+/// Tries to remove each clue of `order` in turn, keeping the removal if the solution stays
+/// unique. In parallel, the next few clues are removed together in every count at once, and
+/// all up to the first count with several solutions go; the clue of that count stays like in
+/// the sequential loop, so both remove the same clues. Removing clues only adds solutions, so
+/// after such a failure every remaining clue is first tried on its own to drop those which can
+/// never go.
+fn minimize_in_order(
+    puzzle: &Grid,
+    order: Vec<usize>,
+    sets: &[UnavoidableSet],
+    parallel: bool,
+) -> Grid {
+    let without = |puzzle: &Grid, cells: &[usize]| -> Option<Grid> {
+        let mut smaller = *puzzle;
+        for &i in cells {
+            smaller.set(EMPTY_CELL, i % 9, i / 9);
+        }
+        (hits_all(&smaller, sets) && has_unique_solution(&smaller)).then_some(smaller)
+    };
+    let mut puzzle = *puzzle;
+    let threads = rayon::current_num_threads();
+    if !parallel || threads == 1 {
+        for i in order {
+            if let Some(smaller) = without(&puzzle, &[i]) {
+                puzzle = smaller;
+            }
+        }
+        return puzzle;
+    }
+
+    let mut pending = order;
+    let mut filter = false;
+    while !pending.is_empty() {
+        if filter {
+            pending = pending
+                .par_iter()
+                .copied()
+                .filter(|&i| without(&puzzle, &[i]).is_some())
+                .collect();
+            if pending.is_empty() {
+                break;
+            }
+        }
+        let tries = pending.len().min(threads);
+        let results: Vec<Option<Grid>> = (1..=tries)
+            .into_par_iter()
+            .map(|count| without(&puzzle, &pending[..count]))
+            .collect();
+        let removed = results.iter().take_while(|r| r.is_some()).count();
+        if removed > 0 {
+            puzzle = results[removed - 1].expect("Counted as unique");
+        }
+        // The clue which made the solution ambiguous stays for good
+        filter = removed < tries;
+        let next = if filter { removed + 1 } else { removed };
+        pending.drain(..next);
+    }
+    puzzle
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn generates_unique_puzzles() {
+        let mut rng = StdRng::seed_from_u64(6);
+        for difficulty in [Difficulty::Easy, Difficulty::Medium] {
+            let generated = generate_with_solution(difficulty, &mut rng);
+            assert_eq!(grade(&generated.puzzle), difficulty);
+            assert!(has_unique_solution(&generated.puzzle));
+            assert_eq!(solve_recursive(generated.puzzle), Some(generated.solution));
+        }
+    }
+
+    #[test]
+    fn parallel_minimizer_matches() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let solution = random_solution(&mut rng);
+        let mut puzzle = solution;
+        for i in (0..NUM_CELLS).step_by(3) {
+            puzzle.set(EMPTY_CELL, i % 9, i / 9);
+        }
+        let minimal = minimize(&puzzle, &mut StdRng::seed_from_u64(4)).unwrap();
+        // However many cores the machine has
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap();
+        let par = pool
+            .install(|| minimize_par(&puzzle, &mut StdRng::seed_from_u64(4)))
+            .unwrap();
+        assert_eq!(minimal.to_string(), par.to_string());
+        assert!(has_unique_solution(&minimal));
+        for i in (0..NUM_CELLS).filter(|&i| minimal.get(i % 9, i / 9) != EMPTY_CELL) {
+            let mut fewer = minimal;
+            fewer.set(EMPTY_CELL, i % 9, i / 9);
+            assert!(!has_unique_solution(&fewer));
+        }
+        assert!(minimize(&Grid::new(&[EMPTY_CELL; NUM_CELLS]), &mut rng).is_err());
+    }
+
+    #[test]
+    fn generates_from_template() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let template: Template =
+            "x...x...x.x.x.x.x...x...x...x..x..x.x.x...x.x.x..x..x...x...x...x.x.x.x.x...x...x"
+                .parse()
+                .unwrap();
+        let generated = generate_from_template(&template, &mut rng).unwrap();
+        assert_eq!(Template::from_grid(&generated.puzzle), template);
+        assert!(has_unique_solution(&generated.puzzle));
+    }
+}
@@ -0,0 +1,156 @@
+//! Heartbeats from long searches with an estimate of the work left, to decide whether to keep
+//! waiting for a very hard instance or give up.
+
+use std::fmt::Display;
+use std::fmt::Error;
+use std::fmt::Formatter;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::{Grid, SolveState, Solver};
+
+/// Random probes added to the estimate on every heartbeat.
+const PROBES: usize = 32;
+
+/// How far a search has come, see [solve_with_heartbeat].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Heartbeat {
+    /// States expanded so far.
+    pub expanded: u64,
+    pub elapsed: Duration,
+    /// Estimated states in the whole search tree, see [estimate_tree_size]. The search stops
+    /// at the first solution, so for a solvable grid this is an upper bound.
+    pub estimated_states: f64,
+}
+
+impl Heartbeat {
+    /// Estimated states left to expand.
+    pub fn remaining(&self) -> f64 {
+        (self.estimated_states - self.expanded as f64).max(0.0)
+    }
+
+    /// Time left at the pace so far, [None] before anything was expanded.
+    pub fn eta(&self) -> Option<Duration> {
+        if self.expanded == 0 {
+            return None;
+        }
+        let per_state = self.elapsed.as_secs_f64() / self.expanded as f64;
+        Duration::try_from_secs_f64(self.remaining() * per_state).ok()
+    }
+}
+
+impl Display for Heartbeat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(
+            f,
+            "Expanded {} of about {:.0} states in {:.1} s",
+            self.expanded,
+            self.estimated_states,
+            self.elapsed.as_secs_f64()
+        )?;
+        match self.eta() {
+            Some(eta) => write!(f, ", about {:.0} s left", eta.as_secs_f64()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Knuth's estimate of the number of states the search for `grid` expands when it has to
+/// search the whole tree: each probe follows random branches from the start and counts the
+/// branches it could have taken, and the estimate is the mean over `probes` of them. It is
+/// unbiased but has a high variance, so more probes give a steadier estimate.
+pub fn estimate_tree_size(solver: &Solver, grid: &Grid, probes: usize, rng: &mut impl Rng) -> f64 {
+    let Some(root) = SolveState::new(*grid, solver) else {
+        return 0.0;
+    };
+    let total: f64 = (0..probes).map(|_| probe(root, rng)).sum();
+    total / probes.max(1) as f64
+}
+
+fn probe(mut state: SolveState, rng: &mut impl Rng) -> f64 {
+    let (mut states, mut width) = (1.0, 1.0);
+    while !state.is_solved() {
+        let Some((cands, x, y)) = state.get_candidate() else {
+            break;
+        };
+        let branches: Vec<SolveState> = cands
+            .into_iter()
+            .filter_map(|cand| state.assign(cand, x, y))
+            .collect();
+        let Some(&next) = branches.choose(rng) else {
+            break;
+        };
+        width *= branches.len() as f64;
+        states += width;
+        state = next;
+    }
+    states
+}
+
+/// Solves sequentially like [Solver::solve], calling `on_heartbeat` every `interval` with the
+/// progress and a fresh estimate of the work left, refined by more probes every time. The
+/// search goes on while it returns true; otherwise it stops and the last heartbeat is returned
+/// as the error.
+pub fn solve_with_heartbeat(
+    solver: &Solver,
+    grid: &Grid,
+    interval: Duration,
+    mut on_heartbeat: impl FnMut(&Heartbeat) -> bool,
+) -> Result<Option<Grid>, Heartbeat> {
+    let start = Instant::now();
+    let mut next_beat = start + interval;
+    // Seeded, so the same search gives the same estimates
+    let mut rng = StdRng::seed_from_u64(0);
+    let (mut probed, mut estimate) = (0, 0.0);
+    let mut last = None;
+    let result = solver.solve_until(*grid, |expanded| {
+        if expanded & 1023 != 0 || Instant::now() < next_beat {
+            return false;
+        }
+        let more = estimate_tree_size(solver, grid, PROBES, &mut rng);
+        estimate = (estimate * probed as f64 + more * PROBES as f64) / (probed + PROBES) as f64;
+        probed += PROBES;
+        let heartbeat = Heartbeat {
+            expanded,
+            elapsed: start.elapsed(),
+            estimated_states: estimate,
+        };
+        last = Some(heartbeat);
+        next_beat = Instant::now() + interval;
+        !on_heartbeat(&heartbeat)
+    });
+    result.ok_or_else(|| last.expect("Only a heartbeat stops the search"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_grid;
+
+    #[test]
+    fn heartbeats_estimate_and_abort() {
+        let grid = parse_grid(include_str!("../sample_files/hard.sudoku")).unwrap();
+        let solver = Solver::new();
+        let mut rng = StdRng::seed_from_u64(1);
+        assert!(estimate_tree_size(&solver, &grid, 100, &mut rng) >= 1.0);
+        let solved = solve_with_heartbeat(&solver, &grid, Duration::from_secs(60), |_| false);
+        assert_eq!(solved, Ok(solver.solve(grid)));
+
+        // Plain backtracking takes long enough on an unsolvable grid to give up on it
+        let impossible = parse_grid(include_str!("../sample_files/impossible.sudoku")).unwrap();
+        let slow = Solver::new().propagate(false);
+        let mut beats = Vec::new();
+        let stopped = solve_with_heartbeat(&slow, &impossible, Duration::ZERO, |beat| {
+            beats.push(*beat);
+            beats.len() < 3
+        });
+        let last = stopped.unwrap_err();
+        assert_eq!(beats.len(), 3);
+        assert_eq!(beats[2], last);
+        assert!(beats.windows(2).all(|w| w[0].expanded < w[1].expanded));
+        assert!(last.estimated_states > 0.0 && last.eta().is_some());
+    }
+}
@@ -0,0 +1,210 @@
+//! Records the order in which cells get their final value while solving and renders it as a heatmap.
+
+use std::fmt::Write;
+
+use crate::{get_index, Grid, SolveState, Solver, EMPTY_CELL, NUM_CELLS};
+
+/// The solution of a puzzle along with the step at which each cell was fixed.
+#[derive(Debug, Clone, Copy)]
+pub struct SolveHeatmap {
+    solution: Grid,
+    // 0 for givens, otherwise 1.. in the order cells were fixed
+    steps: [u8; NUM_CELLS],
+    last_step: u8,
+}
+
+impl SolveHeatmap {
+    pub fn solution(&self) -> &Grid {
+        &self.solution
+    }
+
+    /// The step at which (x, y) was fixed counting from 1, or [None] for a given.
+    pub fn step(&self, x: usize, y: usize) -> Option<usize> {
+        match self.steps[get_index(x, y)] {
+            0 => None,
+            step => Some(step as usize),
+        }
+    }
+
+    /// Number of cells fixed while solving.
+    pub fn steps(&self) -> usize {
+        self.last_step as usize
+    }
+
+    /// How late the cell was fixed, from 0.0 for givens to 1.0 for the last cell.
+    pub fn heat(&self, x: usize, y: usize) -> f64 {
+        match self.step(x, y) {
+            None => 0.0,
+            Some(step) => step as f64 / self.steps().max(1) as f64,
+        }
+    }
+
+    /// The solution with each cell shaded by its heat using 24-bit ANSI background colors.
+    pub fn to_ansi(&self) -> String {
+        let mut out = String::new();
+        for y in 0..9 {
+            if y % 3 == 0 && y > 0 {
+                out.push('\n');
+            }
+            for x in 0..9 {
+                if x % 3 == 0 && x > 0 {
+                    out.push(' ');
+                }
+                let value = self.solution.get(x, y);
+                if self.step(x, y).is_none() {
+                    let _ = write!(out, "\x1b[1m {} \x1b[0m", value);
+                } else {
+                    let (r, g, b) = heat_color(self.heat(x, y));
+                    let _ = write!(out, "\x1b[30;48;2;{};{};{}m {} \x1b[0m", r, g, b, value);
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// The solution as an SVG image with each cell shaded by its heat. Givens are drawn in bold on white.
+    pub fn to_svg(&self) -> String {
+        const CELL: usize = 40;
+        const SIZE: usize = CELL * 9;
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{0}" viewBox="0 0 {0} {0}">"#,
+            SIZE
+        );
+        for y in 0..9 {
+            for x in 0..9 {
+                let (px, py) = (x * CELL, y * CELL);
+                let (fill, weight) = match self.step(x, y) {
+                    None => ("#ffffff".to_string(), "bold"),
+                    Some(_) => {
+                        let (r, g, b) = heat_color(self.heat(x, y));
+                        (format!("#{:02x}{:02x}{:02x}", r, g, b), "normal")
+                    }
+                };
+                let _ = writeln!(
+                    out,
+                    r#"  <rect x="{}" y="{}" width="{}" height="{}" fill="{}" stroke="gray" stroke-width="1"/>"#,
+                    px, py, CELL, CELL, fill
+                );
+                let _ = writeln!(
+                    out,
+                    r#"  <text x="{}" y="{}" font-family="sans-serif" font-size="24" font-weight="{}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                    px + CELL / 2,
+                    py + CELL / 2,
+                    weight,
+                    self.solution.get(x, y)
+                );
+            }
+        }
+        for i in (0..=9).step_by(3) {
+            let p = i * CELL;
+            let _ = writeln!(
+                out,
+                r#"  <line x1="{0}" y1="0" x2="{0}" y2="{1}" stroke="black" stroke-width="3"/>"#,
+                p, SIZE
+            );
+            let _ = writeln!(
+                out,
+                r#"  <line x1="0" y1="{0}" x2="{1}" y2="{0}" stroke="black" stroke-width="3"/>"#,
+                p, SIZE
+            );
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+}
+
+/// Green for early cells through yellow to red for the last ones.
+fn heat_color(heat: f64) -> (u8, u8, u8) {
+    let heat = heat.clamp(0.0, 1.0);
+    if heat < 0.5 {
+        ((heat * 2.0 * 255.0) as u8, 200, 80)
+    } else {
+        (255, ((1.0 - heat) * 2.0 * 200.0) as u8, 80)
+    }
+}
+
+pub fn solve_heatmap(grid: &Grid) -> Option<SolveHeatmap> {
+    solve_heatmap_with(&Solver::new(), grid)
+}
+
+/// Solves the grid sequentially while recording the order of the assignments leading to the solution.
+pub fn solve_heatmap_with(solver: &Solver, grid: &Grid) -> Option<SolveHeatmap> {
+    let mut path = Vec::with_capacity(NUM_CELLS);
+    let state = SolveState::new(*grid, solver)?;
+    // Cells filled by propagating the givens come first
+    path.extend(newly_filled(grid, &state.grid, NUM_CELLS));
+    let solution = trace_recursive(state, &mut path)?;
+
+    let mut steps = [0u8; NUM_CELLS];
+    for (step, &i) in path.iter().enumerate() {
+        steps[i] = step as u8 + 1;
+    }
+    debug_assert!((0..NUM_CELLS).all(|i| {
+        let empty = grid.get(i % 9, i / 9) == EMPTY_CELL;
+        empty == (steps[i] > 0)
+    }));
+    Some(SolveHeatmap {
+        solution,
+        steps,
+        last_step: path.len() as u8,
+    })
+}
+
+fn trace_recursive(solve_state: SolveState, path: &mut Vec<usize>) -> Option<Grid> {
+    if solve_state.is_solved() {
+        return Some(solve_state.grid);
+    }
+    if let Some((cands, x, y)) = solve_state.get_candidate() {
+        for cand in cands {
+            if let Some(branch) = solve_state.assign(cand, x, y) {
+                let len = path.len();
+                let index = get_index(x, y);
+                path.push(index);
+                path.extend(newly_filled(&solve_state.grid, &branch.grid, index));
+                if let Some(solution) = trace_recursive(branch, path) {
+                    return Some(solution);
+                }
+                path.truncate(len);
+            }
+        }
+    }
+    None
+}
+
+/// Cells empty in `before` but filled in `after`, other than `skip`, in row-major order.
+fn newly_filled<'a>(
+    before: &'a Grid,
+    after: &'a Grid,
+    skip: usize,
+) -> impl Iterator<Item = usize> + 'a {
+    (0..NUM_CELLS).filter(move |&i| {
+        let (x, y) = (i % 9, i / 9);
+        i != skip && before.get(x, y) == EMPTY_CELL && after.get(x, y) != EMPTY_CELL
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_grid, solve_recursive};
+
+    #[test]
+    fn records_order() {
+        let grid = parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap();
+        let heatmap = solve_heatmap(&grid).unwrap();
+        assert_eq!(Some(*heatmap.solution()), solve_recursive(grid));
+        assert_eq!(heatmap.steps(), 81 - 17);
+        assert_eq!(heatmap.step(0, 0), None);
+        let mut seen: Vec<usize> = (0..NUM_CELLS)
+            .filter_map(|i| heatmap.step(i % 9, i / 9))
+            .collect();
+        seen.sort_unstable();
+        assert_eq!(seen, (1..=64).collect::<Vec<usize>>());
+
+        assert!(heatmap.to_svg().starts_with("<svg"));
+        assert_eq!(heatmap.to_ansi().lines().count(), 11);
+    }
+}
@@ -0,0 +1,378 @@
+//! Hints for the next step of a human solver, from a gentle nudge to a full explanation.
+
+use crate::logic::{Candidate, Deduction, LogicSolver, Step};
+use crate::{CellValue, Grid, Rules, Unit};
+
+pub use crate::logic::Technique;
+
+/// How much a hint gives away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HintLevel {
+    /// Only where to look, e.g. "Look at box 6."
+    Nudge = 1,
+    /// Where to look and which technique applies.
+    Technique = 2,
+    /// The deduction along with an explanation.
+    Placement = 3,
+}
+
+impl TryFrom<u8> for HintLevel {
+    type Error = String;
+
+    fn try_from(level: u8) -> Result<Self, Self::Error> {
+        match level {
+            1 => Ok(HintLevel::Nudge),
+            2 => Ok(HintLevel::Technique),
+            3 => Ok(HintLevel::Placement),
+            _ => Err(format!("Hint level must be 1, 2 or 3 but got {}", level)),
+        }
+    }
+}
+
+/// The next deduction of a human solver.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hint {
+    pub step: Step,
+}
+
+impl Hint {
+    pub fn technique(&self) -> Technique {
+        self.step.technique
+    }
+
+    /// The cells (x, y) to highlight for the hint at `level`: those of its units for a nudge,
+    /// the pattern for the technique and the pattern along with the changed cells for the
+    /// placement.
+    pub fn cells(&self, level: HintLevel, rules: &Rules) -> Vec<(usize, usize)> {
+        let step = &self.step;
+        let mut cells: Vec<(usize, usize)> = match level {
+            HintLevel::Nudge => step
+                .units
+                .iter()
+                .flat_map(|u| u.cells(rules))
+                .map(|i| (i % 9, i / 9))
+                .collect(),
+            _ => step.cells.clone(),
+        };
+        if level == HintLevel::Placement {
+            match &step.deduction {
+                Deduction::Place(c) => cells.push((c.x, c.y)),
+                Deduction::Eliminate(eliminations) => {
+                    cells.extend(eliminations.iter().map(|c| (c.x, c.y)))
+                }
+            }
+        }
+        let mut unique = Vec::with_capacity(cells.len());
+        for cell in cells {
+            if !unique.contains(&cell) {
+                unique.push(cell);
+            }
+        }
+        unique
+    }
+
+    /// The hint as structured data for applications that word it themselves, e.g. in
+    /// another language. [Hint::text] renders it in English.
+    pub fn message(&self, level: HintLevel) -> Message {
+        let step = &self.step;
+        let values = MessageArg::Values(step.values.into_iter().collect());
+        let units = MessageArg::Units(step.units.clone());
+        let technique = MessageArg::Technique(step.technique);
+        let (key, args) = match level {
+            HintLevel::Nudge => ("hint.nudge", vec![("units", units)]),
+            HintLevel::Technique => (
+                "hint.technique",
+                vec![("technique", technique), ("units", units)],
+            ),
+            HintLevel::Placement => match &step.deduction {
+                Deduction::Place(c) => {
+                    let mut args = vec![
+                        ("value", MessageArg::Value(c.value)),
+                        ("cell", MessageArg::Cell((c.x, c.y))),
+                    ];
+                    // A step built by hand may not match its technique, so it falls back to
+                    // the plain placement
+                    let key = match (step.technique, step.units.first()) {
+                        (Technique::HiddenSingle, Some(&unit)) => {
+                            args.push(("unit", MessageArg::Unit(unit)));
+                            "hint.place.hidden-single"
+                        }
+                        (Technique::NakedSingle, _) => "hint.place.naked-single",
+                        (
+                            Technique::HiddenSingle
+                            | Technique::PointingPair
+                            | Technique::BoxLineReduction
+                            | Technique::NakedPair
+                            | Technique::HiddenPair
+                            | Technique::XWing,
+                            _,
+                        ) => "hint.place",
+                    };
+                    (key, args)
+                }
+                Deduction::Eliminate(eliminations) => {
+                    let key = match step.technique {
+                        Technique::PointingPair | Technique::BoxLineReduction => {
+                            "hint.eliminate.locked-candidates"
+                        }
+                        Technique::NakedPair => "hint.eliminate.naked-pair",
+                        Technique::HiddenPair => "hint.eliminate.hidden-pair",
+                        Technique::XWing => "hint.eliminate.x-wing",
+                        Technique::HiddenSingle | Technique::NakedSingle => "hint.eliminate",
+                    };
+                    let args = vec![
+                        (
+                            "eliminations",
+                            MessageArg::Eliminations(eliminations.clone()),
+                        ),
+                        ("technique", technique),
+                        ("values", values),
+                        ("cells", MessageArg::Cells(step.cells.clone())),
+                        ("units", units),
+                    ];
+                    (key, args)
+                }
+            },
+        };
+        Message { key, args }
+    }
+
+    /// The hint as an English sentence revealing as much as `level` allows.
+    pub fn text(&self, level: HintLevel, rules: &Rules) -> String {
+        self.message(level).english(rules)
+    }
+}
+
+/// A value to fill into a [Message].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MessageArg {
+    Value(CellValue),
+    Values(Vec<CellValue>),
+    /// A cell as (x, y).
+    Cell((usize, usize)),
+    Cells(Vec<(usize, usize)>),
+    Unit(Unit),
+    Units(Vec<Unit>),
+    Technique(Technique),
+    Eliminations(Vec<Candidate>),
+}
+
+/// A sentence as a message key along with named arguments, for translation catalogs.
+///
+/// The keys are `hint.nudge` (units), `hint.technique` (technique, units),
+/// `hint.place.hidden-single` (value, cell, unit), `hint.place.naked-single` and
+/// `hint.place` (value, cell) and `hint.eliminate.locked-candidates`,
+/// `hint.eliminate.naked-pair`, `hint.eliminate.hidden-pair`, `hint.eliminate.x-wing` and
+/// `hint.eliminate` (eliminations, technique, values, cells, units). The plain `hint.place`
+/// and `hint.eliminate` are for steps whose technique does not match their deduction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub key: &'static str,
+    pub args: Vec<(&'static str, MessageArg)>,
+}
+
+impl Message {
+    pub fn arg(&self, name: &str) -> Option<&MessageArg> {
+        self.args.iter().find(|(n, _)| *n == name).map(|(_, a)| a)
+    }
+
+    /// The message in English, the wording of [Hint::text].
+    pub fn english(&self, rules: &Rules) -> String {
+        let text = |name: &str| match self.arg(name) {
+            Some(MessageArg::Value(v)) => v.to_string(),
+            Some(MessageArg::Values(values)) => join(values.iter().map(|v| v.to_string()), " and "),
+            Some(MessageArg::Cell((x, y))) => format!("row {}, column {}", y + 1, x + 1),
+            Some(MessageArg::Cells(cells)) => join(
+                cells.iter().map(|(x, y)| format!("r{}c{}", y + 1, x + 1)),
+                ", ",
+            ),
+            Some(MessageArg::Unit(unit)) => unit.describe(rules),
+            Some(MessageArg::Units(units)) => {
+                join(units.iter().map(|u| u.describe(rules)), " and ")
+            }
+            Some(MessageArg::Technique(technique)) => technique.to_string(),
+            Some(MessageArg::Eliminations(eliminations)) => join(
+                eliminations
+                    .iter()
+                    .map(|c| format!("{} from r{}c{}", c.value, c.y + 1, c.x + 1)),
+                ", ",
+            ),
+            None => String::new(),
+        };
+        let unit = |n: usize| match self.arg("units") {
+            Some(MessageArg::Units(units)) => {
+                units.get(n).map(|u| u.describe(rules)).unwrap_or_default()
+            }
+            _ => String::new(),
+        };
+        match self.key {
+            "hint.nudge" => format!("Look at {}.", text("units")),
+            "hint.technique" => {
+                let article = match self.arg("technique") {
+                    Some(MessageArg::Technique(Technique::XWing)) => "an",
+                    _ => "a",
+                };
+                format!(
+                    "There is {} {} in {}.",
+                    article,
+                    text("technique"),
+                    text("units")
+                )
+            }
+            "hint.place.hidden-single" => format!(
+                "Place {} in {}: it is the only cell in {} that can hold {}.",
+                text("value"),
+                text("cell"),
+                text("unit"),
+                text("value")
+            ),
+            "hint.place.naked-single" => format!(
+                "Place {} in {}: it is the only value left for that cell.",
+                text("value"),
+                text("cell")
+            ),
+            "hint.eliminate.locked-candidates" => format!(
+                "Remove {}: in {}, {} can only go in {}, which also lie in {}.",
+                text("eliminations"),
+                unit(0),
+                text("values"),
+                text("cells"),
+                unit(1)
+            ),
+            "hint.eliminate.naked-pair" => format!(
+                "Remove {}: {} can only hold {}.",
+                text("eliminations"),
+                text("cells"),
+                text("values")
+            ),
+            "hint.eliminate.hidden-pair" => format!(
+                "Remove {}: in {}, {} can only go in {}.",
+                text("eliminations"),
+                unit(0),
+                text("values"),
+                text("cells")
+            ),
+            "hint.eliminate.x-wing" => format!(
+                "Remove {}: in {}, {} can only go in {}, which form a rectangle.",
+                text("eliminations"),
+                text("units"),
+                text("values"),
+                text("cells")
+            ),
+            "hint.place" => format!("Place {} in {}.", text("value"), text("cell")),
+            "hint.eliminate" => format!("Remove {}.", text("eliminations")),
+            // Keys from callers that this crate does not know have no wording
+            key => key.to_string(),
+        }
+    }
+}
+
+fn join(parts: impl Iterator<Item = String>, separator: &str) -> String {
+    parts.collect::<Vec<String>>().join(separator)
+}
+
+pub fn find_hint(grid: &Grid) -> Option<Hint> {
+    find_hint_with_rules(grid, &Rules::classic())
+}
+
+/// Finds the next deduction, trying the easiest techniques first.
+/// Returns [None] if the grid is complete or no technique applies.
+pub fn find_hint_with_rules(grid: &Grid, rules: &Rules) -> Option<Hint> {
+    LogicSolver::new(grid, rules)
+        .next_step()
+        .map(|step| Hint { step })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_grid, solve_recursive, ValueSet, NUM_CELLS};
+
+    #[test]
+    fn hint_levels() {
+        let mut values = [0u8; NUM_CELLS];
+        // Row 1 misses only a 9 at column 9
+        for x in 0..8 {
+            values[x] = x as u8 + 1;
+        }
+        let grid = Grid::new(&values);
+        let hint = find_hint(&grid).unwrap();
+        assert_eq!(
+            hint.step.deduction,
+            Deduction::Place(Candidate {
+                x: 8,
+                y: 0,
+                value: 9
+            })
+        );
+        let rules = Rules::classic();
+        assert_eq!(hint.text(HintLevel::Nudge, &rules), "Look at row 1.");
+        assert_eq!(
+            hint.text(HintLevel::Technique, &rules),
+            "There is a hidden single in row 1."
+        );
+        assert_eq!(
+            hint.text(HintLevel::Placement, &rules),
+            "Place 9 in row 1, column 9: it is the only cell in row 1 that can hold 9."
+        );
+        assert_eq!(hint.cells(HintLevel::Nudge, &rules).len(), 9);
+        assert!(hint.cells(HintLevel::Placement, &rules).contains(&(8, 0)));
+        let message = hint.message(HintLevel::Placement);
+        assert_eq!(message.key, "hint.place.hidden-single");
+        assert_eq!(message.arg("cell"), Some(&MessageArg::Cell((8, 0))));
+        assert_eq!(message.arg("unit"), Some(&MessageArg::Unit(Unit::Row(0))));
+    }
+
+    #[test]
+    fn mismatched_steps() {
+        let rules = Rules::classic();
+        let candidate = Candidate {
+            x: 8,
+            y: 0,
+            value: 9,
+        };
+        let mut hint = Hint {
+            step: Step {
+                technique: Technique::XWing,
+                units: Vec::new(),
+                cells: Vec::new(),
+                values: ValueSet::empty(),
+                deduction: Deduction::Place(candidate),
+            },
+        };
+        assert_eq!(hint.message(HintLevel::Placement).key, "hint.place");
+        assert_eq!(
+            hint.text(HintLevel::Placement, &rules),
+            "Place 9 in row 1, column 9."
+        );
+        hint.step.technique = Technique::HiddenSingle;
+        assert_eq!(hint.message(HintLevel::Placement).key, "hint.place");
+
+        hint.step.deduction = Deduction::Eliminate(vec![candidate]);
+        assert_eq!(
+            hint.text(HintLevel::Placement, &rules),
+            "Remove 9 from r1c9."
+        );
+        // Locked candidates name two units but the step has none
+        hint.step.technique = Technique::PointingPair;
+        assert!(hint
+            .text(HintLevel::Placement, &rules)
+            .starts_with("Remove 9 from r1c9: in ,"));
+
+        let message = Message {
+            key: "hint.unknown",
+            args: Vec::new(),
+        };
+        assert_eq!(message.english(&rules), "hint.unknown");
+    }
+
+    #[test]
+    fn hints_agree_with_solution() {
+        let grid = parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap();
+        let solution = solve_recursive(grid).unwrap();
+        match find_hint(&grid).unwrap().step.deduction {
+            Deduction::Place(c) => assert_eq!(solution.get(c.x, c.y), c.value),
+            Deduction::Eliminate(_) => panic!("The easy sample starts with a single"),
+        }
+    }
+}
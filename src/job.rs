@@ -0,0 +1,206 @@
+//! Generation of large puzzle sets which skips duplicates, also of puzzles in an existing
+//! archive, and can be resumed after an interruption from its [Progress].
+
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::fmt::Error;
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rayon::prelude::*;
+
+use crate::generator::{generate_with_solution, Difficulty, Generated};
+use crate::transform::canonical_hash;
+use crate::Grid;
+
+/// Where a [GenerationJob] is, written as `key: value` lines so it can be saved to a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    /// Puzzles generated so far, duplicates included.
+    pub attempts: u64,
+    /// Puzzles kept so far.
+    pub generated: usize,
+}
+
+impl Display for Progress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        writeln!(f, "seed: {}", self.seed)?;
+        writeln!(f, "difficulty: {}", self.difficulty)?;
+        writeln!(f, "attempts: {}", self.attempts)?;
+        writeln!(f, "generated: {}", self.generated)
+    }
+}
+
+impl FromStr for Progress {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let field = |key: &str| {
+            s.lines()
+                .filter_map(|line| line.split_once(':'))
+                .find(|(k, _)| k.trim() == key)
+                .map(|(_, value)| value.trim())
+                .ok_or_else(|| format!("Missing {} in progress", key))
+        };
+        let number = |key: &str| {
+            field(key)?
+                .parse::<u64>()
+                .map_err(|e| format!("Invalid {}: {}", key, e))
+        };
+        Ok(Progress {
+            seed: number("seed")?,
+            difficulty: field("difficulty")?.parse()?,
+            attempts: number("attempts")?,
+            generated: number("generated")? as usize,
+        })
+    }
+}
+
+/// Generates puzzles of one difficulty, leaving out any equivalent to one seen before.
+///
+/// Every attempt draws from its own generator seeded by the job seed and the attempt number,
+/// so a job resumed from its [Progress] continues exactly where it stopped, and
+/// [GenerationJob::next_batch] gives the same puzzles on any number of threads.
+#[derive(Debug, Clone)]
+pub struct GenerationJob {
+    progress: Progress,
+    // Canonical hashes of the puzzles to leave out
+    seen: HashSet<u64>,
+}
+
+impl GenerationJob {
+    pub fn new(difficulty: Difficulty, seed: u64) -> GenerationJob {
+        GenerationJob::resume(Progress {
+            seed,
+            difficulty,
+            attempts: 0,
+            generated: 0,
+        })
+    }
+
+    pub fn resume(progress: Progress) -> GenerationJob {
+        GenerationJob {
+            progress,
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Leaves out puzzles equivalent to `grids`, e.g. those of an archive or the output of
+    /// the interrupted run.
+    pub fn exclude<'a>(&mut self, grids: impl IntoIterator<Item = &'a Grid>) {
+        self.seen.extend(grids.into_iter().map(canonical_hash));
+    }
+
+    pub fn progress(&self) -> &Progress {
+        &self.progress
+    }
+
+    /// Puzzles generated but left out as duplicates.
+    pub fn duplicates(&self) -> u64 {
+        self.progress.attempts - self.progress.generated as u64
+    }
+
+    /// The next `n` puzzles not seen before, generating on all threads. They are the same
+    /// puzzles as `n` calls of [Iterator::next] give, whatever the number of threads, and the
+    /// progress ends up the same too.
+    pub fn next_batch(&mut self, n: usize) -> Vec<Generated> {
+        let mut batch = Vec::with_capacity(n);
+        while batch.len() < n {
+            let first = self.progress.attempts;
+            let tries = (n - batch.len()).max(rayon::current_num_threads()) as u64;
+            let attempts: Vec<Generated> = (first..first + tries)
+                .into_par_iter()
+                .map(|attempt| self.attempt(attempt))
+                .collect();
+            // Attempts past the last one needed are dropped, as if never made
+            for generated in attempts {
+                if batch.len() == n {
+                    break;
+                }
+                self.keep(generated, &mut batch);
+            }
+        }
+        batch
+    }
+
+    // Every attempt draws from its own generator, so attempts can run in any order
+    fn attempt(&self, attempt: u64) -> Generated {
+        let seed = self.progress.seed ^ attempt.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        let mut rng = StdRng::seed_from_u64(seed);
+        generate_with_solution(self.progress.difficulty, &mut rng)
+    }
+
+    // Counts the attempt and adds its puzzle to `kept` unless seen before
+    fn keep(&mut self, generated: Generated, kept: &mut Vec<Generated>) {
+        self.progress.attempts += 1;
+        if self.seen.insert(canonical_hash(&generated.puzzle)) {
+            self.progress.generated += 1;
+            kept.push(generated);
+        }
+    }
+}
+
+impl Iterator for GenerationJob {
+    type Item = Generated;
+
+    /// The next puzzle not seen before. Never returns [None].
+    fn next(&mut self) -> Option<Generated> {
+        let mut kept = Vec::with_capacity(1);
+        while kept.is_empty() {
+            let generated = self.attempt(self.progress.attempts);
+            self.keep(generated, &mut kept);
+        }
+        kept.pop()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resumes_without_duplicates() {
+        let mut job = GenerationJob::new(Difficulty::Easy, 7);
+        let first = job.next().unwrap().puzzle;
+        let progress: Progress = job.progress().to_string().parse().unwrap();
+        assert_eq!(progress, *job.progress());
+        let second = job.next().unwrap().puzzle;
+
+        let mut resumed = GenerationJob::resume(progress);
+        assert_eq!(resumed.next().unwrap().puzzle, second);
+
+        // The first attempt is left out when its puzzle is already archived
+        let mut deduplicated = GenerationJob::new(Difficulty::Easy, 7);
+        deduplicated.exclude([&first]);
+        assert_eq!(deduplicated.next().unwrap().puzzle, second);
+        assert_eq!(deduplicated.duplicates(), 1);
+    }
+
+    #[test]
+    fn batches_ignore_thread_count() {
+        // Leaving out the first puzzle makes the batch skip a duplicate
+        let first = GenerationJob::new(Difficulty::Easy, 11)
+            .next()
+            .unwrap()
+            .puzzle;
+        let mut sequential = GenerationJob::new(Difficulty::Easy, 11);
+        sequential.exclude([&first]);
+        let expected: Vec<Grid> = sequential.by_ref().take(3).map(|g| g.puzzle).collect();
+        for threads in [1, 3] {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            let mut job = GenerationJob::new(Difficulty::Easy, 11);
+            job.exclude([&first]);
+            let batch = pool.install(|| job.next_batch(3));
+            let puzzles: Vec<Grid> = batch.iter().map(|g| g.puzzle).collect();
+            assert_eq!(puzzles, expected);
+            assert_eq!(job.progress(), sequential.progress());
+        }
+    }
+}
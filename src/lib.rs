@@ -3,10 +3,60 @@
 
 use bitvec::mem::BitMemory;
 use bitvec::prelude::*;
+use printer::GridPrinter;
 use rayon::prelude::*;
 use std::fmt::Display;
 use std::fmt::Error;
 use std::fmt::Formatter;
+use std::str::FromStr;
+
+pub mod accessible;
+pub mod audit;
+pub mod baseline;
+pub mod batch;
+pub mod board;
+pub mod cache;
+pub mod certificate;
+pub mod check;
+pub mod compare;
+pub mod conflict;
+pub mod convert;
+pub mod curve;
+pub mod dataset;
+pub mod error;
+pub mod estimate;
+pub mod format;
+pub mod fuzz;
+pub mod generator;
+pub mod heartbeat;
+pub mod heatmap;
+pub mod hint;
+pub mod job;
+mod json;
+pub mod logic;
+pub mod observer;
+mod parse;
+pub mod play;
+pub mod printer;
+mod propagation;
+pub mod recommend;
+pub mod record;
+pub mod repair;
+pub mod report;
+pub mod rules;
+pub mod snapshot;
+pub mod symbols;
+pub mod template;
+pub mod tournament;
+pub mod transform;
+pub mod unavoidable;
+pub mod verify;
+
+pub use error::{GridError, ParseWarning};
+pub use parse::ParseOptions;
+pub use propagation::{propagate, propagate_with, PropagationDepth, PropagationResult};
+pub use rules::{Rules, Unit, Variant};
+pub use symbols::SymbolSet;
 
 // Cell values are only 0 (EMPTY) and 1..9 an assigned value.
 pub type CellValue = u8;
@@ -17,7 +67,7 @@ const NUM_BITS: usize = NUM_CELLS * 4;
 
 /// A 9x9 Grid for Sudoku compactly represented with 4 bits per cell
 /// Because we are compact we support [Copy] to allow easy splitting.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct Grid {
     cells: BitArr!(for NUM_BITS, in Lsb0, CellValue),
 }
@@ -47,6 +97,67 @@ impl Grid {
         let bits = &mut self.cells[Self::get_bit_range(get_index(x, y))];
         bits.store(val);
     }
+
+    /// The values of the cells in `unit`, in the order of [Unit::cells].
+    pub fn unit_values(&self, unit: Unit, rules: &Rules) -> [CellValue; 9] {
+        unit.cells(rules).map(|i| self.get(i % 9, i / 9))
+    }
+
+    /// Checks that no value appears twice in a row, column or box.
+    pub fn validate(&self) -> Result<(), GridError> {
+        self.validate_with_rules(&Rules::classic())
+    }
+
+    /// Like [Grid::validate] but also checking the extra units of `rules`.
+    pub fn validate_with_rules(&self, rules: &Rules) -> Result<(), GridError> {
+        for unit in rules.units() {
+            let cells = unit.cells(rules);
+            let mut seen: [Option<usize>; 10] = [None; 10];
+            for i in cells {
+                let value = self.get(i % 9, i / 9);
+                if value == EMPTY_CELL {
+                    continue;
+                }
+                if let Some(first) = seen[value as usize] {
+                    return Err(GridError::Duplicate {
+                        value,
+                        unit,
+                        first: (first % 9, first / 9),
+                        second: (i % 9, i / 9),
+                    });
+                }
+                seen[value as usize] = Some(i);
+            }
+        }
+        Ok(())
+    }
+
+    /// A short ID of 12 hex digits for referring to the puzzle, e.g. in bug reports. Equivalent
+    /// puzzles under the classic symmetries share it, see [transform::canonical_hash].
+    pub fn fingerprint(&self) -> String {
+        format!("{:012x}", transform::canonical_hash(self) >> 16)
+    }
+
+    /// The grid on one line as 81 digits with '.' for empty cells, the compact form most
+    /// Sudoku tools read. [Display] gives the pretty form instead.
+    pub fn to_line(self) -> String {
+        self.to_line_with(&SymbolSet::digits())
+    }
+
+    /// Like [Grid::to_line] but using `symbols`.
+    pub fn to_line_with(self, symbols: &SymbolSet) -> String {
+        (0..NUM_CELLS)
+            .map(|i| symbols.symbol(self.get(i % 9, i / 9)))
+            .collect()
+    }
+
+    /// Displays the grid using `symbols` instead of the digits 1..9.
+    pub fn display_with<'a>(&'a self, symbols: &'a SymbolSet) -> GridDisplay<'a> {
+        GridDisplay {
+            grid: self,
+            symbols,
+        }
+    }
 }
 
 #[inline]
@@ -57,33 +168,25 @@ fn get_index(x: usize, y: usize) -> usize {
 
 impl Display for Grid {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
-        for y in 0..9 {
-            if y % 3 == 0 {
-                writeln!(f, "+------+------+-----+")?
-            }
-            for x in 0..9 {
-                if x % 3 == 0 {
-                    write!(f, "|")?
-                }
-                let val = self.get(x, y);
-                if val == EMPTY_CELL {
-                    write!(f, ".")?;
-                } else {
-                    write!(f, "{}", val)?;
-                }
-                if x < 8 {
-                    write!(f, " ")?;
-                }
-            }
-            writeln!(f, "|")?;
-        }
-        writeln!(f, "+------+------+-----+")?;
-        Ok(())
+        self.display_with(&SymbolSet::digits()).fmt(f)
+    }
+}
+
+/// Displays a [Grid] with a custom [SymbolSet], see [Grid::display_with].
+pub struct GridDisplay<'a> {
+    grid: &'a Grid,
+    symbols: &'a SymbolSet,
+}
+
+impl Display for GridDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        let printer = GridPrinter::new().symbols(self.symbols.clone());
+        write!(f, "{}", printer.print(self.grid))
     }
 }
 
 /// Represents a set of the values 1..9.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct ValueSet(u16);
 
 impl ValueSet {
@@ -191,111 +294,208 @@ impl Iterator for ValueSetIterator {
  * A intermediary structure used for solving the Sudoko. Contains a Grid and the up-to-date valid candidates for each cell.
  */
 #[derive(Debug, Clone, Copy)]
-struct SolveState {
+struct SolveState<'a> {
     grid: Grid,
     candidates: [ValueSet; NUM_CELLS],
+    // Number of empty cells left
+    empty: u8,
+    // Bit i of by_count[n] is set if cell i is empty with n candidates, so the cell with the
+    // fewest candidates is found without scanning the grid
+    by_count: [u128; 10],
+    rules: &'a Rules,
+    order: SearchOrder,
+    propagate: bool,
 }
 
-impl SolveState {
-    pub fn new(grid: Grid) -> Self {
-        // NOTE: Can we do the initialization in one step?
-        let mut candidates = [ValueSet::empty(); NUM_CELLS];
-        for i in 0..NUM_CELLS {
-            candidates[i] = get_candidates(&grid, i % 9, i / 9);
+/// Placements waiting to be made while propagating. Every cell is pushed at most once as a naked single,
+/// and hidden singles are pushed one at a time, so this never holds more than 82 entries.
+struct Pending {
+    entries: [(u8, CellValue); NUM_CELLS + 1],
+    len: usize,
+}
+
+impl Pending {
+    fn new() -> Self {
+        Pending {
+            entries: [(0, EMPTY_CELL); NUM_CELLS + 1],
+            len: 0,
         }
-        SolveState { grid, candidates }
     }
 
     #[inline]
-    fn cand_at_mut(&mut self, x: usize, y: usize) -> &mut ValueSet {
-        &mut self.candidates[get_index(x, y)]
+    fn push(&mut self, index: usize, value: CellValue) {
+        self.entries[self.len] = (index as u8, value);
+        self.len += 1;
     }
 
     #[inline]
-    fn cand_at(&self, x: usize, y: usize) -> &ValueSet {
-        &self.candidates[get_index(x, y)]
+    fn pop(&mut self) -> Option<(usize, CellValue)> {
+        self.len = self.len.checked_sub(1)?;
+        let (index, value) = self.entries[self.len];
+        Some((index as usize, value))
     }
+}
 
-    fn deadlocked(&self) -> bool {
-        for y in 0..9 {
-            for x in 0..9 {
-                if self.grid.get(x, y) == EMPTY_CELL && self.cand_at(x, y).count() == 0 {
-                    return true;
-                }
+impl<'a> SolveState<'a> {
+    /// The state for `grid` with the consequences of the givens propagated, or [None] if
+    /// some empty cell already has no candidates.
+    fn new(grid: Grid, solver: &'a Solver) -> Option<Self> {
+        let rules = &solver.rules;
+        let mut candidates = [ValueSet::empty(); NUM_CELLS];
+        let mut empty = 0;
+        let mut by_count = [0; 10];
+        let mut pending = Pending::new();
+        for i in 0..NUM_CELLS {
+            if grid.get(i % 9, i / 9) != EMPTY_CELL {
+                continue;
+            }
+            candidates[i] = get_candidates_with_rules(&grid, i % 9, i / 9, rules);
+            empty += 1;
+            match candidates[i].count() {
+                0 => return None,
+                1 if solver.propagate => pending.push(i, candidates[i].get_first()?),
+                _ => {}
             }
+            by_count[candidates[i].count() as usize] |= 1 << i;
         }
-        false
+        let mut state = SolveState {
+            grid,
+            candidates,
+            empty,
+            by_count,
+            rules,
+            order: solver.order,
+            propagate: solver.propagate,
+        };
+        state.settle(pending).then_some(state)
     }
 
     fn assign(&self, val: CellValue, x: usize, y: usize) -> Option<Self> {
         let mut cpy = *self;
-        cpy.grid.set(val, x, y);
-        cpy.candidates[get_index(x, y)].clear();
-        cpy.remove_val_from_peers(val, x, y);
-        if self.deadlocked() {
-            None
-        } else {
-            Some(cpy)
-        }
-    }
-
-    fn remove_val_from_peers(&mut self, val: CellValue, x: usize, y: usize) {
-        // Constrain Horizontal
-        for cx in 0..9 {
-            self.cand_at_mut(cx, y).remove(val);
-        }
-        // Constrain Vertical
-        for cy in 0..9 {
-            self.cand_at_mut(x, cy).remove(val);
-        }
-        // Constrain Quadrant
-        {
-            let sx = (x / 3) * 3;
-            let sy = (y / 3) * 3;
-            for cy in sy..sy + 3 {
-                for cx in sx..sx + 3 {
-                    self.cand_at_mut(cx, cy).remove(val);
+        let mut pending = Pending::new();
+        pending.push(get_index(x, y), val);
+        cpy.settle(pending).then_some(cpy)
+    }
+
+    /// Makes the pending placements and, when propagating, every placement they force.
+    /// Returns false if a contradiction shows up: a cell or a value in a unit without any place left.
+    fn settle(&mut self, mut pending: Pending) -> bool {
+        loop {
+            while let Some((i, val)) = pending.pop() {
+                let current = self.grid.get(i % 9, i / 9);
+                if current != EMPTY_CELL {
+                    // Forced twice, possibly with different values
+                    if current == val {
+                        continue;
+                    }
+                    return false;
+                }
+                if !self.candidates[i].contains(val) {
+                    return false;
+                }
+                self.grid.set(val, i % 9, i / 9);
+                self.by_count[self.candidates[i].count() as usize] &= !(1 << i);
+                self.candidates[i].clear();
+                self.empty -= 1;
+                if !self.remove_val_from_peers(val, i, &mut pending) {
+                    return false;
                 }
             }
+            if !self.propagate {
+                return true;
+            }
+            match self.find_hidden_single() {
+                Err(()) => return false,
+                Ok(None) => return true,
+                Ok(Some((i, val))) => pending.push(i, val),
+            }
         }
     }
 
-    fn is_solved(&self) -> bool {
-        for y in 0..9 {
-            for x in 0..9 {
-                if self.grid.get(x, y) == EMPTY_CELL {
-                    return false;
+    fn remove_val_from_peers(
+        &mut self,
+        val: CellValue,
+        index: usize,
+        pending: &mut Pending,
+    ) -> bool {
+        for &p in self.rules.peer_indices(index) {
+            let candidates = &mut self.candidates[p];
+            if !candidates.contains(val) {
+                continue;
+            }
+            candidates.remove(val);
+            let count = candidates.count() as usize;
+            self.by_count[count + 1] &= !(1 << p);
+            self.by_count[count] |= 1 << p;
+            match count {
+                0 => return false,
+                1 if self.propagate => {
+                    pending.push(p, candidates.get_first().unwrap_or(EMPTY_CELL))
                 }
+                _ => {}
             }
         }
         true
     }
 
-    fn candidate_fewest_choices(&self) -> Option<(ValueSet, usize, usize)> {
-        let mut lowest_count = 99;
-        let mut best_i: usize = usize::MAX;
-        for i in 0..NUM_CELLS {
-            let candidate = self.candidates[i];
-            let count = candidate.count();
-            if count > 0 && count < lowest_count {
-                lowest_count = count;
-                best_i = i;
+    /// A value with only one place left in some unit, or an error if a value has no place left.
+    fn find_hidden_single(&self) -> Result<Option<(usize, CellValue)>, ()> {
+        for unit in self.rules.unit_cells() {
+            let mut placed = ValueSet::empty();
+            let mut once = ValueSet::empty();
+            let mut more = ValueSet::empty();
+            for &i in unit {
+                placed.add(self.grid.get(i % 9, i / 9));
+                more.0 |= once.0 & self.candidates[i].0;
+                once.0 |= self.candidates[i].0;
+            }
+            if ValueSet::full().0 & !placed.0 & !once.0 != 0 {
+                return Err(());
+            }
+            if let Some(val) = ValueSet(once.0 & !more.0).get_first() {
+                let i = unit.iter().find(|&&i| self.candidates[i].contains(val));
+                return Ok(i.map(|&i| (i, val)));
             }
         }
-        if best_i != usize::MAX {
-            Some((self.candidates[best_i], best_i % 9, best_i / 9))
-        } else {
-            None
+        Ok(None)
+    }
+
+    fn is_solved(&self) -> bool {
+        self.empty == 0
+    }
+
+    fn candidate_fewest_choices(&self) -> Option<(ValueSet, usize, usize)> {
+        let cells = self.by_count[1..].iter().find(|&&cells| cells != 0)?;
+        // Ties go to the last cell. Either end is arbitrary, but puzzles made to defeat
+        // brute force tend to target a top-left first search.
+        let i = 127 - cells.leading_zeros() as usize;
+        Some((self.candidates[i], i % 9, i / 9))
+    }
+
+    fn candidate_first_empty(&self) -> Option<(ValueSet, usize, usize)> {
+        let cells = self.by_count.iter().fold(0, |all, cells| all | cells);
+        if cells == 0 {
+            return None;
         }
+        let i = cells.trailing_zeros() as usize;
+        Some((self.candidates[i], i % 9, i / 9))
     }
- 
+
     #[inline]
     fn get_candidate(&self) -> Option<(ValueSet, usize, usize)> {
-        self.candidate_fewest_choices()
+        match self.order {
+            SearchOrder::FewestCandidates => self.candidate_fewest_choices(),
+            SearchOrder::Lexicographic => self.candidate_first_empty(),
+        }
     }
 }
 
 pub fn get_candidates(grid: &Grid, x: usize, y: usize) -> ValueSet {
+    get_candidates_with_rules(grid, x, y, &Rules::classic())
+}
+
+/// Like [get_candidates] but also respecting the extra units of `rules`.
+pub fn get_candidates_with_rules(grid: &Grid, x: usize, y: usize, rules: &Rules) -> ValueSet {
     debug_assert!(x < 9 && y < 9);
     let mut candidates = ValueSet::full();
 
@@ -303,22 +503,9 @@ pub fn get_candidates(grid: &Grid, x: usize, y: usize) -> ValueSet {
         return ValueSet::empty();
     }
 
-    // Scan horizontal
-    for cx in 0..9 {
-        candidates.remove(grid.get(cx, y));
-    }
-    // Scan vertical
-    for cy in 0..9 {
-        candidates.remove(grid.get(x, cy));
-    }
-    // Scan quadrant
-    {
-        let sx = (x / 3) * 3;
-        let sy = (y / 3) * 3;
-        for cy in sy..sy + 3 {
-            for cx in sx..sx + 3 {
-                candidates.remove(grid.get(cx, cy));
-            }
+    for unit in rules.units_at(get_index(x, y)) {
+        for value in grid.unit_values(unit, rules) {
+            candidates.remove(value);
         }
     }
 
@@ -368,27 +555,255 @@ fn solve_recursive_internal_par(solve_state: SolveState) -> Option<SolveState> {
 }
 
 pub fn solve_recursive(grid: Grid) -> Option<Grid> {
-    solve_recursive_internal(SolveState::new(grid)).map(|st| st.grid)
+    solve_recursive_with_rules(grid, &Rules::classic())
 }
 
 pub fn solve_recursive_par(grid: Grid) -> Option<Grid> {
-    solve_recursive_internal_par(SolveState::new(grid)).map(|st| st.grid)
+    solve_recursive_par_with_rules(grid, &Rules::classic())
+}
+
+pub fn solve_recursive_with_rules(grid: Grid, rules: &Rules) -> Option<Grid> {
+    Solver::new().with_rules(rules.clone()).solve(grid)
+}
+
+pub fn solve_recursive_par_with_rules(grid: Grid, rules: &Rules) -> Option<Grid> {
+    Solver::new()
+        .with_rules(rules.clone())
+        .parallel(true)
+        .solve(grid)
+}
+
+/// Order in which the backtracking search picks cells and values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchOrder {
+    /// Branch on the cell with the fewest candidates. Usually the fastest.
+    #[default]
+    FewestCandidates,
+    /// Branch on the first empty cell in row-major order, trying values in increasing order.
+    /// Solutions are then found in lexicographic order of their cells, with or without parallelism,
+    /// so the same puzzle always yields the same solution.
+    Lexicographic,
+}
+
+/// Configurable backtracking solver.
+#[derive(Debug, Clone)]
+pub struct Solver {
+    rules: Rules,
+    order: SearchOrder,
+    parallel: bool,
+    propagate: bool,
+}
+
+impl Default for Solver {
+    fn default() -> Self {
+        Solver {
+            rules: Rules::classic(),
+            order: SearchOrder::default(),
+            parallel: false,
+            propagate: true,
+        }
+    }
+}
+
+impl Solver {
+    pub fn new() -> Solver {
+        Solver::default()
+    }
+
+    pub fn with_rules(mut self, rules: Rules) -> Solver {
+        self.rules = rules;
+        self
+    }
+
+    pub fn with_order(mut self, order: SearchOrder) -> Solver {
+        self.order = order;
+        self
+    }
+
+    pub fn parallel(mut self, parallel: bool) -> Solver {
+        self.parallel = parallel;
+        self
+    }
+
+    /// Whether to place naked and hidden singles as soon as they appear instead of branching
+    /// on them. On by default; turning it off gives plain backtracking, mostly useful for comparison.
+    pub fn propagate(mut self, propagate: bool) -> Solver {
+        self.propagate = propagate;
+        self
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// Singles are propagated first, and a grid they solve returns right away without search,
+    /// threads or allocation, so interactive apps can call this on every keystroke.
+    pub fn solve(&self, grid: Grid) -> Option<Grid> {
+        let state = SolveState::new(grid, self)?;
+        // Most puzzles people play fall to singles alone, so there is no search to spread
+        // over threads
+        if state.is_solved() {
+            return Some(state.grid);
+        }
+        if self.parallel {
+            solve_recursive_internal_par(state).map(|st| st.grid)
+        } else {
+            solve_recursive_internal(state).map(|st| st.grid)
+        }
+    }
+
+    /// Lazily enumerates all solutions. Enumeration is always sequential, and with
+    /// [SearchOrder::Lexicographic] the solutions come in lexicographic order.
+    pub fn solutions(&self, grid: Grid) -> Solutions<'_> {
+        Solutions {
+            stack: SolveState::new(grid, self).into_iter().collect(),
+            expanded: 0,
+        }
+    }
+
+    /// Up to `limit` solutions in the order they are found.
+    pub fn solve_all(&self, grid: Grid, limit: usize) -> Vec<Grid> {
+        self.solutions(grid).take(limit).collect()
+    }
+
+    /// Counts solutions but stops once `cap` have been found.
+    pub fn count_solutions(&self, grid: Grid, cap: usize) -> usize {
+        self.solutions(grid).take(cap).count()
+    }
+
+    pub fn has_unique_solution(&self, grid: Grid) -> bool {
+        self.count_solutions(grid, 2) == 1
+    }
+
+    /// Solves sequentially like [Solver::solve], but gives up with [None] as soon as
+    /// `give_up` returns true. It is asked before each state is expanded, with the number of
+    /// states expanded so far.
+    pub(crate) fn solve_until(
+        &self,
+        grid: Grid,
+        give_up: impl FnMut(u64) -> bool,
+    ) -> Option<Option<Grid>> {
+        self.solutions(grid).next_until(give_up)
+    }
+}
+
+/// Iterator over the solutions of a grid, see [Solver::solutions].
+pub struct Solutions<'a> {
+    // Depth-first search frontier, next state to expand on top
+    stack: Vec<SolveState<'a>>,
+    // States expanded so far
+    expanded: u64,
+}
+
+impl Solutions<'_> {
+    /// The next solution, or [Some] of [None] once there are no more. Gives up with [None]
+    /// as soon as `give_up` returns true, which is asked before each state is expanded with
+    /// the number of states expanded so far.
+    fn next_until(&mut self, mut give_up: impl FnMut(u64) -> bool) -> Option<Option<Grid>> {
+        while let Some(state) = self.stack.pop() {
+            if state.is_solved() {
+                return Some(Some(state.grid));
+            }
+            if give_up(self.expanded) {
+                self.stack.push(state);
+                return None;
+            }
+            self.expanded += 1;
+            if let Some((cands, x, y)) = state.get_candidate() {
+                let branches: Vec<SolveState> = cands
+                    .into_iter()
+                    .filter_map(|cand| state.assign(cand, x, y))
+                    .collect();
+                self.stack.extend(branches.into_iter().rev());
+            }
+        }
+        Some(None)
+    }
 }
 
-fn is_digit(c: char) -> bool {
-    '0' <= c && c <= '9'
+impl Iterator for Solutions<'_> {
+    type Item = Grid;
+
+    fn next(&mut self) -> Option<Grid> {
+        self.next_until(|_| false).flatten()
+    }
+}
+
+pub fn solve_all(grid: Grid, limit: usize) -> Vec<Grid> {
+    Solver::new().solve_all(grid, limit)
+}
+
+pub fn count_solutions(grid: &Grid, cap: usize) -> usize {
+    Solver::new().count_solutions(*grid, cap)
+}
+
+pub fn has_unique_solution(grid: &Grid) -> bool {
+    Solver::new().has_unique_solution(*grid)
+}
+
+impl FromStr for SearchOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fewest" => Ok(SearchOrder::FewestCandidates),
+            "lexicographic" => Ok(SearchOrder::Lexicographic),
+            _ => Err(format!("Unknown search order '{}'", s)),
+        }
+    }
 }
 
 pub fn parse_grid(text: &str) -> Option<Grid> {
-    let nums: Vec<u8> = text
-        .chars()
-        .filter(|&c| is_digit(c) || c == '.')
-        .map(|c| if c == '.' { 0 } else { c.to_digit(10).unwrap() } as u8)
-        .collect();
-    if nums.len() == NUM_CELLS {
-        return Some(Grid::new(&nums));
+    parse_grid_with_symbols(text, &SymbolSet::digits())
+}
+
+/// Parses a grid written with `symbols`. Characters outside the symbol set are ignored.
+pub fn parse_grid_with_symbols(text: &str, symbols: &SymbolSet) -> Option<Grid> {
+    ParseOptions::lenient()
+        .symbols(symbols.clone())
+        .parse(text)
+        .ok()
+}
+
+/// Like [parse_grid] but rejecting characters other than digits, '.', '0' and the
+/// '|', '+', '-' and whitespace of the grid layout, and grids that break the rules.
+/// See [ParseOptions] for other levels of strictness.
+pub fn parse_grid_checked(text: &str) -> Result<Grid, GridError> {
+    parse_grid_checked_with_symbols(text, &SymbolSet::digits())
+}
+
+pub fn parse_grid_checked_with_symbols(text: &str, symbols: &SymbolSet) -> Result<Grid, GridError> {
+    ParseOptions::strict().symbols(symbols.clone()).parse(text)
+}
+
+/// Like [parse_grid_checked] but reading every grid of `text`, see [ParseOptions::parse_all].
+pub fn parse_grids(text: &str) -> Result<Vec<Grid>, GridError> {
+    ParseOptions::strict().parse_all(text)
+}
+
+impl FromStr for Grid {
+    type Err = GridError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_grid_checked(s)
+    }
+}
+
+/// Serializes as the string of [Grid::to_line].
+#[cfg(feature = "serde")]
+impl serde::Serialize for Grid {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_line())
+    }
+}
+
+/// Deserializes from a string in any layout [parse_grid_checked] accepts.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Grid {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        parse_grid_checked(&text).map_err(serde::de::Error::custom)
     }
-    None
 }
 
 #[cfg(test)]
@@ -444,4 +859,123 @@ mod tests {
         let grid = parse_grid(TEST_GRID).unwrap();
         assert!(solve_recursive(grid).is_some());
     }
+
+    #[test]
+    fn solves_singles_without_search() {
+        let grid = parse_grid(TEST_GRID).unwrap();
+        let mut easy = solve_recursive(grid).unwrap();
+        for i in (0..NUM_CELLS).step_by(4) {
+            easy.set(EMPTY_CELL, i % 9, i / 9);
+        }
+        let solver = Solver::new();
+        let state = SolveState::new(easy, &solver).unwrap();
+        assert!(state.is_solved());
+        assert_eq!(solver.solve(easy), Some(state.grid));
+        assert_eq!(solver.clone().parallel(true).solve(easy), Some(state.grid));
+    }
+
+    #[test]
+    fn lexicographic_order() {
+        let empty = Grid::new(&[0u8; NUM_CELLS]);
+        let solver = Solver::new().with_order(SearchOrder::Lexicographic);
+        let solved = solver.solve(empty).unwrap();
+        let first_rows: Vec<CellValue> = (0..18).map(|i| solved.get(i % 9, i / 9)).collect();
+        assert_eq!(
+            first_rows,
+            [1, 2, 3, 4, 5, 6, 7, 8, 9, 4, 5, 6, 7, 8, 9, 1, 2, 3]
+        );
+
+        // Clearing a band of a solved grid leaves several solutions
+        let mut grid = solve_recursive(parse_grid(TEST_GRID).unwrap()).unwrap();
+        for i in 0..27 {
+            grid.set(EMPTY_CELL, i % 9, i / 9);
+        }
+        let seq = solver.solve(grid).unwrap();
+        let par = solver.clone().parallel(true).solve(grid).unwrap();
+        assert_eq!(seq.to_string(), par.to_string());
+    }
+
+    #[test]
+    fn enumerate_solutions() {
+        let grid = parse_grid(TEST_GRID).unwrap();
+        assert!(has_unique_solution(&grid));
+        assert_eq!(solve_all(grid, 10).len(), 1);
+
+        let empty = Grid::new(&[0u8; NUM_CELLS]);
+        assert_eq!(count_solutions(&empty, 500), 500);
+        assert!(!has_unique_solution(&empty));
+
+        let solver = Solver::new().with_order(SearchOrder::Lexicographic);
+        let lines: Vec<String> = solver
+            .solutions(empty)
+            .take(20)
+            .map(|g| g.to_string())
+            .collect();
+        let mut sorted = lines.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(lines, sorted);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            TEST_GRID.parse::<Grid>(),
+            Ok(parse_grid(TEST_GRID).unwrap())
+        );
+        assert_eq!(
+            parse_grid_checked("123"),
+            Err(GridError::WrongCellCount { found: 3 })
+        );
+        assert_eq!(
+            parse_grid_checked(&TEST_GRID.replacen("8", "x", 1)),
+            Err(GridError::InvalidCharacter {
+                character: 'x',
+                line: 2,
+                column: 19
+            })
+        );
+
+        let duplicate = parse_grid(&TEST_GRID.replacen(".", "5", 1)).unwrap();
+        let error = duplicate.validate().unwrap_err();
+        assert_eq!(
+            error,
+            GridError::Duplicate {
+                value: 5,
+                unit: Unit::Row(0),
+                first: (1, 0),
+                second: (8, 0)
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "Duplicate 5 in row 1: row 1, column 2 and row 1, column 9"
+        );
+    }
+
+    #[test]
+    fn can_solve_position_variant() {
+        let rules = Rules::new(&[Variant::Position]);
+        let solved = solve_recursive_with_rules(Grid::new(&[0u8; NUM_CELLS]), &rules).unwrap();
+        for unit in rules.extra_units() {
+            let values: HashSet<CellValue> =
+                unit.iter().map(|&i| solved.get(i % 9, i / 9)).collect();
+            assert_eq!(values.len(), 9);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_as_line() {
+        use serde::de::value::{Error, StrDeserializer};
+        use serde::de::IntoDeserializer;
+        use serde::Deserialize;
+
+        let grid = parse_grid(TEST_GRID).unwrap();
+        let line = grid.to_line();
+        let deserializer: StrDeserializer<Error> = line.as_str().into_deserializer();
+        assert_eq!(Grid::deserialize(deserializer), Ok(grid));
+        let deserializer: StrDeserializer<Error> = "123".into_deserializer();
+        assert!(Grid::deserialize(deserializer).is_err());
+    }
 }
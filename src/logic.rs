@@ -0,0 +1,739 @@
+//! Logical solver applying the techniques a human would use, recording every deduction as a [Step].
+
+use std::fmt::Display;
+use std::fmt::Error;
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+use crate::rules::{Unit, UnitCells};
+use crate::{get_candidates_with_rules, CellValue, Grid, Rules, ValueSet, EMPTY_CELL, NUM_CELLS};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Technique {
+    /// A value has only one possible cell within a unit.
+    HiddenSingle,
+    /// A cell has only one possible value.
+    NakedSingle,
+    /// Within a box a value is confined to one row or column, so it cannot appear elsewhere in that line.
+    PointingPair,
+    /// Within a line a value is confined to one box, so it cannot appear elsewhere in that box.
+    BoxLineReduction,
+    /// Two cells of a unit can only hold the same two values, which then cannot appear elsewhere in the unit.
+    NakedPair,
+    /// Two values of a unit can only go in the same two cells, which then cannot hold other values.
+    HiddenPair,
+    /// A value is confined to the same two columns in two rows (or vice versa) and
+    /// cannot appear elsewhere in those columns.
+    XWing,
+}
+
+impl Technique {
+    /// All techniques from easiest to hardest.
+    pub const ALL: [Technique; 7] = [
+        Technique::HiddenSingle,
+        Technique::NakedSingle,
+        Technique::PointingPair,
+        Technique::BoxLineReduction,
+        Technique::NakedPair,
+        Technique::HiddenPair,
+        Technique::XWing,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Technique::HiddenSingle => "hidden single",
+            Technique::NakedSingle => "naked single",
+            Technique::PointingPair => "pointing pair",
+            Technique::BoxLineReduction => "box/line reduction",
+            Technique::NakedPair => "naked pair",
+            Technique::HiddenPair => "hidden pair",
+            Technique::XWing => "X-wing",
+        }
+    }
+
+    /// Relative difficulty of the technique, starting at 1 for the easiest.
+    pub fn tier(&self) -> u8 {
+        match self {
+            Technique::HiddenSingle => 1,
+            Technique::NakedSingle => 2,
+            Technique::PointingPair | Technique::BoxLineReduction => 3,
+            Technique::NakedPair => 4,
+            Technique::HiddenPair => 5,
+            Technique::XWing => 6,
+        }
+    }
+
+    /// The difficulty of a puzzle needing this technique.
+    pub fn difficulty(&self) -> Difficulty {
+        match self.tier() {
+            1..=2 => Difficulty::Easy,
+            3..=4 => Difficulty::Medium,
+            _ => Difficulty::Hard,
+        }
+    }
+}
+
+impl Display for Technique {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Technique {
+    type Err = String;
+
+    /// Takes the name with dashes for spaces and slashes, e.g. "box-line-reduction".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let key = |t: &Technique| t.name().to_lowercase().replace([' ', '/'], "-");
+        Technique::ALL
+            .into_iter()
+            .find(|t| key(t) == s.to_lowercase())
+            .ok_or_else(|| format!("Unknown technique: {}", s))
+    }
+}
+
+/// The techniques a [LogicSolver] may use, e.g. to check that a puzzle for teaching needs
+/// nothing beyond what has been covered so far.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TechniqueSet(u8);
+
+impl TechniqueSet {
+    pub fn empty() -> TechniqueSet {
+        TechniqueSet(0)
+    }
+
+    pub fn all() -> TechniqueSet {
+        Technique::ALL.into_iter().collect()
+    }
+
+    /// Naked and hidden singles.
+    pub fn singles() -> TechniqueSet {
+        [Technique::HiddenSingle, Technique::NakedSingle]
+            .into_iter()
+            .collect()
+    }
+
+    /// The techniques a puzzle of `difficulty` may need.
+    pub fn up_to(difficulty: Difficulty) -> TechniqueSet {
+        Technique::ALL
+            .into_iter()
+            .filter(|t| t.difficulty() <= difficulty)
+            .collect()
+    }
+
+    pub fn contains(&self, technique: Technique) -> bool {
+        self.0 & (1 << technique as u8) != 0
+    }
+
+    pub fn add(&mut self, technique: Technique) {
+        self.0 |= 1 << technique as u8;
+    }
+
+    pub fn remove(&mut self, technique: Technique) {
+        self.0 &= !(1 << technique as u8);
+    }
+
+    /// The techniques from easiest to hardest.
+    pub fn iter(&self) -> impl Iterator<Item = Technique> + '_ {
+        Technique::ALL.into_iter().filter(|&t| self.contains(t))
+    }
+}
+
+impl FromIterator<Technique> for TechniqueSet {
+    fn from_iter<I: IntoIterator<Item = Technique>>(iter: I) -> Self {
+        let mut set = TechniqueSet::empty();
+        for technique in iter {
+            set.add(technique);
+        }
+        set
+    }
+}
+
+impl Display for TechniqueSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        let names: Vec<&str> = self.iter().map(|t| t.name()).collect();
+        write!(f, "{}", names.join(", "))
+    }
+}
+
+impl FromStr for TechniqueSet {
+    type Err = String;
+
+    /// Takes "all", "singles" or a comma separated list of techniques, e.g.
+    /// "singles,pointing-pair".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut set = TechniqueSet::empty();
+        for part in s.split(',').map(str::trim) {
+            match part {
+                "all" => return Ok(TechniqueSet::all()),
+                "singles" => set.0 |= TechniqueSet::singles().0,
+                _ => set.add(part.parse()?),
+            }
+        }
+        Ok(set)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Difficulty {
+    /// Singles only.
+    Easy,
+    /// Needs locked candidates or naked pairs.
+    Medium,
+    /// Needs hidden pairs or X-wings.
+    Hard,
+    /// Cannot be solved with the known techniques.
+    Expert,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard,
+        Difficulty::Expert,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+            Difficulty::Expert => "expert",
+        }
+    }
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Difficulty::ALL
+            .iter()
+            .find(|d| d.name().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| format!("Unknown difficulty '{}'", s))
+    }
+}
+
+/// A value in a cell at (x, y).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Candidate {
+    pub x: usize,
+    pub y: usize,
+    pub value: CellValue,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Deduction {
+    Place(Candidate),
+    Eliminate(Vec<Candidate>),
+}
+
+/// One deduction of the logical solver and what justified it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub technique: Technique,
+    /// Where the technique applies, the main unit first.
+    pub units: Vec<Unit>,
+    /// The cells (x, y) forming the pattern, e.g. both cells of a naked pair.
+    pub cells: Vec<(usize, usize)>,
+    /// The values forming the pattern.
+    pub values: ValueSet,
+    pub deduction: Deduction,
+}
+
+/// A grid along with the candidates a human would have pencilled in, to which techniques are applied.
+#[derive(Debug, Clone)]
+pub struct LogicSolver<'a> {
+    grid: Grid,
+    candidates: [ValueSet; NUM_CELLS],
+    rules: &'a Rules,
+    // Every unit along with its cells, in the order of Rules::units
+    units: Vec<(Unit, UnitCells)>,
+    techniques: TechniqueSet,
+}
+
+impl<'a> LogicSolver<'a> {
+    pub fn new(grid: &Grid, rules: &'a Rules) -> Self {
+        let mut candidates = [ValueSet::empty(); NUM_CELLS];
+        for i in 0..NUM_CELLS {
+            candidates[i] = get_candidates_with_rules(grid, i % 9, i / 9, rules);
+        }
+        let units = rules.units().map(|u| (u, u.cells(rules))).collect();
+        LogicSolver {
+            grid: *grid,
+            candidates,
+            rules,
+            units,
+            techniques: TechniqueSet::all(),
+        }
+    }
+
+    /// Restricts [LogicSolver::next_step] and [LogicSolver::run] to `techniques`.
+    pub fn with_techniques(mut self, techniques: TechniqueSet) -> Self {
+        self.techniques = techniques;
+        self
+    }
+
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    pub fn techniques(&self) -> TechniqueSet {
+        self.techniques
+    }
+
+    pub fn rules(&self) -> &'a Rules {
+        self.rules
+    }
+
+    pub fn candidates(&self, x: usize, y: usize) -> ValueSet {
+        self.candidates[y * 9 + x]
+    }
+
+    /// Whether every cell is filled without breaking the rules, so clashing givens never
+    /// count as solved.
+    pub fn is_solved(&self) -> bool {
+        (0..NUM_CELLS).all(|i| self.value_at(i) != EMPTY_CELL)
+            && self.grid.validate_with_rules(self.rules).is_ok()
+    }
+
+    /// Places a value and removes it from the candidates of every peer.
+    pub fn place(&mut self, x: usize, y: usize, value: CellValue) {
+        let index = y * 9 + x;
+        self.grid.set(value, x, y);
+        self.candidates[index].clear();
+        for unit in self.rules.units_at(index) {
+            for i in unit.cells(self.rules) {
+                self.candidates[i].remove(value);
+            }
+        }
+    }
+
+    pub fn eliminate(&mut self, x: usize, y: usize, value: CellValue) {
+        self.candidates[y * 9 + x].remove(value);
+    }
+
+    pub fn apply(&mut self, step: &Step) {
+        match &step.deduction {
+            Deduction::Place(c) => self.place(c.x, c.y, c.value),
+            Deduction::Eliminate(eliminations) => {
+                for c in eliminations {
+                    self.eliminate(c.x, c.y, c.value);
+                }
+            }
+        }
+    }
+
+    /// The next deduction using the easiest allowed technique that makes progress.
+    pub fn next_step(&self) -> Option<Step> {
+        self.techniques.iter().find_map(|t| self.find(t))
+    }
+
+    /// Applies steps until the grid is solved or no technique makes progress.
+    pub fn run(&mut self) -> Vec<Step> {
+        let mut steps = Vec::new();
+        while let Some(step) = self.next_step() {
+            self.apply(&step);
+            steps.push(step);
+        }
+        steps
+    }
+
+    /// Looks for an application of one particular technique.
+    pub fn find(&self, technique: Technique) -> Option<Step> {
+        match technique {
+            Technique::HiddenSingle => self.find_hidden_single(),
+            Technique::NakedSingle => self.find_naked_single(),
+            Technique::PointingPair => self.find_locked_candidates(true),
+            Technique::BoxLineReduction => self.find_locked_candidates(false),
+            Technique::NakedPair => self.find_naked_pair(),
+            Technique::HiddenPair => self.find_hidden_pair(),
+            Technique::XWing => self.find_x_wing(),
+        }
+    }
+
+    fn value_at(&self, i: usize) -> CellValue {
+        self.grid.get(i % 9, i / 9)
+    }
+
+    fn candidate(&self, i: usize, value: CellValue) -> Candidate {
+        Candidate {
+            x: i % 9,
+            y: i / 9,
+            value,
+        }
+    }
+
+    fn find_hidden_single(&self) -> Option<Step> {
+        for (name, unit) in &self.units {
+            for value in 1..=9 {
+                if unit.iter().any(|&i| self.value_at(i) == value) {
+                    continue;
+                }
+                let mut places = unit.iter().filter(|&&i| self.candidates[i].contains(value));
+                if let (Some(&i), None) = (places.next(), places.next()) {
+                    return Some(Step {
+                        technique: Technique::HiddenSingle,
+                        units: vec![*name],
+                        cells: vec![(i % 9, i / 9)],
+                        values: [value].into_iter().collect(),
+                        deduction: Deduction::Place(self.candidate(i, value)),
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    fn find_naked_single(&self) -> Option<Step> {
+        let i = (0..NUM_CELLS).find(|&i| self.candidates[i].count() == 1)?;
+        let (x, y) = (i % 9, i / 9);
+        let value = self.candidates[i].get_first()?;
+        Some(Step {
+            technique: Technique::NakedSingle,
+            units: vec![Unit::box_of(x, y)],
+            cells: vec![(x, y)],
+            values: self.candidates[i],
+            deduction: Deduction::Place(self.candidate(i, value)),
+        })
+    }
+
+    /// Pointing: a value confined to a box and a line is removed from the rest of the line.
+    /// Otherwise box/line reduction: confined to a line (or extra unit) and another unit.
+    fn find_locked_candidates(&self, pointing: bool) -> Option<Step> {
+        for (name_a, unit_a) in &self.units {
+            if matches!(name_a, Unit::Box(_)) != pointing {
+                continue;
+            }
+            for value in 1..=9 {
+                let places: Vec<usize> = unit_a
+                    .iter()
+                    .copied()
+                    .filter(|&i| self.candidates[i].contains(value))
+                    .collect();
+                if places.len() < 2 {
+                    continue;
+                }
+                for (name_b, unit_b) in &self.units {
+                    if name_b == name_a || !places.iter().all(|i| unit_b.contains(i)) {
+                        continue;
+                    }
+                    let eliminations: Vec<Candidate> = unit_b
+                        .iter()
+                        .filter(|&i| !unit_a.contains(i) && self.candidates[*i].contains(value))
+                        .map(|&i| self.candidate(i, value))
+                        .collect();
+                    if !eliminations.is_empty() {
+                        return Some(Step {
+                            technique: if pointing {
+                                Technique::PointingPair
+                            } else {
+                                Technique::BoxLineReduction
+                            },
+                            units: vec![*name_a, *name_b],
+                            cells: places.iter().map(|&i| (i % 9, i / 9)).collect(),
+                            values: [value].into_iter().collect(),
+                            deduction: Deduction::Eliminate(eliminations),
+                        });
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn find_naked_pair(&self) -> Option<Step> {
+        for (name, unit) in &self.units {
+            let pairs: Vec<usize> = unit
+                .iter()
+                .copied()
+                .filter(|&i| self.candidates[i].count() == 2)
+                .collect();
+            for (n, &a) in pairs.iter().enumerate() {
+                for &b in &pairs[n + 1..] {
+                    let values = self.candidates[a];
+                    if self.candidates[b] != values {
+                        continue;
+                    }
+                    let eliminations: Vec<Candidate> = unit
+                        .iter()
+                        .filter(|&&i| i != a && i != b)
+                        .flat_map(|&i| {
+                            values
+                                .into_iter()
+                                .filter(move |&v| self.candidates[i].contains(v))
+                                .map(move |v| self.candidate(i, v))
+                        })
+                        .collect();
+                    if !eliminations.is_empty() {
+                        return Some(Step {
+                            technique: Technique::NakedPair,
+                            units: vec![*name],
+                            cells: vec![(a % 9, a / 9), (b % 9, b / 9)],
+                            values,
+                            deduction: Deduction::Eliminate(eliminations),
+                        });
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn find_hidden_pair(&self) -> Option<Step> {
+        for (name, unit) in &self.units {
+            // Bit k of places[v] is set if unit[k] can hold v
+            let mut places = [0u16; 10];
+            for (k, &i) in unit.iter().enumerate() {
+                for v in self.candidates[i] {
+                    places[v as usize] |= 1 << k;
+                }
+            }
+            for v1 in 1..=9 {
+                for v2 in v1 + 1..=9 {
+                    if places[v1] != places[v2] || places[v1].count_ones() != 2 {
+                        continue;
+                    }
+                    let values: ValueSet = [v1 as CellValue, v2 as CellValue].into_iter().collect();
+                    let cells: Vec<usize> = (0..9)
+                        .filter(|k| places[v1] & (1 << k) != 0)
+                        .map(|k| unit[k])
+                        .collect();
+                    let eliminations: Vec<Candidate> = cells
+                        .iter()
+                        .flat_map(|&i| {
+                            self.candidates[i]
+                                .into_iter()
+                                .filter(|&v| !values.contains(v))
+                                .map(move |v| self.candidate(i, v))
+                        })
+                        .collect();
+                    if !eliminations.is_empty() {
+                        return Some(Step {
+                            technique: Technique::HiddenPair,
+                            units: vec![*name],
+                            cells: cells.iter().map(|&i| (i % 9, i / 9)).collect(),
+                            values,
+                            deduction: Deduction::Eliminate(eliminations),
+                        });
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn find_x_wing(&self) -> Option<Step> {
+        for value in 1..=9 {
+            for rows in [true, false] {
+                // Cell index for position k along line l
+                let index = |l: usize, k: usize| if rows { l * 9 + k } else { k * 9 + l };
+                let line = |l: usize| if rows { Unit::Row(l) } else { Unit::Col(l) };
+                let mut masks = [0u16; 9];
+                for l in 0..9 {
+                    for k in 0..9 {
+                        if self.candidates[index(l, k)].contains(value) {
+                            masks[l] |= 1 << k;
+                        }
+                    }
+                }
+                for l1 in 0..9 {
+                    if masks[l1].count_ones() != 2 {
+                        continue;
+                    }
+                    for l2 in l1 + 1..9 {
+                        if masks[l2] != masks[l1] {
+                            continue;
+                        }
+                        let crossing: Vec<usize> =
+                            (0..9).filter(|k| masks[l1] & (1 << k) != 0).collect();
+                        let eliminations: Vec<Candidate> = (0..9)
+                            .filter(|&l| l != l1 && l != l2)
+                            .flat_map(|l| crossing.iter().map(move |&k| index(l, k)))
+                            .filter(|&i| self.candidates[i].contains(value))
+                            .map(|i| self.candidate(i, value))
+                            .collect();
+                        if !eliminations.is_empty() {
+                            let corners = [l1, l2]
+                                .into_iter()
+                                .flat_map(|l| crossing.iter().map(move |&k| index(l, k)));
+                            return Some(Step {
+                                technique: Technique::XWing,
+                                units: vec![line(l1), line(l2)],
+                                cells: corners.map(|i| (i % 9, i / 9)).collect(),
+                                values: [value].into_iter().collect(),
+                                deduction: Deduction::Eliminate(eliminations),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+pub fn solve_logical(grid: &Grid) -> (Option<Grid>, Vec<Step>) {
+    solve_logical_with_rules(grid, &Rules::classic())
+}
+
+/// Solves the grid using only the named techniques. The steps taken are returned even
+/// if the techniques were not enough to solve the grid.
+pub fn solve_logical_with_rules(grid: &Grid, rules: &Rules) -> (Option<Grid>, Vec<Step>) {
+    solve_logical_with(grid, rules, TechniqueSet::all())
+}
+
+/// Like [solve_logical_with_rules] but only using `techniques`.
+pub fn solve_logical_with(
+    grid: &Grid,
+    rules: &Rules,
+    techniques: TechniqueSet,
+) -> (Option<Grid>, Vec<Step>) {
+    let mut solver = LogicSolver::new(grid, rules).with_techniques(techniques);
+    let steps = solver.run();
+    (solver.is_solved().then(|| *solver.grid()), steps)
+}
+
+/// Whether `techniques` alone are enough to solve the grid.
+pub fn solvable_with(grid: &Grid, rules: &Rules, techniques: TechniqueSet) -> bool {
+    solve_logical_with(grid, rules, techniques).0.is_some()
+}
+
+pub fn grade(grid: &Grid) -> Difficulty {
+    grade_with_rules(grid, &Rules::classic())
+}
+
+/// Rates a puzzle by the hardest technique needed to solve it, or [Difficulty::Expert]
+/// if the techniques are not enough.
+pub fn grade_with_rules(grid: &Grid, rules: &Rules) -> Difficulty {
+    match solve_logical_with_rules(grid, rules) {
+        (Some(_), steps) => steps
+            .iter()
+            .map(|s| s.technique.difficulty())
+            .max()
+            .unwrap_or(Difficulty::Easy),
+        (None, _) => Difficulty::Expert,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_grid, solve_recursive};
+
+    #[test]
+    fn solves_with_steps() {
+        let grid = parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap();
+        let (solved, steps) = solve_logical(&grid);
+        assert_eq!(solved, solve_recursive(grid));
+        let placements = steps
+            .iter()
+            .filter(|s| matches!(s.deduction, Deduction::Place(_)))
+            .count();
+        assert_eq!(placements, 81 - 17);
+        assert_eq!(grade(&grid), Difficulty::Medium);
+    }
+
+    #[test]
+    fn rejects_clashing_givens() {
+        let grid = parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap();
+        let mut full = solve_recursive(grid).unwrap();
+        let rules = Rules::classic();
+        assert_eq!(solve_logical(&full).0, Some(full));
+        full.set(full.get(1, 0), 0, 0);
+        assert_eq!(solve_logical(&full), (None, Vec::new()));
+        assert!(!solvable_with(&full, &rules, TechniqueSet::all()));
+        assert_eq!(grade(&full), Difficulty::Expert);
+    }
+
+    #[test]
+    fn restricted_techniques() {
+        let grid = parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap();
+        let rules = Rules::classic();
+        assert!(!solvable_with(&grid, &rules, TechniqueSet::singles()));
+        assert!(solvable_with(
+            &grid,
+            &rules,
+            TechniqueSet::up_to(Difficulty::Medium)
+        ));
+
+        let (_, steps) = solve_logical_with(&grid, &rules, TechniqueSet::singles());
+        assert!(steps.iter().all(|s| s.technique.tier() <= 2));
+
+        let set: TechniqueSet = "singles,pointing-pair,box-line-reduction".parse().unwrap();
+        assert_eq!(
+            set.to_string(),
+            "hidden single, naked single, pointing pair, box/line reduction"
+        );
+        assert_eq!("x-wing".parse(), Ok(Technique::XWing));
+        assert!("swordfish".parse::<TechniqueSet>().is_err());
+    }
+
+    #[test]
+    fn x_wing() {
+        // Value 1 is confined to columns 1 and 5 in rows 2 and 8
+        #[rustfmt::skip]
+        let grid = parse_grid("
+            . . . |. . . |. . .
+            . 2 3 |4 . 5 |6 7 8
+            . . . |. . . |. . .
+            ------+------+------
+            . . . |. . . |. . .
+            . . . |. . . |. . .
+            . . . |. . . |. . .
+            ------+------+------
+            . . . |. . . |. . .
+            . 4 5 |6 . 7 |8 9 2
+            . . . |. . . |. . .
+        ").unwrap();
+        let rules = Rules::classic();
+        let step = LogicSolver::new(&grid, &rules)
+            .find(Technique::XWing)
+            .unwrap();
+        assert_eq!(step.units, vec![Unit::Row(1), Unit::Row(7)]);
+        assert_eq!(step.cells, vec![(0, 1), (4, 1), (0, 7), (4, 7)]);
+        match step.deduction {
+            Deduction::Eliminate(eliminations) => {
+                assert_eq!(eliminations.len(), 14);
+                assert!(eliminations
+                    .iter()
+                    .all(|c| c.value == 1 && (c.x == 0 || c.x == 4)));
+            }
+            Deduction::Place(_) => panic!("X-wing only eliminates"),
+        }
+    }
+
+    #[test]
+    fn naked_pair() {
+        // Row 1 needs 1, 2 and 9, and the 9s below rule it out for the first two cells
+        #[rustfmt::skip]
+        let grid = parse_grid("
+            . . . |3 4 5 |6 7 8
+            . . . |. . . |. . .
+            . . . |. . . |. . .
+            ------+------+------
+            . . . |. . . |. . .
+            9 . . |. . . |. . .
+            . 9 . |. . . |. . .
+            ------+------+------
+            . . . |. . . |. . .
+            . . . |. . . |. . .
+            . . . |. . . |. . .
+        ").unwrap();
+        let rules = Rules::classic();
+        let step = LogicSolver::new(&grid, &rules)
+            .find(Technique::NakedPair)
+            .unwrap();
+        assert_eq!(step.units, vec![Unit::Box(0)]);
+        assert_eq!(step.cells, vec![(0, 0), (1, 0)]);
+        assert_eq!(step.values, [1, 2].into_iter().collect());
+    }
+}
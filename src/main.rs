@@ -1,56 +1,1432 @@
-pub mod lib;
+use clap::{App, AppSettings, Arg, ArgMatches};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::time::{Duration, Instant};
 
-use clap::{App, Arg};
-use std::time::Instant;
-
-use lib::{parse_grid, solve_recursive, solve_recursive_par};
+use sudoku::accessible::describe_grid_with_symbols;
+use sudoku::audit::audit;
+use sudoku::baseline::Baseline;
+use sudoku::batch::{parse_batch, solve_batch_with, tune_batch};
+use sudoku::board::{solve_board, solve_board_par, Board, BoardSymbols, MAX_BOX_SIZE};
+use sudoku::certificate::certify_unique;
+use sudoku::check::entry_conflicts_with_rules;
+use sudoku::compare::compare_solvers;
+use sudoku::conflict::explain_conflict;
+use sudoku::convert::{convert, ConvertFormat};
+use sudoku::curve::difficulty_curve_with_rules;
+use sudoku::dataset::{examples, generate_examples, to_csv, to_npy, Encoding};
+use sudoku::estimate::estimate_difficulty_with_rules;
+use sudoku::format::{parse_fpuzzles, Format};
+use sudoku::generator::{generate_from_template, generate_with_solution, Difficulty};
+use sudoku::heartbeat::solve_with_heartbeat;
+use sudoku::heatmap::solve_heatmap_with;
+use sudoku::hint::{find_hint_with_rules, Hint, HintLevel};
+use sudoku::job::{GenerationJob, Progress};
+use sudoku::logic::{grade_with_rules, solve_logical_with, TechniqueSet};
+use sudoku::printer::{BorderStyle, GridPrinter, HighlightStyle};
+use sudoku::record::{parse_records, today, write_records, PuzzleRecord, RecordFormat};
+use sudoku::repair::suggest_repairs;
+use sudoku::report::solve_with_report;
+use sudoku::template::{common_templates, Template};
+use sudoku::tournament::TournamentPack;
+use sudoku::transform::augment;
+use sudoku::verify::verify_batch;
+use sudoku::{
+    Grid, GridError, ParseOptions, ParseWarning, Rules, SearchOrder, Solver, SymbolSet, Variant,
+};
 
 fn main() -> Result<(), String> {
     let matches = App::new("Sudoku solver")
         .version("0.1")
         .about("Solves Sudokus")
+        .setting(AppSettings::SubcommandsNegateReqs)
         .arg(
             Arg::new("parallel")
                 .short('p')
                 .long("parallel")
                 .takes_value(false),
         )
+        .arg(
+            Arg::new("variant")
+                .about("Adds the extra units of a Sudoku variant (position)")
+                .long("variant")
+                .value_name("NAME")
+                .multiple_occurrences(true)
+                .global(true),
+        )
+        .arg(
+            Arg::new("symbols")
+                .about("Symbols for the values 1..9, optionally followed by the empty cell symbol")
+                .long("symbols")
+                .value_name("SYMBOLS")
+                .global(true),
+        )
+        .arg(
+            Arg::new("format")
+                .about("Format of the input grid and of printed grids. The variants of fpuzzles input are added to --variant")
+                .long("format")
+                .value_name("FORMAT")
+                .possible_values(["grid", "line", "sdk", "json", "fpuzzles"]),
+        )
+        .arg(
+            Arg::new("border")
+                .about("Lines drawn around the boxes of printed grids")
+                .long("border")
+                .value_name("STYLE")
+                .possible_values(["ascii", "unicode", "none"]),
+        )
+        .arg(
+            Arg::new("coordinates")
+                .about("Numbers the rows and columns of printed grids")
+                .long("coordinates")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::new("highlight")
+                .about("How cells of a hint stand out in printed grids")
+                .long("highlight")
+                .value_name("STYLE")
+                .possible_values(["brackets", "color"]),
+        )
+        .arg(
+            Arg::new("lenient")
+                .about("Skips unknown characters in the input grid and ignores cells after the first 81")
+                .long("lenient")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::new("deny_warnings")
+                .about("Fails instead of warning when the input grid had to be read leniently, e.g. with characters skipped")
+                .long("deny-warnings")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::new("accessible")
+                .about("Describes grids in words, suited for screen readers")
+                .long("accessible")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::new("hint")
+                .about("Shows a hint for the next step instead of solving: 1 nudge, 2 technique, 3 placement")
+                .long("hint")
+                .value_name("LEVEL")
+                .possible_values(["1", "2", "3"]),
+        )
+        .arg(
+            Arg::new("order")
+                .about("Search order: fewest (fastest) or lexicographic (reproducible solution order)")
+                .long("order")
+                .value_name("ORDER")
+                .possible_values(["fewest", "lexicographic"]),
+        )
+        .arg(
+            Arg::new("heartbeat")
+                .about("Reports the progress of the search every SECONDS, with an estimate of the time left")
+                .long("heartbeat")
+                .value_name("SECONDS")
+                .conflicts_with("parallel"),
+        )
+        .arg(
+            Arg::new("count")
+                .about("Counts solutions, stopping after CAP of them, instead of solving")
+                .long("count")
+                .value_name("CAP"),
+        )
+        .arg(
+            Arg::new("certify")
+                .about("Proves the solution unique with a case split tree anyone can check without searching")
+                .long("certify")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::new("stats")
+                .about("Counts the candidates each constraint removes, showing which rules do the work")
+                .long("stats")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::new("heatmap")
+                .about("Prints the order cells were solved in as a heatmap")
+                .long("heatmap")
+                .value_name("FORMAT")
+                .possible_values(["ansi", "svg"]),
+        )
+        .arg(
+            Arg::new("curve")
+                .about("Shows the technique tier needed for every step of a logical solve")
+                .long("curve")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::new("explain")
+                .about("Solves with human techniques only, explaining every step, and grades the puzzle")
+                .long("explain")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::new("techniques")
+                .about("Restricts --explain to these techniques, e.g. \"singles,pointing-pair\" or \"all\"")
+                .long("techniques")
+                .value_name("LIST")
+                .requires("explain"),
+        )
+        .arg(
+            Arg::new("generate")
+                .about("Generates a puzzle of the given difficulty instead of reading one")
+                .long("generate")
+                .value_name("DIFFICULTY")
+                .possible_values(["easy", "medium", "hard", "expert"]),
+        )
+        .arg(
+            Arg::new("template")
+                .about("Generates a puzzle with clues exactly at the cells marked by x, * or a digit in FILE")
+                .long("template")
+                .value_name("FILE")
+                .conflicts_with("generate"),
+        )
+        .arg(
+            Arg::new("solution")
+                .about("Also prints the solution of a generated puzzle")
+                .long("solution")
+                .takes_value(false)
+                .requires("generate"),
+        )
+        .arg(
+            Arg::new("augment")
+                .about("Prints N distinct equivalent puzzles made by relabelling, permuting and transposing the input")
+                .long("augment")
+                .value_name("N"),
+        )
+        .arg(
+            Arg::new("seed")
+                .about("Seeds the random generator for reproducible output")
+                .long("seed")
+                .value_name("SEED"),
+        )
+        .arg(
+            Arg::new("record")
+                .about("Prints the generated puzzle as a record with metadata instead of a grid")
+                .long("record")
+                .value_name("FORMAT")
+                .possible_values(["sdm", "json"])
+                .requires("generate"),
+        )
+        .arg(
+            Arg::new("title")
+                .about("Title of the generated record")
+                .long("title")
+                .value_name("TITLE")
+                .requires("record"),
+        )
+        .arg(
+            Arg::new("author")
+                .about("Author of the generated record")
+                .long("author")
+                .value_name("AUTHOR")
+                .requires("record"),
+        )
+        .arg(
+            Arg::new("tag")
+                .about("Adds a tag to the generated record")
+                .long("tag")
+                .value_name("TAG")
+                .multiple_occurrences(true)
+                .requires("record"),
+        )
+        .arg(
+            Arg::new("batch")
+                .about("Solves every puzzle of the input file, one per line or separated by blank lines, across all threads")
+                .long("batch")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::new("tune")
+                .about("Experimental: times each search configuration on the first SAMPLE puzzles of the batch and solves the rest with the fastest")
+                .long("tune")
+                .value_name("SAMPLE")
+                .requires("batch")
+                .conflicts_with("order"),
+        )
+        .arg(
+            Arg::new("output")
+                .about("Writes the batch solutions one per line, or as CSV if FILE ends in .csv")
+                .long("output")
+                .value_name("FILE")
+                .requires("batch"),
+        )
+        .arg(
+            Arg::new("box_size")
+                .about("Solves a board of N*N boxes of N*N cells, e.g. 4 for 16x16 with values 1-9 and A-G")
+                .long("box-size")
+                .value_name("N"),
+        )
+        .arg(
+            Arg::new("board_symbols")
+                .about("How board values are written: 1-9A-Z, 0-9A-Z (0-F for 16x16) or separated numbers")
+                .long("board-symbols")
+                .value_name("SYMBOLS")
+                .possible_values(["alphanumeric", "zero-based", "numbers"])
+                .requires("box_size"),
+        )
         .arg(
             Arg::new("input_file")
                 .about("Sets the input source file")
-                .required(true)
+                .required_unless_present_any(["generate", "template"])
                 .value_name("FILE")
                 .index(1),
         )
+        .subcommand(
+            App::new("convert")
+                .about("Converts a file of puzzles to another format, one record at a time")
+                .arg(
+                    Arg::new("input")
+                        .about("File to read, or - for standard input")
+                        .value_name("IN")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::new("output")
+                        .about("File to write, or - for standard output")
+                        .value_name("OUT")
+                        .required(true)
+                        .index(2),
+                )
+                .arg(
+                    Arg::new("from")
+                        .about("Format of the input, by default from its extension")
+                        .long("from")
+                        .value_name("FORMAT")
+                        .possible_values(["sdm", "json", "line", "grid", "sdk"]),
+                )
+                .arg(
+                    Arg::new("to")
+                        .about("Format of the output, by default from its extension")
+                        .long("to")
+                        .value_name("FORMAT")
+                        .possible_values(["sdm", "json", "line", "grid", "sdk"]),
+                ),
+        )
+        .subcommand(
+            App::new("audit")
+                .about("Checks that every puzzle of a collection is valid and has exactly one solution")
+                .arg(
+                    Arg::new("collection")
+                        .about("SDM, JSON or line file with the puzzles")
+                        .value_name("FILE")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::new("verdicts")
+                        .about("CSV file for the verdict on each puzzle, FILE.audit.csv by default")
+                        .long("verdicts")
+                        .value_name("OUT"),
+                ),
+        )
+        .subcommand(
+            App::new("verify")
+                .about("Checks solutions against their puzzles, both one per line")
+                .arg(
+                    Arg::new("puzzles")
+                        .about("File with the puzzles")
+                        .long("puzzles")
+                        .value_name("FILE")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("solutions")
+                        .about("File with the claimed solutions in the same order")
+                        .long("solutions")
+                        .value_name("FILE")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            App::new("bench")
+                .about("Times plain backtracking (A) against the propagating solver (B) on puzzles, one per line")
+                .arg(
+                    Arg::new("puzzles")
+                        .about("File with the puzzles")
+                        .value_name("FILE")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::new("save-baseline")
+                        .about("Saves the per-puzzle times of B as a baseline file")
+                        .long("save-baseline")
+                        .value_name("FILE"),
+                )
+                .arg(
+                    Arg::new("baseline")
+                        .about("Compares the times of B against a saved baseline and fails on a significant slowdown")
+                        .long("baseline")
+                        .value_name("FILE"),
+                ),
+        )
+        .subcommand(
+            App::new("rate")
+                .about("Rates every puzzle of an SDM or JSON record file and prints the records")
+                .arg(
+                    Arg::new("records")
+                        .about("File with the records")
+                        .value_name("FILE")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::new("estimate")
+                        .about("Estimates the ratings from a single propagation pass, much faster but less accurate")
+                        .long("estimate")
+                        .takes_value(false),
+                ),
+        )
+        .subcommand(
+            App::new("archive")
+                .about("Adds the records of a file to a collection, skipping puzzles already in it")
+                .arg(
+                    Arg::new("collection")
+                        .about("Collection to add to, JSON if it ends in .json and SDM otherwise")
+                        .long("collection")
+                        .value_name("FILE")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("records")
+                        .about("File with the records to add")
+                        .value_name("FILE")
+                        .required(true)
+                        .index(1),
+                ),
+        )
+        .subcommand(
+            App::new("patterns")
+                .about("Ranks the clue patterns of collections, up to rotation and reflection, by how often they occur")
+                .arg(
+                    Arg::new("collections")
+                        .about("Files with the puzzles, as records or one per line")
+                        .value_name("FILE")
+                        .required(true)
+                        .multiple_values(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::new("top")
+                        .about("How many patterns to show, 10 by default")
+                        .long("top")
+                        .value_name("N"),
+                )
+                .arg(
+                    Arg::new("save")
+                        .about("Writes each shown pattern to DIR as a template for --template")
+                        .long("save")
+                        .value_name("DIR"),
+                ),
+        )
+        .subcommand(
+            App::new("tournament")
+                .about("Generates a competition set of puzzles, each needing a harder technique than the one before")
+                .arg(
+                    Arg::new("size")
+                        .about("Number of puzzles, 6 by default")
+                        .long("size")
+                        .value_name("N"),
+                )
+                .arg(
+                    Arg::new("seed")
+                        .about("Seeds the random generator for reproducible output")
+                        .long("seed")
+                        .value_name("SEED"),
+                )
+                .arg(
+                    Arg::new("archive")
+                        .about("Leaves out puzzles equivalent to those in FILE, can be repeated")
+                        .long("archive")
+                        .value_name("FILE")
+                        .multiple_occurrences(true),
+                )
+                .arg(
+                    Arg::new("title")
+                        .about("Title of the pack, numbered for each puzzle")
+                        .long("title")
+                        .value_name("TITLE"),
+                ),
+        )
+        .subcommand(
+            App::new("generate-set")
+                .about("Generates many puzzles into an SDM file, skipping equivalent ones, and resumes if interrupted")
+                .arg(
+                    Arg::new("output")
+                        .about("SDM file the records are appended to")
+                        .value_name("FILE")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::new("count")
+                        .about("Number of puzzles the file should hold")
+                        .long("count")
+                        .value_name("N")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("difficulty")
+                        .about("Difficulty of the generated puzzles")
+                        .long("difficulty")
+                        .value_name("DIFFICULTY")
+                        .possible_values(["easy", "medium", "hard", "expert"]),
+                )
+                .arg(
+                    Arg::new("seed")
+                        .about("Seeds the random generator for reproducible output")
+                        .long("seed")
+                        .value_name("SEED"),
+                )
+                .arg(
+                    Arg::new("archive")
+                        .about("Skips puzzles equivalent to one of the records in FILE")
+                        .long("archive")
+                        .value_name("FILE")
+                        .multiple_occurrences(true),
+                )
+                .arg(
+                    Arg::new("progress")
+                        .about("Where the job keeps its progress, which it resumes from if present [default: FILE.progress]")
+                        .long("progress")
+                        .value_name("PROGRESS"),
+                ),
+        )
+        .subcommand(
+            App::new("dataset")
+                .about("Exports solved and rated puzzles as rows of numbers for machine learning")
+                .arg(
+                    Arg::new("puzzles")
+                        .about("File with the puzzles, one per line or separated by blank lines")
+                        .value_name("FILE")
+                        .required_unless_present("generate")
+                        .index(1),
+                )
+                .arg(
+                    Arg::new("generate")
+                        .about("Generates N puzzles instead of reading them")
+                        .long("generate")
+                        .value_name("N"),
+                )
+                .arg(
+                    Arg::new("difficulty")
+                        .about("Difficulty of the generated puzzles")
+                        .long("difficulty")
+                        .value_name("DIFFICULTY")
+                        .possible_values(["easy", "medium", "hard", "expert"])
+                        .requires("generate"),
+                )
+                .arg(
+                    Arg::new("seed")
+                        .about("Seeds the random generator for reproducible output")
+                        .long("seed")
+                        .value_name("SEED")
+                        .requires("generate"),
+                )
+                .arg(
+                    Arg::new("encoding")
+                        .about("Cells as digits 0-9 or one-hot columns")
+                        .long("encoding")
+                        .value_name("ENCODING")
+                        .possible_values(["digits", "one-hot"]),
+                )
+                .arg(
+                    Arg::new("output")
+                        .about("File to write, a NumPy array if it ends in .npy and CSV otherwise")
+                        .long("output")
+                        .value_name("FILE")
+                        .required(true),
+                ),
+        )
         .get_matches();
-    let filename: &str = matches.value_of("input_file").unwrap();
-    let run_parallel = matches.is_present("parallel");
+    let settings = Settings::new(&matches)?;
+    match matches.subcommand() {
+        Some(("convert", subcommand)) => run_convert(subcommand),
+        Some(("audit", subcommand)) => run_audit(subcommand, &settings),
+        Some(("verify", subcommand)) => run_verify(subcommand, &settings),
+        Some(("dataset", subcommand)) => run_dataset(subcommand, &settings),
+        Some(("bench", subcommand)) => run_bench(subcommand, &settings),
+        Some(("rate", subcommand)) => run_rate(subcommand, &settings),
+        Some(("archive", subcommand)) => run_archive(subcommand),
+        Some(("patterns", subcommand)) => run_patterns(subcommand),
+        Some(("tournament", subcommand)) => run_tournament(subcommand),
+        Some(("generate-set", subcommand)) => run_generate_set(subcommand),
+        _ if matches.is_present("template") => run_template(&matches, &settings),
+        _ if matches.is_present("generate") => run_generate(&matches, &settings),
+        _ => run_file(&matches, settings),
+    }
+}
+
+/// The global options, shared by the subcommands.
+struct Settings {
+    variants: Vec<Variant>,
+    rules: Rules,
+    order: SearchOrder,
+    format: Format,
+    symbols: SymbolSet,
+    printer: GridPrinter,
+    parallel: bool,
+    accessible: bool,
+}
+
+impl Settings {
+    fn new(matches: &ArgMatches) -> Result<Settings, String> {
+        let variants = matches
+            .values_of("variant")
+            .map(|names| names.map(str::parse).collect::<Result<Vec<Variant>, _>>())
+            .transpose()?
+            .unwrap_or_default();
+        let order: SearchOrder = matches
+            .value_of("order")
+            .map(str::parse)
+            .transpose()?
+            .unwrap_or_default();
+        let format: Format = matches
+            .value_of("format")
+            .map(str::parse)
+            .transpose()?
+            .unwrap_or_default();
+        let symbols: SymbolSet = matches
+            .value_of("symbols")
+            .map(str::parse)
+            .transpose()?
+            .unwrap_or_default();
+        let border: BorderStyle = matches
+            .value_of("border")
+            .map(str::parse)
+            .transpose()?
+            .unwrap_or_default();
+        let highlight: HighlightStyle = matches
+            .value_of("highlight")
+            .map(str::parse)
+            .transpose()?
+            .unwrap_or_default();
+        let printer = GridPrinter::new()
+            .symbols(symbols.clone())
+            .border(border)
+            .highlight_style(highlight)
+            .coordinates(matches.is_present("coordinates"));
+        Ok(Settings {
+            rules: Rules::new(&variants),
+            variants,
+            order,
+            format,
+            symbols,
+            printer,
+            parallel: matches.is_present("parallel"),
+            accessible: matches.is_present("accessible"),
+        })
+    }
+
+    /// The puzzle, or its solution if given, in the chosen format.
+    fn render_solution(&self, puzzle: &Grid, solution: Option<&Grid>) -> String {
+        if self.accessible {
+            format!(
+                "{}\n",
+                describe_grid_with_symbols(solution.unwrap_or(puzzle), &self.symbols)
+            )
+        } else if self.format == Format::Grid {
+            self.printer.print(solution.unwrap_or(puzzle))
+        } else {
+            self.format.write(puzzle, solution, &self.symbols)
+        }
+    }
+
+    fn render(&self, grid: &Grid) -> String {
+        self.render_solution(grid, None)
+    }
+}
+
+fn read(path: &str) -> Result<String, String> {
+    std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))
+}
+
+fn run_convert(conversion: &ArgMatches) -> Result<(), String> {
+    let format = |name: &str, path: &str| match conversion.value_of(name) {
+        Some(format) => format.parse(),
+        None => ConvertFormat::for_path(path)
+            .ok_or_else(|| format!("Cannot tell the format of {}, use --{}", path, name)),
+    };
+    let input = conversion.value_of("input").unwrap();
+    let output = conversion.value_of("output").unwrap();
+    let from = format("from", input)?;
+    let to = format("to", output)?;
+    let reader: Box<dyn std::io::BufRead> = match input {
+        "-" => Box::new(std::io::stdin().lock()),
+        path => Box::new(std::io::BufReader::new(
+            std::fs::File::open(path).map_err(|e| format!("{}: {}", path, e))?,
+        )),
+    };
+    let writer: Box<dyn std::io::Write> = match output {
+        "-" => Box::new(std::io::stdout()),
+        path => Box::new(std::io::BufWriter::new(
+            std::fs::File::create(path).map_err(|e| format!("{}: {}", path, e))?,
+        )),
+    };
+    let count = convert(reader, writer, from, to).map_err(|e| format!("{}: {}", input, e))?;
+    if output != "-" {
+        println!("Converted {} puzzles from {} to {}", count, from, to);
+    }
+    Ok(())
+}
+
+fn run_audit(audit_matches: &ArgMatches, settings: &Settings) -> Result<(), String> {
+    let path = audit_matches.value_of("collection").unwrap();
+    let content = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    let report = audit(&content, &settings.rules).map_err(|e| format!("{}: {}", path, e))?;
+    let verdicts = audit_matches
+        .value_of("verdicts")
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{}.audit.csv", path));
+    std::fs::write(&verdicts, report.to_csv()).map_err(|e| format!("{}: {}", verdicts, e))?;
+    print!("{}", report);
+    if !report.is_ok() {
+        return Err(format!(
+            "{} puzzle(s) in {} need fixing, see {}",
+            report.problems().count(),
+            path,
+            verdicts
+        ));
+    }
+    Ok(())
+}
+
+fn run_verify(verify: &ArgMatches, settings: &Settings) -> Result<(), String> {
+    let read = |name: &str| {
+        let path = verify.value_of(name).unwrap();
+        std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))
+    };
+    let report = verify_batch(
+        &read("puzzles")?,
+        &read("solutions")?,
+        &settings.symbols,
+        &settings.rules,
+    )?;
+    print!("{}", report);
+    if !report.is_ok() {
+        return Err(format!("{} solution(s) are wrong", report.mismatches.len()));
+    }
+    Ok(())
+}
+
+fn run_dataset(dataset: &ArgMatches, settings: &Settings) -> Result<(), String> {
+    let examples = match dataset.value_of("generate") {
+        Some(n) => {
+            if !settings.rules.is_classic() {
+                return Err("Generation only supports classic Sudoku".to_string());
+            }
+            let n: usize = n.parse().map_err(|e| format!("Invalid count: {}", e))?;
+            let difficulty: Difficulty = dataset
+                .value_of("difficulty")
+                .map(str::parse)
+                .transpose()?
+                .unwrap_or(Difficulty::Easy);
+            let mut rng = match dataset.value_of("seed") {
+                Some(seed) => {
+                    StdRng::seed_from_u64(seed.parse().map_err(|e| format!("Invalid seed: {}", e))?)
+                }
+                None => StdRng::from_entropy(),
+            };
+            generate_examples(n, difficulty, &mut rng)
+        }
+        None => {
+            let path = dataset.value_of("puzzles").unwrap();
+            let content = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+            let puzzles = ParseOptions::strict()
+                .symbols(settings.symbols.clone())
+                .parse_all(&content)
+                .map_err(|e| format!("Invalid puzzles in {}: {}", path, e))?;
+            examples(&puzzles, &settings.rules)
+        }
+    };
+    let encoding: Encoding = dataset
+        .value_of("encoding")
+        .map(str::parse)
+        .transpose()?
+        .unwrap_or_default();
+    let output = dataset.value_of("output").unwrap();
+    let content = if output.ends_with(".npy") {
+        to_npy(&examples, encoding)
+    } else {
+        to_csv(&examples, encoding).into_bytes()
+    };
+    std::fs::write(output, content).map_err(|e| format!("{}: {}", output, e))?;
+    println!("Wrote {} examples to {}", examples.len(), output);
+    Ok(())
+}
+
+fn run_bench(bench: &ArgMatches, settings: &Settings) -> Result<(), String> {
+    let path = bench.value_of("puzzles").unwrap();
+    let content = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    let puzzles = parse_batch(&content, &settings.symbols)?;
+    let solver = Solver::new()
+        .with_rules(settings.rules.clone())
+        .with_order(settings.order);
+    let report = compare_solvers(&solver.clone().propagate(false), &solver, &puzzles);
+    print!("{}", report);
+    let run = Baseline::from_report(&puzzles, &report);
+    if let Some(path) = bench.value_of("save-baseline") {
+        std::fs::write(path, run.to_csv()).map_err(|e| format!("{}: {}", path, e))?;
+    }
+    if let Some(path) = bench.value_of("baseline") {
+        let content = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        let baseline = Baseline::parse(&content).map_err(|e| format!("{}: {}", path, e))?;
+        let comparison = baseline.compare(&run)?;
+        println!("B is {}", comparison);
+        if comparison.is_regression() {
+            return Err(format!("Significant slowdown against {}", path));
+        }
+    }
+    Ok(())
+}
+
+fn run_rate(rate: &ArgMatches, settings: &Settings) -> Result<(), String> {
+    let path = rate.value_of("records").unwrap();
+    let content = read(path)?;
+    let mut records = parse_records(&content).map_err(|e| format!("{}: {}", path, e))?;
+    for record in &mut records {
+        if rate.is_present("estimate") {
+            record.rating = Some(estimate_difficulty_with_rules(
+                &record.grid,
+                &settings.rules,
+            ));
+        } else {
+            record.rate(&settings.rules);
+        }
+    }
+    print!(
+        "{}",
+        write_records(&records, RecordFormat::detect(&content))
+    );
+    Ok(())
+}
+
+fn run_archive(archive: &ArgMatches) -> Result<(), String> {
+    let path = archive.value_of("collection").unwrap();
+    let mut collection = match std::fs::read_to_string(path) {
+        Ok(content) => parse_records(&content).map_err(|e| format!("{}: {}", path, e))?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(format!("{}: {}", path, e)),
+    };
+    let input = archive.value_of("records").unwrap();
+    let records = parse_records(&read(input)?).map_err(|e| format!("{}: {}", input, e))?;
+    let (mut added, mut skipped) = (0, 0);
+    for record in records {
+        if collection.iter().any(|r| r.grid == record.grid) {
+            skipped += 1;
+        } else {
+            collection.push(record);
+            added += 1;
+        }
+    }
+    std::fs::write(
+        path,
+        write_records(&collection, RecordFormat::for_path(path)),
+    )
+    .map_err(|e| format!("{}: {}", path, e))?;
+    println!("Added {}, skipped {} already in {}", added, skipped, path);
+    Ok(())
+}
+
+fn run_patterns(patterns: &ArgMatches) -> Result<(), String> {
+    let top: usize = match patterns.value_of("top") {
+        Some(top) => top.parse().map_err(|e| format!("Invalid top: {}", e))?,
+        None => 10,
+    };
+    let mut grids = Vec::new();
+    for path in patterns.values_of("collections").unwrap() {
+        let records = parse_records(&read(path)?).map_err(|e| format!("{}: {}", path, e))?;
+        grids.extend(records.into_iter().map(|r| r.grid));
+    }
+    let templates = common_templates(&grids);
+    println!(
+        "{} distinct patterns in {} puzzles",
+        templates.len(),
+        grids.len()
+    );
+    for (n, counted) in templates.iter().take(top).enumerate() {
+        let header = format!(
+            "# {}: {} puzzles, {} clues, unchanged by {} of 8 orientations",
+            n + 1,
+            counted.count,
+            counted.template.clues(),
+            counted.template.symmetry_count()
+        );
+        print!("\n{}\n{}", header, counted.template);
+        if let Some(dir) = patterns.value_of("save") {
+            let path = std::path::Path::new(dir).join(format!("pattern-{}.txt", n + 1));
+            std::fs::write(&path, format!("{}\n{}", header, counted.template))
+                .map_err(|e| format!("{}: {}", path.display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+fn run_tournament(tournament: &ArgMatches) -> Result<(), String> {
+    let size: usize = match tournament.value_of("size") {
+        Some(size) => size.parse().map_err(|e| format!("Invalid size: {}", e))?,
+        None => 6,
+    };
+    let seed = match tournament.value_of("seed") {
+        Some(seed) => seed.parse().map_err(|e| format!("Invalid seed: {}", e))?,
+        None => rand::random(),
+    };
+    let mut pack = TournamentPack::new(size, seed);
+    for path in tournament.values_of("archive").into_iter().flatten() {
+        let archive = parse_records(&read(path)?).map_err(|e| format!("{}: {}", path, e))?;
+        pack.exclude(archive.iter().map(|r| &r.grid));
+    }
+    let title = tournament.value_of("title").unwrap_or("Puzzle");
+    let records: Vec<PuzzleRecord> = pack
+        .generate()?
+        .into_iter()
+        .enumerate()
+        .map(|(n, p)| {
+            let mut record = PuzzleRecord::new(p.generated.puzzle);
+            record.title = Some(format!("{} {}", title, n + 1));
+            record.source = Some(format!("tournament (seed {})", seed));
+            record.rating = Some(p.hardest.difficulty());
+            record.tags = vec![p.hardest.to_string()];
+            record.date = Some(today());
+            record
+        })
+        .collect();
+    print!("{}", write_records(&records, RecordFormat::Sdm));
+    Ok(())
+}
+
+fn run_generate_set(set: &ArgMatches) -> Result<(), String> {
+    let output = set.value_of("output").unwrap();
+    let progress_path = set
+        .value_of("progress")
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{}.progress", output));
+    let count: usize = set
+        .value_of("count")
+        .unwrap()
+        .parse()
+        .map_err(|e| format!("Invalid count: {}", e))?;
+    let existing = match std::fs::read_to_string(output) {
+        Ok(content) => parse_records(&content).map_err(|e| format!("{}: {}", output, e))?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(format!("{}: {}", output, e)),
+    };
+    let mut job = match std::fs::read_to_string(&progress_path) {
+        Ok(content) => {
+            let progress: Progress = content
+                .parse()
+                .map_err(|e| format!("{}: {}", progress_path, e))?;
+            println!(
+                "Resuming after {} puzzles from {}",
+                existing.len(),
+                progress_path
+            );
+            GenerationJob::resume(progress)
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let difficulty: Difficulty = set.value_of("difficulty").unwrap_or("medium").parse()?;
+            let seed = match set.value_of("seed") {
+                Some(seed) => seed.parse().map_err(|e| format!("Invalid seed: {}", e))?,
+                None => rand::random(),
+            };
+            GenerationJob::new(difficulty, seed)
+        }
+        Err(e) => return Err(format!("{}: {}", progress_path, e)),
+    };
+    job.exclude(existing.iter().map(|r| &r.grid));
+    for path in set.values_of("archive").into_iter().flatten() {
+        let archive = parse_records(&read(path)?).map_err(|e| format!("{}: {}", path, e))?;
+        job.exclude(archive.iter().map(|r| &r.grid));
+    }
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(output)
+        .map_err(|e| format!("{}: {}", output, e))?;
+    let progress = *job.progress();
+    // One batch per thread count keeps the progress file close behind the output, and
+    // the batches give the same puzzles however many threads there are
+    let mut remaining = count.saturating_sub(existing.len());
+    while remaining > 0 {
+        let batch = job.next_batch(remaining.min(rayon::current_num_threads()));
+        remaining -= batch.len();
+        for generated in batch {
+            let mut record = PuzzleRecord::new(generated.puzzle);
+            record.source = Some(format!("generated (seed {})", progress.seed));
+            record.rating = Some(progress.difficulty);
+            record.date = Some(today());
+            // Written one at a time, so the file is the same whatever the batch size
+            std::io::Write::write_all(
+                &mut file,
+                write_records(&[record], RecordFormat::Sdm).as_bytes(),
+            )
+            .map_err(|e| format!("{}: {}", output, e))?;
+        }
+        std::fs::write(&progress_path, job.progress().to_string())
+            .map_err(|e| format!("{}: {}", progress_path, e))?;
+    }
+    println!(
+        "{} holds {} puzzles, skipped {} duplicates",
+        output,
+        existing.len().max(count),
+        job.duplicates()
+    );
+    Ok(())
+}
 
+fn run_template(matches: &ArgMatches, settings: &Settings) -> Result<(), String> {
+    let path = matches.value_of("template").unwrap();
+    if !settings.rules.is_classic() {
+        return Err("Generation only supports classic Sudoku".to_string());
+    }
+    let template: Template = read(path)?
+        .parse()
+        .map_err(|e| format!("{}: {}", path, e))?;
+    let mut rng = match matches.value_of("seed") {
+        Some(seed) => {
+            StdRng::seed_from_u64(seed.parse().map_err(|e| format!("Invalid seed: {}", e))?)
+        }
+        None => StdRng::from_entropy(),
+    };
+    let generated = generate_from_template(&template, &mut rng)
+        .map_err(|e| format!("Template {} cannot be used: {}", path, e))?;
+    println!(
+        "Generated {} puzzle {} with {} clues:\n{}",
+        grade_with_rules(&generated.puzzle, &settings.rules),
+        generated.puzzle.fingerprint(),
+        template.clues(),
+        settings.render(&generated.puzzle)
+    );
+    Ok(())
+}
+
+fn run_generate(matches: &ArgMatches, settings: &Settings) -> Result<(), String> {
+    let difficulty = matches.value_of("generate").unwrap();
+    if !settings.rules.is_classic() {
+        return Err("Generation only supports classic Sudoku".to_string());
+    }
+    let difficulty: Difficulty = difficulty.parse()?;
+    let seed: Option<u64> = matches
+        .value_of("seed")
+        .map(|seed| seed.parse().map_err(|e| format!("Invalid seed: {}", e)))
+        .transpose()?;
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let generated = generate_with_solution(difficulty, &mut rng);
+    if let Some(format) = matches.value_of("record") {
+        let mut record = PuzzleRecord::new(generated.puzzle);
+        record.title = matches.value_of("title").map(str::to_string);
+        record.author = matches.value_of("author").map(str::to_string);
+        record.source = Some(match seed {
+            Some(seed) => format!("generated (seed {})", seed),
+            None => "generated".to_string(),
+        });
+        record.rating = Some(difficulty);
+        record.tags = matches
+            .values_of("tag")
+            .map(|tags| tags.map(str::to_string).collect())
+            .unwrap_or_default();
+        record.date = Some(today());
+        print!("{}", write_records(&[record], format.parse()?));
+        return Ok(());
+    }
+    println!(
+        "Generated {} puzzle {}:\n{}",
+        difficulty,
+        generated.puzzle.fingerprint(),
+        settings.render(&generated.puzzle)
+    );
+    if matches.is_present("solution") {
+        println!(
+            "Solution:\n{}",
+            settings.render_solution(&generated.puzzle, Some(&generated.solution))
+        );
+    }
+    Ok(())
+}
+
+fn run_file(matches: &ArgMatches, settings: Settings) -> Result<(), String> {
     // Load from file path
+    let filename: &str = matches.value_of("input_file").unwrap();
     let file_content = std::fs::read_to_string(filename).map_err(|e| e.to_string())?;
-    let grid = parse_grid(&file_content).ok_or("Unable to parse Sudoku grid from file")?;
+    if matches.is_present("box_size") {
+        return run_board(matches, &settings, filename, &file_content);
+    }
+
+    // The constraints of an f-puzzles file decide the variants, and unsupported ones fail here
+    let settings = if settings.format == Format::FPuzzles {
+        let (_, detected) = parse_fpuzzles(&file_content, &ParseOptions::lenient())
+            .map_err(|e| format!("Invalid grid in {}: {}", filename, e))?;
+        let missing: Vec<Variant> = detected
+            .into_iter()
+            .filter(|v| !settings.variants.contains(v))
+            .collect();
+        Settings {
+            rules: Rules::new(&[settings.variants.as_slice(), &missing].concat()),
+            ..settings
+        }
+    } else {
+        settings
+    };
+    let options = ParseOptions::strict()
+        .symbols(settings.symbols.clone())
+        .extra_characters(matches.is_present("lenient"))
+        .truncate(matches.is_present("lenient"));
+    if matches.is_present("batch") {
+        run_batch(matches, &settings, filename, &file_content, &options)
+    } else {
+        let grid = read_puzzle(matches, &settings, filename, &file_content, &options)?;
+        run_solve(matches, &settings, grid)
+    }
+}
+
+fn run_board(
+    matches: &ArgMatches,
+    settings: &Settings,
+    filename: &str,
+    file_content: &str,
+) -> Result<(), String> {
+    let box_size: usize = matches
+        .value_of("box_size")
+        .unwrap()
+        .parse()
+        .map_err(|e| format!("Invalid box size: {}", e))?;
+    if !(1..=MAX_BOX_SIZE).contains(&box_size) {
+        return Err(format!("Box size must be between 1 and {}", MAX_BOX_SIZE));
+    }
+    let board_symbols: BoardSymbols = matches
+        .value_of("board_symbols")
+        .map(str::parse)
+        .transpose()?
+        .unwrap_or_default();
+    let board = Board::parse_with(file_content, box_size, board_symbols)
+        .map_err(|e| format!("Invalid board in {}: {}", filename, e))?;
+    println!("Board Input:\n{}", board.display_with(board_symbols));
+    let start_time = Instant::now();
+    let solved = if settings.parallel {
+        solve_board_par(&board)
+    } else {
+        solve_board(&board)
+    };
+    println!("Time elapsed [ms]: {}", start_time.elapsed().as_millis());
+    match solved {
+        Some(solution) => println!("One solution is\n{}", solution.display_with(board_symbols)),
+        None => println!("Unable to solve puzzle"),
+    }
+    Ok(())
+}
+
+fn run_batch(
+    matches: &ArgMatches,
+    settings: &Settings,
+    filename: &str,
+    file_content: &str,
+    options: &ParseOptions,
+) -> Result<(), String> {
+    let (puzzles, warnings) = options
+        .parse_all_with_warnings(file_content)
+        .map_err(|e| format!("Invalid puzzles in {}: {}", filename, e))?;
+    check_warnings(matches, filename, warnings)?;
+    let solver = Solver::new()
+        .with_rules(settings.rules.clone())
+        .with_order(settings.order);
+    let report = match matches.value_of("tune") {
+        Some(sample) => {
+            let sample: usize = sample
+                .parse()
+                .map_err(|e| format!("Invalid sample size: {}", e))?;
+            let (tuning, report) = tune_batch(&solver, puzzles.into_iter(), sample);
+            print!("{}", tuning);
+            report
+        }
+        None => solve_batch_with(&solver, puzzles.into_iter()),
+    };
+    for result in &report.results {
+        match result.solution {
+            Some(_) => println!(
+                "#{}: solved in {:.3} ms",
+                result.index + 1,
+                result.time.as_secs_f64() * 1000.0
+            ),
+            None => println!("#{}: unable to solve", result.index + 1),
+        }
+    }
+    print!("{}", report);
+    if let Some(output) = matches.value_of("output") {
+        let content = if output.ends_with(".csv") {
+            report.to_csv()
+        } else {
+            report.to_lines()
+        };
+        std::fs::write(output, content).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn check_warnings(
+    matches: &ArgMatches,
+    filename: &str,
+    warnings: Vec<ParseWarning>,
+) -> Result<(), String> {
+    for warning in &warnings {
+        eprintln!("Warning: {}: {}", filename, warning);
+    }
+    if matches.is_present("deny_warnings") && !warnings.is_empty() {
+        return Err(format!(
+            "{} warning(s) reading {}, denied by --deny-warnings",
+            warnings.len(),
+            filename
+        ));
+    }
+    Ok(())
+}
+
+/// Reads the single puzzle of the file, printing the conflicts and possible repairs of a
+/// grid that breaks the rules.
+fn read_puzzle(
+    matches: &ArgMatches,
+    settings: &Settings,
+    filename: &str,
+    file_content: &str,
+    options: &ParseOptions,
+) -> Result<Grid, String> {
+    // Duplicates are reported below along with suggested repairs
+    let unchecked = options.clone().validate(false);
+    let (grid, warnings) = settings
+        .format
+        .parse_with_warnings(file_content, &unchecked)
+        .map_err(|e| match options.parse_all(file_content) {
+            Ok(grids) if grids.len() > 1 => format!(
+                "{} holds {} puzzles, use --batch to solve them all",
+                filename,
+                grids.len()
+            ),
+            _ => format!("Invalid grid in {}: {}", filename, e),
+        })?;
+    check_warnings(matches, filename, warnings)?;
+    grid.validate_with_rules(&settings.rules).map_err(|e| {
+        if let GridError::Duplicate { first, second, .. } = e {
+            // Each clash between the two cells is listed once, under the first
+            let conflicts: Vec<_> = [first, second]
+                .into_iter()
+                .flat_map(|(x, y)| {
+                    entry_conflicts_with_rules(&grid, x, y, &settings.rules)
+                        .into_iter()
+                        .map(move |c| ((x, y), c))
+                })
+                .filter(|&(entry, c)| !(entry == second && c.cell == first))
+                .collect();
+            let cells = conflicts.iter().flat_map(|&(entry, c)| [entry, c.cell]);
+            eprint!("{}", settings.printer.clone().highlight(cells).print(&grid));
+            for ((x, y), conflict) in &conflicts {
+                eprintln!(
+                    "Row {}, column {}: {}",
+                    y + 1,
+                    x + 1,
+                    conflict.describe(&settings.rules)
+                );
+            }
+        }
+        for repair in suggest_repairs(&grid, &settings.rules) {
+            eprintln!("Possible repair: {}", repair);
+        }
+        format!("Invalid grid in {}: {}", filename, e)
+    })?;
+    Ok(grid)
+}
 
-    if run_parallel {
+fn run_solve(matches: &ArgMatches, settings: &Settings, grid: Grid) -> Result<(), String> {
+    if settings.parallel {
         println!("Using parallism");
     }
-    println!("Grid Input:\n{}", grid);
+    for variant in settings.rules.variants() {
+        println!("Variant: {}", variant);
+    }
+    if !settings.rules.is_classic() {
+        println!("Rules: {}", settings.rules.summary());
+    }
+    println!("Grid Input:\n{}", settings.render(&grid));
+    if settings.rules.is_classic() {
+        println!("Fingerprint: {}", grid.fingerprint());
+    }
+
+    if let Some(n) = matches.value_of("augment") {
+        if !settings.rules.is_classic() {
+            return Err("Augmentation only supports classic Sudoku".to_string());
+        }
+        let n: usize = n.parse().map_err(|e| format!("Invalid count: {}", e))?;
+        let seed = match matches.value_of("seed") {
+            Some(seed) => seed.parse().map_err(|e| format!("Invalid seed: {}", e))?,
+            None => rand::random(),
+        };
+        for variant in augment(&grid, n, seed) {
+            print!(
+                "{}",
+                settings.format.write(&variant, None, &settings.symbols)
+            );
+        }
+        return Ok(());
+    }
+
+    if let Some(level) = matches.value_of("hint") {
+        let level = HintLevel::try_from(level.parse::<u8>().map_err(|e| e.to_string())?)?;
+        match find_hint_with_rules(&grid, &settings.rules) {
+            Some(hint) => {
+                println!("Hint: {}", hint.text(level, &settings.rules));
+                if settings.format == Format::Grid && !settings.accessible {
+                    let cells = hint.cells(level, &settings.rules);
+                    print!("{}", settings.printer.clone().highlight(cells).print(&grid));
+                }
+            }
+            None => println!("No hint available"),
+        }
+        return Ok(());
+    }
+
+    if matches.is_present("curve") {
+        let curve =
+            difficulty_curve_with_rules(&grid, &settings.rules).ok_or("Unable to solve puzzle")?;
+        println!("Difficulty curve: {}", curve);
+        println!(
+            "Peak tier {} in {} of {} steps, mean tier {:.2}",
+            curve.peak(),
+            curve.peak_steps(),
+            curve.steps.len(),
+            curve.mean_tier()
+        );
+        return Ok(());
+    }
+
+    if matches.is_present("explain") {
+        let techniques: TechniqueSet = matches
+            .value_of("techniques")
+            .map(str::parse)
+            .transpose()?
+            .unwrap_or_else(TechniqueSet::all);
+        let (solved, steps) = solve_logical_with(&grid, &settings.rules, techniques);
+        for (n, step) in steps.into_iter().enumerate() {
+            let hint = Hint { step };
+            println!(
+                "{}. {}",
+                n + 1,
+                hint.text(HintLevel::Placement, &settings.rules)
+            );
+        }
+        match solved {
+            Some(solution) => println!(
+                "Solved:\n{}",
+                settings.render_solution(&grid, Some(&solution))
+            ),
+            None if techniques == TechniqueSet::all() => {
+                println!("Stuck: no known technique applies")
+            }
+            None => println!("Stuck: {} are not enough", techniques),
+        }
+        println!("Difficulty: {}", grade_with_rules(&grid, &settings.rules));
+        return Ok(());
+    }
 
     let start_time = Instant::now();
 
-    let solved = if run_parallel {
-        solve_recursive_par(grid)
-    } else {
-        solve_recursive(grid)
+    let solver = Solver::new()
+        .with_rules(settings.rules.clone())
+        .with_order(settings.order)
+        .parallel(settings.parallel);
+    if let Some(cap) = matches.value_of("count") {
+        let cap: usize = cap.parse().map_err(|e| format!("Invalid cap: {}", e))?;
+        let count = solver.count_solutions(grid, cap);
+        println!("Time elapsed [ms]: {}", start_time.elapsed().as_millis());
+        match count {
+            0 => println!("No solutions"),
+            n if n >= cap => println!("At least {} solution(s)", n),
+            1 => println!("Unique solution"),
+            n => println!("{} solutions", n),
+        }
+        return Ok(());
+    }
+
+    if matches.is_present("certify") {
+        match certify_unique(&grid, &settings.rules) {
+            Some(certificate) => {
+                println!("Time elapsed [ms]: {}", start_time.elapsed().as_millis());
+                println!(
+                    "Unique solution, certificate with {} leaves:\n{}",
+                    certificate.proof.leaves(),
+                    certificate.proof
+                );
+                println!(
+                    "{}",
+                    settings.render_solution(&grid, Some(&certificate.solution))
+                );
+            }
+            None => println!("No unique solution"),
+        }
+        return Ok(());
+    }
+
+    if let Some(format) = matches.value_of("heatmap") {
+        let heatmap = solve_heatmap_with(&solver, &grid).ok_or("Unable to solve puzzle")?;
+        println!("Time elapsed [ms]: {}", start_time.elapsed().as_millis());
+        match format {
+            "svg" => print!("{}", heatmap.to_svg()),
+            _ => print!("{}", heatmap.to_ansi()),
+        }
+        return Ok(());
+    }
+
+    if matches.is_present("stats") {
+        let report = solve_with_report(&solver, &grid);
+        println!("Time elapsed [ms]: {}", start_time.elapsed().as_millis());
+        match report.solution {
+            Some(solution) => println!(
+                "One solution is\n{}",
+                settings.render_solution(&grid, Some(&solution))
+            ),
+            None => println!("Unable to solve puzzle"),
+        }
+        print!("{}", report);
+        return Ok(());
+    }
+
+    let solved = match matches.value_of("heartbeat") {
+        Some(seconds) => {
+            let seconds: f64 = seconds
+                .parse()
+                .map_err(|e| format!("Invalid heartbeat: {}", e))?;
+            let interval = Duration::try_from_secs_f64(seconds)
+                .map_err(|e| format!("Invalid heartbeat: {}", e))?;
+            solve_with_heartbeat(&solver, &grid, interval, |heartbeat| {
+                eprintln!("{}", heartbeat);
+                true
+            })
+            .expect("The search is never stopped")
+        }
+        None => solver.solve(grid),
     };
 
     println!("Time elapsed [ms]: {}", start_time.elapsed().as_millis());
 
     match solved {
         Some(solved_grid) => {
-            println!("One solution is\n{}", solved_grid);
+            println!(
+                "One solution is\n{}",
+                settings.render_solution(&grid, Some(&solved_grid))
+            );
         }
         None => {
             println!("Unable to solve puzzle");
+            if let Some(conflict) = explain_conflict(&grid, &settings.rules) {
+                println!("{}", conflict.text(&settings.rules));
+            }
         }
     };
 
@@ -0,0 +1,181 @@
+//! Hooks for following a solve as it happens, e.g. for logging, metrics or visualizations,
+//! without changing the solvers.
+
+use crate::logic::{Candidate, Deduction, LogicSolver, Step};
+use crate::{get_index, Grid, Rules, SolveState, Solver, Unit, EMPTY_CELL, NUM_CELLS};
+
+/// Something the solver did. Candidates removed from the peers of a placement are implied by
+/// it and not reported on their own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveEvent<'a> {
+    /// A value went into a cell, either as a guess or forced by the cells around it.
+    Placed { candidate: Candidate, guess: bool },
+    /// A technique removed a candidate.
+    Eliminated(Candidate),
+    /// A guess led to a contradiction and was undone along with everything placed since.
+    Backtracked(Candidate),
+    /// The logical solver found a step, reported before its placement or eliminations.
+    TechniqueApplied(&'a Step),
+    /// The last empty cell of a unit was filled, reported right after that placement, e.g. to
+    /// animate the completed row, column or box. In a play session the values need not be
+    /// right, and during a search a backtrack may empty the unit again.
+    UnitCompleted(Unit),
+}
+
+/// Receives the events of [solve_observed] and [solve_logical_observed]. Any
+/// `FnMut(&SolveEvent)` closure is an observer.
+pub trait SolveObserver {
+    fn observe(&mut self, event: &SolveEvent);
+}
+
+impl<F: FnMut(&SolveEvent)> SolveObserver for F {
+    fn observe(&mut self, event: &SolveEvent) {
+        self(event)
+    }
+}
+
+/// Solves the grid sequentially like [Solver::solve], reporting every placement and backtrack.
+pub fn solve_observed(
+    solver: &Solver,
+    grid: &Grid,
+    observer: &mut impl SolveObserver,
+) -> Option<Grid> {
+    let state = SolveState::new(*grid, solver)?;
+    report_filled(grid, &state.grid, solver.rules(), observer);
+    observe_recursive(state, observer)
+}
+
+fn observe_recursive(state: SolveState, observer: &mut impl SolveObserver) -> Option<Grid> {
+    if state.is_solved() {
+        return Some(state.grid);
+    }
+    if let Some((cands, x, y)) = state.get_candidate() {
+        for value in cands {
+            let candidate = Candidate { x, y, value };
+            observer.observe(&SolveEvent::Placed {
+                candidate,
+                guess: true,
+            });
+            let mut before = state.grid;
+            before.set(value, x, y);
+            report_completed(&before, candidate, state.rules, observer);
+            if let Some(branch) = state.assign(value, x, y) {
+                report_filled(&before, &branch.grid, state.rules, observer);
+                if let Some(solution) = observe_recursive(branch, observer) {
+                    return Some(solution);
+                }
+            }
+            observer.observe(&SolveEvent::Backtracked(candidate));
+        }
+    }
+    None
+}
+
+// Reports the cells empty in `before` but filled in `after` as forced placements
+fn report_filled(before: &Grid, after: &Grid, rules: &Rules, observer: &mut impl SolveObserver) {
+    let mut grid = *before;
+    for i in 0..NUM_CELLS {
+        let (x, y) = (i % 9, i / 9);
+        if before.get(x, y) == EMPTY_CELL && after.get(x, y) != EMPTY_CELL {
+            let candidate = Candidate {
+                x,
+                y,
+                value: after.get(x, y),
+            };
+            observer.observe(&SolveEvent::Placed {
+                candidate,
+                guess: false,
+            });
+            grid.set(candidate.value, x, y);
+            report_completed(&grid, candidate, rules, observer);
+        }
+    }
+}
+
+/// Reports the units of the cell just filled by `placed` which `grid` has no empty cells left in.
+pub(crate) fn report_completed(
+    grid: &Grid,
+    placed: Candidate,
+    rules: &Rules,
+    observer: &mut impl SolveObserver,
+) {
+    for unit in rules.units_at(get_index(placed.x, placed.y)) {
+        let cells = unit.cells(rules);
+        if cells.iter().all(|&i| grid.get(i % 9, i / 9) != EMPTY_CELL) {
+            observer.observe(&SolveEvent::UnitCompleted(unit));
+        }
+    }
+}
+
+/// Runs the logical solver like [LogicSolver::run], reporting each step along with its
+/// placement or eliminations.
+pub fn solve_logical_observed(
+    solver: &mut LogicSolver,
+    observer: &mut impl SolveObserver,
+) -> Vec<Step> {
+    let mut steps = Vec::new();
+    while let Some(step) = solver.next_step() {
+        observer.observe(&SolveEvent::TechniqueApplied(&step));
+        match &step.deduction {
+            Deduction::Place(candidate) => observer.observe(&SolveEvent::Placed {
+                candidate: *candidate,
+                guess: false,
+            }),
+            Deduction::Eliminate(removed) => {
+                for &candidate in removed {
+                    observer.observe(&SolveEvent::Eliminated(candidate));
+                }
+            }
+        }
+        solver.apply(&step);
+        if let Deduction::Place(candidate) = step.deduction {
+            report_completed(solver.grid(), candidate, solver.rules(), observer);
+        }
+        steps.push(step);
+    }
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_grid, Rules};
+
+    #[test]
+    fn observers_see_every_placement() {
+        let grid = parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap();
+        // Undoing a guess takes back everything placed since
+        let mut placed: Vec<Candidate> = Vec::new();
+        let solution = solve_observed(
+            &Solver::new(),
+            &grid,
+            &mut |event: &SolveEvent| match *event {
+                SolveEvent::Placed { candidate, .. } => placed.push(candidate),
+                SolveEvent::Backtracked(guess) => while placed.pop() != Some(guess) {},
+                _ => {}
+            },
+        )
+        .unwrap();
+        assert_eq!(placed.len(), 81 - 17);
+        assert!(placed
+            .iter()
+            .all(|c| solution.get(c.x, c.y) == c.value && grid.get(c.x, c.y) == EMPTY_CELL));
+
+        let rules = Rules::classic();
+        let mut solver = LogicSolver::new(&grid, &rules);
+        let (mut techniques, mut eliminated) = (0, 0);
+        let mut completed = Vec::new();
+        let steps = solve_logical_observed(&mut solver, &mut |event: &SolveEvent| match event {
+            SolveEvent::TechniqueApplied(_) => techniques += 1,
+            SolveEvent::Eliminated(_) => eliminated += 1,
+            SolveEvent::UnitCompleted(unit) => completed.push(*unit),
+            _ => {}
+        });
+        assert_eq!(techniques, steps.len());
+        assert!(eliminated > 0);
+        // Without guesses every unit is completed exactly once
+        assert_eq!(completed.len(), rules.units().count());
+        assert!(rules.units().all(|unit| completed.contains(&unit)));
+        assert!(solver.is_solved());
+    }
+}
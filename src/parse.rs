@@ -0,0 +1,327 @@
+//! How strictly grids are read from text.
+
+use crate::{CellValue, Grid, GridError, ParseWarning, SymbolSet, EMPTY_CELL, NUM_CELLS};
+
+/// Options for reading a grid, e.g. `ParseOptions::strict().single_line(true).empty(&['.'])`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOptions {
+    symbols: SymbolSet,
+    // Overrides the empty cell symbols of `symbols`
+    empty: Option<Vec<char>>,
+    extra_characters: bool,
+    single_line: bool,
+    truncate: bool,
+    validate: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions::strict()
+    }
+}
+
+impl ParseOptions {
+    /// Rejects unknown characters, anything but 81 cells and grids breaking the classic
+    /// rules, like [crate::parse_grid_checked].
+    pub fn strict() -> ParseOptions {
+        ParseOptions {
+            symbols: SymbolSet::digits(),
+            empty: None,
+            extra_characters: false,
+            single_line: false,
+            truncate: false,
+            validate: true,
+        }
+    }
+
+    /// Skips unknown characters and does not check the rules, like [crate::parse_grid].
+    pub fn lenient() -> ParseOptions {
+        ParseOptions::strict()
+            .extra_characters(true)
+            .validate(false)
+    }
+
+    pub fn symbols(mut self, symbols: SymbolSet) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
+    /// Only these characters denote an empty cell, e.g. `&['.']` to reject '0'.
+    /// Defaults to the empty cell symbols of the symbol set.
+    pub fn empty(mut self, empty: &[char]) -> Self {
+        self.empty = Some(empty.to_vec());
+        self
+    }
+
+    /// Skip characters which are neither symbols nor layout rather than rejecting them.
+    /// Layout is whitespace and the '|', '+' and '-' of box borders.
+    pub fn extra_characters(mut self, allow: bool) -> Self {
+        self.extra_characters = allow;
+        self
+    }
+
+    /// Require all 81 cells on a single line without layout, rather than allowing
+    /// a row per line.
+    pub fn single_line(mut self, single_line: bool) -> Self {
+        self.single_line = single_line;
+        self
+    }
+
+    /// Take the first 81 cells and ignore the rest, rather than failing on text with more
+    /// cells, e.g. a file with two puzzles. [ParseOptions::parse_all] does the same for every
+    /// grid, taking the first 81 cells of a block of lines that holds too many.
+    pub fn truncate(mut self, truncate: bool) -> Self {
+        self.truncate = truncate;
+        self
+    }
+
+    /// Check the grid against the classic rules.
+    pub fn validate(mut self, validate: bool) -> Self {
+        self.validate = validate;
+        self
+    }
+
+    pub fn parse(&self, text: &str) -> Result<Grid, GridError> {
+        self.parse_with_warnings(text).map(|(grid, _)| grid)
+    }
+
+    /// Like [ParseOptions::parse], also returning what the options let through: skipped
+    /// characters and cells after the first 81.
+    pub fn parse_with_warnings(&self, text: &str) -> Result<(Grid, Vec<ParseWarning>), GridError> {
+        if self.single_line {
+            let lines = text.lines().filter(|l| !l.trim().is_empty()).count();
+            if lines > 1 {
+                return Err(GridError::MultipleLines { found: lines });
+            }
+        }
+        let mut warnings = Vec::new();
+        let mut nums: Vec<CellValue> = Vec::with_capacity(NUM_CELLS);
+        let mut found = 0;
+        for (line, content) in text.lines().enumerate() {
+            if self.truncate && found >= NUM_CELLS {
+                // Ignored lines are only counted, so they may hold anything
+                found += self
+                    .line_cells(line + 1, content, &mut Vec::new())
+                    .map_or(0, |cells| cells.len());
+                continue;
+            }
+            nums.extend(self.line_cells(line + 1, content, &mut warnings)?);
+            found = nums.len();
+        }
+        if self.truncate && found > NUM_CELLS {
+            warnings.push(ParseWarning::ExtraCells { found });
+            nums.truncate(NUM_CELLS);
+        }
+        Ok((self.grid(&nums)?, warnings))
+    }
+
+    /// Reads every grid of `text`. Grids are separated by blank lines, and a block of lines
+    /// holding 81 cells each is read as one grid per line. Lines starting with '#' are skipped.
+    pub fn parse_all(&self, text: &str) -> Result<Vec<Grid>, GridError> {
+        self.parse_all_with_warnings(text).map(|(grids, _)| grids)
+    }
+
+    /// Like [ParseOptions::parse_all], also returning the characters skipped on any line.
+    pub fn parse_all_with_warnings(
+        &self,
+        text: &str,
+    ) -> Result<(Vec<Grid>, Vec<ParseWarning>), GridError> {
+        let mut grids = Vec::new();
+        let mut warnings = Vec::new();
+        let mut block: Vec<Vec<CellValue>> = Vec::new();
+        for (line, content) in text.lines().enumerate() {
+            let trimmed = content.trim();
+            if trimmed.starts_with('#') {
+                continue;
+            }
+            if trimmed.is_empty() {
+                self.split_block(&block, &mut grids, &mut warnings)?;
+                block.clear();
+            } else {
+                block.push(self.line_cells(line + 1, content, &mut warnings)?);
+            }
+        }
+        self.split_block(&block, &mut grids, &mut warnings)?;
+        Ok((grids, warnings))
+    }
+
+    // Adds the grids of a block of consecutive non-blank lines, given as the cells of each line
+    fn split_block(
+        &self,
+        block: &[Vec<CellValue>],
+        grids: &mut Vec<Grid>,
+        warnings: &mut Vec<ParseWarning>,
+    ) -> Result<(), GridError> {
+        let total: usize = block.iter().map(Vec::len).sum();
+        if total == NUM_CELLS && !(self.single_line && block.len() > 1) {
+            grids.push(self.grid(&block.concat())?);
+        } else if block.iter().all(|l| l.is_empty() || l.len() == NUM_CELLS) {
+            for line in block.iter().filter(|l| !l.is_empty()) {
+                grids.push(self.grid(line)?);
+            }
+        } else if self.truncate && total > NUM_CELLS && !(self.single_line && block.len() > 1) {
+            warnings.push(ParseWarning::ExtraCells { found: total });
+            grids.push(self.grid(&block.concat()[..NUM_CELLS])?);
+        } else {
+            return Err(GridError::WrongCellCount { found: total });
+        }
+        Ok(())
+    }
+
+    // The cells on one line, `line` being 1-based
+    fn line_cells(
+        &self,
+        line: usize,
+        content: &str,
+        warnings: &mut Vec<ParseWarning>,
+    ) -> Result<Vec<CellValue>, GridError> {
+        let mut cells = Vec::new();
+        let mut skipped: Option<ParseWarning> = None;
+        // Surrounding whitespace is fine even on a single line
+        let start = content.len() - content.trim_start().len();
+        let end = content.trim_end().len();
+        for (column, (byte, c)) in content.char_indices().enumerate() {
+            let layout = if self.single_line {
+                c.is_whitespace() && !(start..end).contains(&byte)
+            } else {
+                c.is_whitespace() || matches!(c, '|' | '+' | '-')
+            };
+            match self.value(c) {
+                Some(value) => cells.push(value),
+                None if layout => {}
+                None if self.extra_characters => match &mut skipped {
+                    Some(ParseWarning::SkippedCharacters { count, .. }) => *count += 1,
+                    _ => {
+                        skipped = Some(ParseWarning::SkippedCharacters {
+                            count: 1,
+                            first: c,
+                            line,
+                            column: column + 1,
+                        })
+                    }
+                },
+                None => {
+                    return Err(GridError::InvalidCharacter {
+                        character: c,
+                        line,
+                        column: column + 1,
+                    })
+                }
+            }
+        }
+        warnings.extend(skipped);
+        Ok(cells)
+    }
+
+    fn grid(&self, nums: &[CellValue]) -> Result<Grid, GridError> {
+        if nums.len() != NUM_CELLS {
+            return Err(GridError::WrongCellCount { found: nums.len() });
+        }
+        let grid = Grid::new(nums);
+        if self.validate {
+            grid.validate()?;
+        }
+        Ok(grid)
+    }
+
+    fn value(&self, c: char) -> Option<CellValue> {
+        match &self.empty {
+            Some(empty) if empty.contains(&c) => Some(EMPTY_CELL),
+            Some(_) => self.symbols.value(c).filter(|&v| v != EMPTY_CELL),
+            None => self.symbols.value(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leniency() {
+        let line = format!("12345678{}", ".".repeat(73));
+        let rows: String = line
+            .as_bytes()
+            .chunks(9)
+            .map(|row| format!("{}\n", std::str::from_utf8(row).unwrap()))
+            .collect();
+        let strict = ParseOptions::strict();
+        assert_eq!(strict.parse(&rows), strict.parse(&line));
+        assert_eq!(
+            strict.clone().single_line(true).parse(&rows),
+            Err(GridError::MultipleLines { found: 9 })
+        );
+        assert!(strict.clone().single_line(true).parse(&line).is_ok());
+
+        let with_notes = format!("Puzzle: {}", line);
+        assert!(matches!(
+            strict.parse(&with_notes),
+            Err(GridError::InvalidCharacter { character: 'P', .. })
+        ));
+        assert!(ParseOptions::lenient().parse(&with_notes).is_ok());
+
+        let zeros = line.replace('.', "0");
+        assert!(strict.parse(&zeros).is_ok());
+        assert!(strict.clone().empty(&['.']).parse(&zeros).is_err());
+
+        // Two puzzles in one file
+        let two = format!("{}\n{}", line, line);
+        assert_eq!(
+            strict.parse(&two),
+            Err(GridError::WrongCellCount { found: 162 })
+        );
+        assert_eq!(
+            strict.clone().truncate(true).parse(&two),
+            strict.parse(&line)
+        );
+
+        // Whatever leniency let through is reported
+        let (_, warnings) = ParseOptions::lenient()
+            .truncate(true)
+            .parse_with_warnings(&format!("{}\n{}", with_notes, line))
+            .unwrap();
+        assert_eq!(
+            warnings,
+            [
+                ParseWarning::SkippedCharacters {
+                    count: 7,
+                    first: 'P',
+                    line: 1,
+                    column: 1
+                },
+                ParseWarning::ExtraCells { found: 162 }
+            ]
+        );
+        assert_eq!(
+            strict.parse_with_warnings(&line),
+            Ok((strict.parse(&line).unwrap(), vec![]))
+        );
+    }
+
+    #[test]
+    fn several_grids() {
+        let line = format!("12345678{}", ".".repeat(73));
+        let rows: String = line
+            .as_bytes()
+            .chunks(9)
+            .map(|row| format!("|{}|\n", std::str::from_utf8(row).unwrap()))
+            .collect();
+        let grid = ParseOptions::strict().parse(&line).unwrap();
+
+        let text = format!("# Two lines\n{}\n{}\n\n{}\n{}", line, line, rows, rows);
+        let strict = ParseOptions::strict();
+        assert_eq!(strict.parse_all(&text), Ok(vec![grid; 4]));
+        assert_eq!(
+            strict.parse_all(&format!("{}{}", rows, line)),
+            Err(GridError::WrongCellCount { found: 162 })
+        );
+        assert_eq!(
+            strict
+                .clone()
+                .truncate(true)
+                .parse_all_with_warnings(&format!("{}{}\n\n{}", rows, line, line)),
+            Ok((vec![grid; 2], vec![ParseWarning::ExtraCells { found: 162 }]))
+        );
+        assert_eq!(strict.parse_all(""), Ok(Vec::new()));
+    }
+}
@@ -0,0 +1,298 @@
+//! A player's progress on a puzzle: entries and pencil marks, updated one [Move] at a time.
+
+use crate::check::Move;
+use crate::logic::Candidate;
+use crate::observer::{report_completed, SolveEvent, SolveObserver};
+use crate::{
+    get_candidates_with_rules, get_index, CellValue, Grid, Rules, ValueSet, EMPTY_CELL, NUM_CELLS,
+};
+
+/// The player's grid and pencil marks. With auto notes on, the marks of every empty cell are
+/// its candidates, so placing or clearing a value updates the marks of its peers; the player's
+/// own eliminations stay on top of that. With auto notes off the marks only change through
+/// [Move::Eliminate] and [Move::Restore].
+#[derive(Debug, Clone)]
+pub struct PlayState {
+    puzzle: Grid,
+    grid: Grid,
+    // Marks the player removed, on top of those removed by auto notes
+    eliminated: [ValueSet; NUM_CELLS],
+    // Candidates of every empty cell, kept up to date move by move whether auto notes are on
+    // or not, so turning them on shows the right marks at once
+    candidates: [ValueSet; NUM_CELLS],
+    auto_notes: bool,
+    rules: Rules,
+}
+
+impl PlayState {
+    pub fn new(puzzle: Grid) -> PlayState {
+        PlayState::with_rules(puzzle, Rules::classic())
+    }
+
+    pub fn with_rules(puzzle: Grid, rules: Rules) -> PlayState {
+        let candidates =
+            std::array::from_fn(|i| get_candidates_with_rules(&puzzle, i % 9, i / 9, &rules));
+        PlayState {
+            puzzle,
+            grid: puzzle,
+            eliminated: [ValueSet::empty(); NUM_CELLS],
+            candidates,
+            auto_notes: false,
+            rules,
+        }
+    }
+
+    /// Turns auto notes on or off, see [PlayState::set_auto_notes].
+    pub fn auto_notes(mut self, on: bool) -> Self {
+        self.set_auto_notes(on);
+        self
+    }
+
+    /// Turning auto notes off keeps the marks as they are, so nothing reappears; from then on
+    /// they are the player's to maintain.
+    pub fn set_auto_notes(&mut self, on: bool) {
+        if self.auto_notes && !on {
+            for i in 0..NUM_CELLS {
+                let marks = self.marks(i % 9, i / 9);
+                self.eliminated[i] = ValueSet::full()
+                    .into_iter()
+                    .filter(|&v| !marks.contains(v))
+                    .collect();
+            }
+        }
+        self.auto_notes = on;
+    }
+
+    pub fn is_auto_notes(&self) -> bool {
+        self.auto_notes
+    }
+
+    /// The givens along with the player's entries.
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    /// The pencil marks of the cell at (x, y), empty if the cell is filled.
+    pub fn marks(&self, x: usize, y: usize) -> ValueSet {
+        if self.grid.get(x, y) != EMPTY_CELL {
+            return ValueSet::empty();
+        }
+        let shown = if self.auto_notes {
+            self.candidates[get_index(x, y)]
+        } else {
+            ValueSet::full()
+        };
+        let eliminated = self.eliminated[get_index(x, y)];
+        shown
+            .into_iter()
+            .filter(|&v| !eliminated.contains(v))
+            .collect()
+    }
+
+    /// Applies a move. Moves changing a given or naming a cell or value outside the grid are
+    /// refused.
+    pub fn apply(&mut self, m: Move) -> Result<(), String> {
+        self.apply_observed(m, &mut |_: &SolveEvent| {})
+    }
+
+    /// Like [PlayState::apply], reporting a placement as [SolveEvent::Placed] followed by a
+    /// [SolveEvent::UnitCompleted] for every unit it fills.
+    pub fn apply_observed(
+        &mut self,
+        m: Move,
+        observer: &mut impl SolveObserver,
+    ) -> Result<(), String> {
+        let (x, y, value) = match m {
+            Move::Place { x, y, value }
+            | Move::Eliminate { x, y, value }
+            | Move::Restore { x, y, value } => (x, y, Some(value)),
+            Move::Clear { x, y } => (x, y, None),
+        };
+        if x >= 9 || y >= 9 {
+            return Err(format!(
+                "Row {}, column {} is outside the grid",
+                y + 1,
+                x + 1
+            ));
+        }
+        if let Some(value) = value.filter(|v| !(1..=9).contains(v)) {
+            return Err(format!("Value {} is not between 1 and 9", value));
+        }
+        if self.puzzle.get(x, y) != EMPTY_CELL {
+            return Err(format!("Row {}, column {} is a given", y + 1, x + 1));
+        }
+        let index = get_index(x, y);
+        let eliminated = &mut self.eliminated[index];
+        match m {
+            Move::Place { value, .. } => {
+                let replaced = self.grid.get(x, y);
+                self.grid.set(value, x, y);
+                if replaced == EMPTY_CELL {
+                    self.remove_from_peers(index, value);
+                } else {
+                    self.refresh_peers(index);
+                }
+                let candidate = Candidate { x, y, value };
+                observer.observe(&SolveEvent::Placed {
+                    candidate,
+                    guess: false,
+                });
+                report_completed(&self.grid, candidate, &self.rules, observer);
+            }
+            Move::Clear { .. } => {
+                self.grid.set(EMPTY_CELL, x, y);
+                self.refresh_peers(index);
+            }
+            Move::Eliminate { value, .. } => eliminated.add(value),
+            Move::Restore { value, .. } => eliminated.remove(value),
+        }
+        Ok(())
+    }
+
+    fn remove_from_peers(&mut self, index: usize, value: CellValue) {
+        self.candidates[index] = ValueSet::empty();
+        for unit in self.rules.units_at(index) {
+            for i in unit.cells(&self.rules) {
+                self.candidates[i].remove(value);
+            }
+        }
+    }
+
+    // A removed value may be a candidate again for the cell and its peers, unless another
+    // peer of theirs still holds it, so their candidates are worked out afresh
+    fn refresh_peers(&mut self, index: usize) {
+        for unit in self.rules.units_at(index) {
+            for i in unit.cells(&self.rules) {
+                self.candidates[i] =
+                    get_candidates_with_rules(&self.grid, i % 9, i / 9, &self.rules);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_grid;
+
+    #[test]
+    fn auto_notes_follow_placements() {
+        let puzzle = parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap();
+        let mut state = PlayState::new(puzzle).auto_notes(true);
+        assert_eq!(
+            state.marks(1, 0),
+            get_candidates_with_rules(&puzzle, 1, 0, &Rules::classic())
+        );
+
+        let value = state.marks(1, 0).get_first().unwrap();
+        state.apply(Move::Place { x: 2, y: 0, value }).unwrap();
+        assert!(!state.marks(1, 0).contains(value));
+        state
+            .apply(Move::Eliminate {
+                x: 1,
+                y: 2,
+                value: 9,
+            })
+            .unwrap();
+        state.apply(Move::Clear { x: 2, y: 0 }).unwrap();
+        assert!(state.marks(1, 0).contains(value));
+        assert!(!state.marks(1, 2).contains(9));
+
+        // Purists keep the marks they had, and placements no longer touch them
+        state.set_auto_notes(false);
+        let marks = state.marks(1, 0);
+        state.apply(Move::Place { x: 2, y: 0, value }).unwrap();
+        assert_eq!(state.marks(1, 0), marks);
+        assert!(state.apply(Move::Clear { x: 6, y: 0 }).is_err());
+    }
+
+    #[test]
+    fn auto_notes_match_candidates() {
+        let puzzle = parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap();
+        let solution = crate::solve_recursive(puzzle).unwrap();
+        let rules = Rules::classic();
+        let mut state = PlayState::new(puzzle).auto_notes(true);
+        let (x, y) = (1, 0);
+        let wrong = state.marks(x, y).into_iter().last().unwrap();
+        let moves = [
+            Move::Place { x, y, value: wrong },
+            Move::Place {
+                x: 2,
+                y: 0,
+                value: 9,
+            },
+            Move::Place {
+                x,
+                y,
+                value: solution.get(x, y),
+            },
+            Move::Clear { x: 2, y: 0 },
+            Move::Clear { x, y },
+        ];
+        for m in moves {
+            state.apply(m).unwrap();
+            for i in 0..NUM_CELLS {
+                let (x, y) = (i % 9, i / 9);
+                if state.grid().get(x, y) == EMPTY_CELL {
+                    let candidates = get_candidates_with_rules(state.grid(), x, y, &rules);
+                    assert_eq!(state.marks(x, y), candidates);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn refuses_moves_outside_the_grid() {
+        let puzzle = parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap();
+        let mut state = PlayState::new(puzzle);
+        assert!(state.apply(Move::Clear { x: 9, y: 0 }).is_err());
+        assert!(state
+            .apply(Move::Place {
+                x: 1,
+                y: 9,
+                value: 1
+            })
+            .is_err());
+        assert!(state
+            .apply(Move::Place {
+                x: 1,
+                y: 0,
+                value: 0
+            })
+            .is_err());
+        assert!(state
+            .apply(Move::Eliminate {
+                x: 1,
+                y: 0,
+                value: 10
+            })
+            .is_err());
+        assert_eq!(state.grid(), &puzzle);
+    }
+
+    #[test]
+    fn placements_complete_units() {
+        let puzzle = parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap();
+        let solution = crate::solve_recursive(puzzle).unwrap();
+        let mut state = PlayState::new(puzzle);
+        let mut completed = Vec::new();
+        for i in 0..NUM_CELLS {
+            let (x, y) = (i % 9, i / 9);
+            if puzzle.get(x, y) == EMPTY_CELL {
+                let value = solution.get(x, y);
+                let m = Move::Place { x, y, value };
+                state
+                    .apply_observed(m, &mut |event: &SolveEvent| {
+                        if let SolveEvent::UnitCompleted(unit) = event {
+                            completed.push((*unit, i));
+                        }
+                    })
+                    .unwrap();
+            }
+        }
+        // Filling row by row, the first row is done before anything else
+        assert_eq!(completed.len(), 27);
+        assert_eq!(completed[0].0, crate::Unit::Row(0));
+        assert_eq!(completed.last().unwrap().1, 80);
+    }
+}
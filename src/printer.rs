@@ -0,0 +1,330 @@
+//! Configurable text rendering of grids for the contexts which need more than the plain
+//! [Display](std::fmt::Display) layout, e.g. hints, diffs and teaching material.
+
+use std::fmt::Display;
+use std::fmt::Error;
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+use crate::{Grid, SymbolSet, EMPTY_CELL, NUM_CELLS};
+
+/// The lines drawn around boxes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BorderStyle {
+    /// `+`, `-` and `|`, the layout of [Display](std::fmt::Display) for [Grid].
+    #[default]
+    Ascii,
+    /// Box drawing characters.
+    Unicode,
+    /// No lines, boxes are set apart by spaces and blank lines.
+    None,
+}
+
+impl BorderStyle {
+    pub const ALL: [BorderStyle; 3] = [BorderStyle::Ascii, BorderStyle::Unicode, BorderStyle::None];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BorderStyle::Ascii => "ascii",
+            BorderStyle::Unicode => "unicode",
+            BorderStyle::None => "none",
+        }
+    }
+
+    // Corners and junctions of the top, middle and bottom lines followed by the horizontal
+    // and vertical line, or None for no lines
+    fn chars(&self) -> Option<[[char; 3]; 3]> {
+        match self {
+            BorderStyle::Ascii => Some([['+', '+', '+'], ['+', '+', '+'], ['+', '+', '+']]),
+            BorderStyle::Unicode => Some([['┌', '┬', '┐'], ['├', '┼', '┤'], ['└', '┴', '┘']]),
+            BorderStyle::None => None,
+        }
+    }
+
+    fn horizontal(&self) -> char {
+        match self {
+            BorderStyle::Unicode => '─',
+            _ => '-',
+        }
+    }
+
+    fn vertical(&self) -> char {
+        match self {
+            BorderStyle::Unicode => '│',
+            _ => '|',
+        }
+    }
+}
+
+impl Display for BorderStyle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for BorderStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        BorderStyle::ALL
+            .iter()
+            .find(|b| b.name().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| format!("Unknown border style '{}'", s))
+    }
+}
+
+/// How highlighted cells stand out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HighlightStyle {
+    /// Square brackets around the cell, which survive copying the text.
+    #[default]
+    Brackets,
+    /// Black on yellow using ANSI escape codes, for terminals.
+    Color,
+}
+
+impl HighlightStyle {
+    pub const ALL: [HighlightStyle; 2] = [HighlightStyle::Brackets, HighlightStyle::Color];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            HighlightStyle::Brackets => "brackets",
+            HighlightStyle::Color => "color",
+        }
+    }
+}
+
+impl Display for HighlightStyle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for HighlightStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        HighlightStyle::ALL
+            .iter()
+            .find(|h| h.name().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| format!("Unknown highlight style '{}'", s))
+    }
+}
+
+/// The cells (x, y) whose value differs between the grids, e.g. to highlight the progress
+/// made since an earlier state.
+pub fn changed_cells(before: &Grid, after: &Grid) -> Vec<(usize, usize)> {
+    (0..NUM_CELLS)
+        .map(|i| (i % 9, i / 9))
+        .filter(|&(x, y)| before.get(x, y) != after.get(x, y))
+        .collect()
+}
+
+/// Renders grids as text, e.g.
+/// `GridPrinter::new().border(BorderStyle::Unicode).coordinates(true).print(&grid)`.
+/// The default is the layout of [Display](std::fmt::Display) for [Grid].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GridPrinter {
+    symbols: SymbolSet,
+    // Overrides the empty cell symbol of `symbols`
+    empty: Option<char>,
+    spacing: usize,
+    border: BorderStyle,
+    highlighted: Vec<(usize, usize)>,
+    highlight_style: HighlightStyle,
+    coordinates: bool,
+}
+
+impl Default for GridPrinter {
+    fn default() -> Self {
+        GridPrinter::new()
+    }
+}
+
+impl GridPrinter {
+    pub fn new() -> GridPrinter {
+        GridPrinter {
+            symbols: SymbolSet::digits(),
+            empty: None,
+            spacing: 1,
+            border: BorderStyle::Ascii,
+            highlighted: Vec::new(),
+            highlight_style: HighlightStyle::Brackets,
+            coordinates: false,
+        }
+    }
+
+    pub fn symbols(mut self, symbols: SymbolSet) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
+    /// The character printed for empty cells instead of the one of the symbol set.
+    pub fn empty(mut self, empty: char) -> Self {
+        self.empty = Some(empty);
+        self
+    }
+
+    /// Spaces between neighbouring cells.
+    pub fn spacing(mut self, spacing: usize) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    pub fn border(mut self, border: BorderStyle) -> Self {
+        self.border = border;
+        self
+    }
+
+    /// Makes the cells at (x, y) stand out, e.g. those of [crate::hint::Hint::cells] or
+    /// [changed_cells].
+    pub fn highlight(mut self, cells: impl IntoIterator<Item = (usize, usize)>) -> Self {
+        self.highlighted.extend(cells);
+        self
+    }
+
+    pub fn highlight_style(mut self, style: HighlightStyle) -> Self {
+        self.highlight_style = style;
+        self
+    }
+
+    /// Numbers the rows and columns from 1 in the margins.
+    pub fn coordinates(mut self, coordinates: bool) -> Self {
+        self.coordinates = coordinates;
+        self
+    }
+
+    pub fn print(&self, grid: &Grid) -> String {
+        let symbol = |x: usize, y: usize| match (grid.get(x, y), self.empty) {
+            (EMPTY_CELL, Some(empty)) => empty,
+            (value, _) => self.symbols.symbol(value),
+        };
+        // Brackets need room in every cell to keep the columns aligned
+        let bracketed =
+            self.highlight_style == HighlightStyle::Brackets && !self.highlighted.is_empty();
+        let cell = |x: usize, y: usize| match (bracketed, self.highlighted.contains(&(x, y))) {
+            (false, false) => symbol(x, y).to_string(),
+            (false, true) => format!("\x1b[30;43m{}\x1b[0m", symbol(x, y)),
+            (true, false) => format!(" {} ", symbol(x, y)),
+            (true, true) => format!("[{}]", symbol(x, y)),
+        };
+        let width = if bracketed { 3 } else { 1 };
+        let margin = if self.coordinates { "  " } else { "" };
+
+        let mut lines = Vec::new();
+        if self.coordinates {
+            let header = self.row(|x| format!("{:^width$}", x + 1, width = width));
+            let vertical = self.border.vertical();
+            let header = header.replace(vertical, " ");
+            lines.push(format!("{}{}", margin, header));
+        }
+        for y in 0..9 {
+            if y % 3 == 0 {
+                if let Some(line) = self.line(y / 3, width) {
+                    lines.push(format!("{}{}", margin, line));
+                } else if y > 0 {
+                    lines.push(String::new());
+                }
+            }
+            let row = self.row(|x| cell(x, y));
+            match self.coordinates {
+                true => lines.push(format!("{} {}", y + 1, row)),
+                false => lines.push(row),
+            }
+        }
+        if let Some(line) = self.line(3, width) {
+            lines.push(format!("{}{}", margin, line));
+        }
+        lines
+            .iter()
+            .map(|line| format!("{}\n", line.trim_end()))
+            .collect()
+    }
+
+    // The cells of a row with the vertical lines between boxes
+    fn row(&self, cell: impl Fn(usize) -> String) -> String {
+        let gap = " ".repeat(self.spacing);
+        let mut out = String::new();
+        for x in 0..9 {
+            if x % 3 == 0 {
+                match self.border {
+                    BorderStyle::None if x > 0 => out.push(' '),
+                    BorderStyle::None => (),
+                    _ => out.push(self.border.vertical()),
+                }
+            }
+            out.push_str(&cell(x));
+            if x < 8 {
+                out.push_str(&gap);
+            }
+        }
+        if self.border != BorderStyle::None {
+            out.push(self.border.vertical());
+        }
+        out
+    }
+
+    // The horizontal line above band `n`, 3 being the line at the bottom
+    fn line(&self, n: usize, width: usize) -> Option<String> {
+        let chars = self.border.chars()?;
+        let [left, middle, right] = chars[match n {
+            0 => 0,
+            3 => 2,
+            _ => 1,
+        }];
+        let horizontal = self.border.horizontal().to_string();
+        // The last cell of a row has no gap after it
+        let box_width = |b: usize| 3 * width + self.spacing * if b < 2 { 3 } else { 2 };
+        let mut out = String::new();
+        out.push(left);
+        for b in 0..3 {
+            out.push_str(&horizontal.repeat(box_width(b)));
+            out.push(if b < 2 { middle } else { right });
+        }
+        Some(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_grid;
+
+    #[test]
+    fn custom_layouts() {
+        let grid = parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap();
+        assert_eq!(GridPrinter::new().print(&grid), grid.to_string());
+
+        let unicode = GridPrinter::new()
+            .border(BorderStyle::Unicode)
+            .coordinates(true)
+            .empty('·')
+            .print(&grid);
+        let lines: Vec<&str> = unicode.lines().collect();
+        assert_eq!(lines[0], "   1 2 3  4 5 6  7 8 9");
+        assert_eq!(lines[1], "  ┌──────┬──────┬─────┐");
+        assert_eq!(lines[2], "1 │4 · · │· · · │8 · 5│");
+        assert_eq!(lines.len(), 14);
+
+        let plain = GridPrinter::new()
+            .border(BorderStyle::None)
+            .spacing(0)
+            .highlight([(0, 0)])
+            .print(&grid);
+        assert_eq!(plain.lines().next(), Some("[4] .  .   .  .  .   8  .  5"));
+        assert_eq!(plain.lines().nth(3), Some(""));
+
+        let mut next = grid;
+        next.set(9, 1, 0);
+        let colored = GridPrinter::new()
+            .highlight(changed_cells(&grid, &next))
+            .highlight_style(HighlightStyle::Color)
+            .print(&next);
+        assert_eq!(
+            colored.lines().nth(1),
+            Some("|4 \x1b[30;43m9\x1b[0m . |. . . |8 . 5|")
+        );
+    }
+}
@@ -0,0 +1,113 @@
+//! Logical deductions to a fixpoint without any guessing, the half of the solver that
+//! hint interfaces and difficulty filters need.
+
+use crate::conflict::{check, Contradiction};
+use crate::logic::{Candidate, Deduction, LogicSolver, TechniqueSet};
+use crate::{Grid, Rules, ValueSet, NUM_CELLS};
+
+/// Which deductions [propagate_with] makes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PropagationDepth {
+    /// Naked and hidden singles only.
+    #[default]
+    Singles,
+    /// Every technique of [TechniqueSet::all].
+    AllTechniques,
+}
+
+/// The state after propagating, see [propagate].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropagationResult {
+    /// The givens along with every placed value.
+    pub grid: Grid,
+    candidates: [ValueSet; NUM_CELLS],
+    /// Candidates removed by techniques other than singles, in the order they were found.
+    pub eliminations: Vec<Candidate>,
+    /// Set if propagation ran into a cell or unit without options, so the puzzle has no solution.
+    pub contradiction: Option<Contradiction>,
+    // Whether the grid is full and keeps the rules propagation ran with
+    solved: bool,
+}
+
+impl PropagationResult {
+    /// Candidates left for the cell at (x, y), empty if the cell is filled.
+    pub fn candidates(&self, x: usize, y: usize) -> ValueSet {
+        self.candidates[y * 9 + x]
+    }
+
+    /// Whether every cell is filled without breaking the rules given to [propagate_with].
+    pub fn is_solved(&self) -> bool {
+        self.contradiction.is_none() && self.solved
+    }
+}
+
+/// Places naked and hidden singles until none are left.
+pub fn propagate(grid: &Grid) -> PropagationResult {
+    propagate_with(grid, &Rules::classic(), PropagationDepth::Singles)
+}
+
+/// Applies the deductions of `depth` until none are left or a contradiction shows up.
+pub fn propagate_with(grid: &Grid, rules: &Rules, depth: PropagationDepth) -> PropagationResult {
+    let techniques = match depth {
+        PropagationDepth::Singles => TechniqueSet::singles(),
+        PropagationDepth::AllTechniques => TechniqueSet::all(),
+    };
+    let mut solver = LogicSolver::new(grid, rules).with_techniques(techniques);
+    let mut eliminations = Vec::new();
+    let contradiction = loop {
+        if let Some((contradiction, _)) = check(&solver, rules) {
+            break Some(contradiction);
+        }
+        let Some(step) = solver.next_step() else {
+            break None;
+        };
+        solver.apply(&step);
+        if let Deduction::Eliminate(removed) = step.deduction {
+            eliminations.extend(removed);
+        }
+    };
+    PropagationResult {
+        grid: *solver.grid(),
+        candidates: std::array::from_fn(|i| solver.candidates(i % 9, i / 9)),
+        eliminations,
+        contradiction,
+        solved: solver.is_solved(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_grid, solve_recursive, Variant};
+
+    #[test]
+    fn propagates_to_fixpoint() {
+        let easy = parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap();
+        let singles = propagate(&easy);
+        assert_eq!(singles.contradiction, None);
+        assert!(singles.eliminations.is_empty());
+        // Every placement agrees with the solution
+        let solution = solve_recursive(easy).unwrap();
+        for i in 0..NUM_CELLS {
+            let value = singles.grid.get(i % 9, i / 9);
+            assert!(value == 0 || value == solution.get(i % 9, i / 9));
+        }
+
+        let all = propagate_with(&easy, &Rules::classic(), PropagationDepth::AllTechniques);
+        assert!(all.is_solved());
+        assert_eq!(all.grid, solution);
+        assert!(!all.eliminations.is_empty());
+        assert!(!singles.is_solved());
+        assert!(singles.candidates(0, 1).count() > 1);
+    }
+
+    #[test]
+    fn solved_under_its_rules() {
+        let easy = parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap();
+        let solution = solve_recursive(easy).unwrap();
+        let position = Rules::new(&[Variant::Position]);
+        assert!(solution.validate_with_rules(&position).is_err());
+        assert!(propagate(&solution).is_solved());
+        assert!(!propagate_with(&solution, &position, PropagationDepth::Singles).is_solved());
+    }
+}
@@ -0,0 +1,92 @@
+//! Suggestions for repairing puzzles with more than one solution.
+
+use crate::{CellValue, Grid, Solver, EMPTY_CELL, NUM_CELLS};
+
+/// A clue to add to a puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClueSuggestion {
+    pub x: usize,
+    pub y: usize,
+    pub value: CellValue,
+    /// Number of solutions left after adding the clue.
+    pub remaining: usize,
+    /// False if the puzzle had more solutions than were enumerated, in which case
+    /// `remaining` only counts among the enumerated ones.
+    pub exact: bool,
+}
+
+impl ClueSuggestion {
+    /// Whether adding the clue makes the solution unique.
+    pub fn makes_unique(&self) -> bool {
+        self.exact && self.remaining == 1
+    }
+}
+
+pub fn recommend_clue(puzzle: &Grid, limit: usize) -> Option<ClueSuggestion> {
+    recommend_clue_with(&Solver::new(), puzzle, limit)
+}
+
+/// Suggests the single clue which leaves the fewest solutions, looking at up to `limit` solutions.
+/// Returns [None] if the puzzle has no solutions or already has a unique one.
+pub fn recommend_clue_with(solver: &Solver, puzzle: &Grid, limit: usize) -> Option<ClueSuggestion> {
+    // One extra solution tells whether the enumeration was complete
+    let mut solutions = solver.solve_all(*puzzle, limit.saturating_add(1));
+    let exact = solutions.len() <= limit;
+    solutions.truncate(limit);
+    if solutions.len() < 2 {
+        return None;
+    }
+
+    // counts[i][v - 1] is the number of solutions with value v at cell i
+    let mut counts = [[0usize; 9]; NUM_CELLS];
+    for solution in &solutions {
+        for i in 0..NUM_CELLS {
+            counts[i][solution.get(i % 9, i / 9) as usize - 1] += 1;
+        }
+    }
+
+    let mut best: Option<ClueSuggestion> = None;
+    for i in 0..NUM_CELLS {
+        let (x, y) = (i % 9, i / 9);
+        if puzzle.get(x, y) != EMPTY_CELL {
+            continue;
+        }
+        for v in 0..9 {
+            let remaining = counts[i][v];
+            if remaining > 0 && best.is_none_or(|b| remaining < b.remaining) {
+                best = Some(ClueSuggestion {
+                    x,
+                    y,
+                    value: v as CellValue + 1,
+                    remaining,
+                    exact,
+                });
+            }
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_grid, solve_recursive};
+
+    #[test]
+    fn restores_uniqueness() {
+        let solution =
+            solve_recursive(parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap())
+                .unwrap();
+        let mut puzzle = solution;
+        for i in 0..27 {
+            puzzle.set(EMPTY_CELL, i % 9, i / 9);
+        }
+        let suggestion = recommend_clue(&puzzle, 1000).unwrap();
+        assert!(suggestion.exact);
+        puzzle.set(suggestion.value, suggestion.x, suggestion.y);
+        let left = crate::count_solutions(&puzzle, 1000);
+        assert_eq!(left, suggestion.remaining);
+
+        assert_eq!(recommend_clue(&solution, 1000), None);
+    }
+}
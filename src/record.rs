@@ -0,0 +1,339 @@
+//! Puzzles along with their provenance, stored as JSON or as SDM with comment headers.
+//!
+//! SDM is one 81-character puzzle per line. Metadata goes into `# key: value` comments
+//! above the puzzle, so tools that skip comments still read the file as plain SDM:
+//!
+//! ```text
+//! # title: Morning puzzle
+//! # rating: medium
+//! # tags: daily, symmetric
+//! 4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......
+//! ```
+
+use std::fmt::Write;
+use std::io::BufRead;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::json::{self, quote, Json};
+use crate::logic::{grade_with_rules, Difficulty};
+use crate::{parse_grid_checked, Grid, Rules};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PuzzleRecord {
+    pub grid: Grid,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    /// Where the puzzle came from, e.g. a book, a website or the generator.
+    pub source: Option<String>,
+    pub rating: Option<Difficulty>,
+    pub tags: Vec<String>,
+    /// Date the puzzle was created or published, preferably as YYYY-MM-DD.
+    pub date: Option<String>,
+}
+
+impl PuzzleRecord {
+    /// A record without any metadata.
+    pub fn new(grid: Grid) -> PuzzleRecord {
+        PuzzleRecord {
+            grid,
+            title: None,
+            author: None,
+            source: None,
+            rating: None,
+            tags: Vec::new(),
+            date: None,
+        }
+    }
+
+    /// The short ID of the puzzle, see [Grid::fingerprint].
+    pub fn fingerprint(&self) -> String {
+        self.grid.fingerprint()
+    }
+
+    /// Sets the rating to the grade of the puzzle, see [grade_with_rules].
+    pub fn rate(&mut self, rules: &Rules) {
+        self.rating = Some(grade_with_rules(&self.grid, rules));
+    }
+
+    // Metadata as key and value in the order they are written
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = Vec::new();
+        let mut add = |key, value: &Option<String>| {
+            if let Some(value) = value {
+                fields.push((key, value.clone()));
+            }
+        };
+        add("title", &self.title);
+        add("author", &self.author);
+        add("source", &self.source);
+        add("rating", &self.rating.map(|r| r.to_string()));
+        add("date", &self.date);
+        fields
+    }
+
+    fn set_field(&mut self, key: &str, value: String) -> Result<(), String> {
+        match key {
+            "title" => self.title = Some(value),
+            "author" => self.author = Some(value),
+            "source" => self.source = Some(value),
+            "rating" => self.rating = Some(value.parse()?),
+            "date" => self.date = Some(value),
+            "tags" => {
+                self.tags = value
+                    .split(',')
+                    .map(|t| t.trim().to_string())
+                    .filter(|t| !t.is_empty())
+                    .collect()
+            }
+            // Unknown keys are plain comments
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RecordFormat {
+    #[default]
+    Sdm,
+    Json,
+}
+
+impl RecordFormat {
+    /// JSON for paths ending in .json, SDM otherwise.
+    pub fn for_path(path: &str) -> RecordFormat {
+        if path.to_ascii_lowercase().ends_with(".json") {
+            RecordFormat::Json
+        } else {
+            RecordFormat::Sdm
+        }
+    }
+
+    /// JSON if the text starts with '[' or '{', SDM otherwise.
+    pub fn detect(text: &str) -> RecordFormat {
+        match text.trim_start().chars().next() {
+            Some('[') | Some('{') => RecordFormat::Json,
+            _ => RecordFormat::Sdm,
+        }
+    }
+}
+
+impl FromStr for RecordFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "sdm" => Ok(RecordFormat::Sdm),
+            "json" => Ok(RecordFormat::Json),
+            _ => Err(format!("Unknown record format '{}'", s)),
+        }
+    }
+}
+
+pub fn write_records(records: &[PuzzleRecord], format: RecordFormat) -> String {
+    match format {
+        RecordFormat::Sdm => to_sdm(records),
+        RecordFormat::Json => to_json(records),
+    }
+}
+
+/// Parses records in the format found by [RecordFormat::detect].
+pub fn parse_records(text: &str) -> Result<Vec<PuzzleRecord>, String> {
+    match RecordFormat::detect(text) {
+        RecordFormat::Sdm => parse_sdm(text),
+        RecordFormat::Json => parse_json(text),
+    }
+}
+
+/// Every record as its comment header followed by the puzzle line, separated by blank lines.
+pub fn to_sdm(records: &[PuzzleRecord]) -> String {
+    let mut out = String::new();
+    for (n, record) in records.iter().enumerate() {
+        out.push_str(&sdm_record(record, n == 0));
+    }
+    out
+}
+
+/// One record of [to_sdm], preceded by a blank line unless it comes `first` or has no metadata.
+pub(crate) fn sdm_record(record: &PuzzleRecord, first: bool) -> String {
+    let mut out = String::new();
+    if !first && (!record.fields().is_empty() || !record.tags.is_empty()) {
+        out.push('\n');
+    }
+    for (key, value) in record.fields() {
+        let _ = writeln!(out, "# {}: {}", key, value.replace('\n', " "));
+    }
+    if !record.tags.is_empty() {
+        let _ = writeln!(out, "# tags: {}", record.tags.join(", "));
+    }
+    out.push_str(&record.grid.to_line());
+    out.push('\n');
+    out
+}
+
+/// Parses SDM, collecting the `# key: value` comments above each puzzle into its record.
+pub fn parse_sdm(text: &str) -> Result<Vec<PuzzleRecord>, String> {
+    SdmReader::new(text.as_bytes()).collect()
+}
+
+/// Reads SDM records one at a time, for files too large to hold in memory.
+pub struct SdmReader<R> {
+    lines: std::io::Lines<R>,
+    line: usize,
+}
+
+impl<R: BufRead> SdmReader<R> {
+    pub fn new(reader: R) -> SdmReader<R> {
+        SdmReader {
+            lines: reader.lines(),
+            line: 0,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for SdmReader<R> {
+    type Item = Result<PuzzleRecord, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut header: Vec<(String, String)> = Vec::new();
+        for line in self.lines.by_ref() {
+            self.line += 1;
+            let n = self.line;
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => return Some(Err(format!("Line {}: {}", n, e))),
+            };
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(comment) = line.strip_prefix('#') {
+                if let Some((key, value)) = comment.split_once(':') {
+                    header.push((key.trim().to_ascii_lowercase(), value.trim().to_string()));
+                }
+                continue;
+            }
+            let record = parse_grid_checked(line)
+                .map_err(|e| e.to_string())
+                .and_then(|grid| {
+                    let mut record = PuzzleRecord::new(grid);
+                    for (key, value) in header.drain(..) {
+                        record.set_field(&key, value)?;
+                    }
+                    Ok(record)
+                });
+            return Some(record.map_err(|e| format!("Line {}: {}", n, e)));
+        }
+        None
+    }
+}
+
+/// A JSON array with one object per record. Missing metadata is left out.
+pub fn to_json(records: &[PuzzleRecord]) -> String {
+    let mut out = String::from("[");
+    for (n, record) in records.iter().enumerate() {
+        out.push_str(if n == 0 { "\n" } else { ",\n" });
+        out.push_str(&json_record(record));
+    }
+    out.push_str(if records.is_empty() { "]\n" } else { "\n]\n" });
+    out
+}
+
+/// One object of the array of [to_json], indented but without a trailing newline.
+pub(crate) fn json_record(record: &PuzzleRecord) -> String {
+    let mut out = format!("  {{\n    \"grid\": {}", quote(&record.grid.to_line()));
+    for (key, value) in record.fields() {
+        let _ = write!(out, ",\n    \"{}\": {}", key, quote(&value));
+    }
+    if !record.tags.is_empty() {
+        let tags: Vec<String> = record.tags.iter().map(|t| quote(t)).collect();
+        let _ = write!(out, ",\n    \"tags\": [{}]", tags.join(", "));
+    }
+    out.push_str("\n  }");
+    out
+}
+
+/// Parses a JSON array of records, or a single record object.
+pub fn parse_json(text: &str) -> Result<Vec<PuzzleRecord>, String> {
+    match json::parse(text)? {
+        Json::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(n, item)| record_from_json(item).map_err(|e| format!("Record {}: {}", n + 1, e)))
+            .collect(),
+        object => Ok(vec![record_from_json(&object)?]),
+    }
+}
+
+fn record_from_json(json: &Json) -> Result<PuzzleRecord, String> {
+    let fields = match json {
+        Json::Object(fields) => fields,
+        _ => return Err("expected an object".to_string()),
+    };
+    let grid = json
+        .get("grid")
+        .and_then(Json::as_str)
+        .ok_or("missing \"grid\"")?;
+    let mut record = PuzzleRecord::new(parse_grid_checked(grid).map_err(|e| e.to_string())?);
+    for (key, value) in fields {
+        match (key.as_str(), value) {
+            ("grid", _) | (_, Json::Null) => {}
+            ("tags", Json::Array(tags)) => {
+                record.tags = tags
+                    .iter()
+                    .map(|t| t.as_str().map(str::to_string).ok_or("tags must be strings"))
+                    .collect::<Result<_, _>>()?
+            }
+            (key, Json::String(value)) => record.set_field(key, value.clone())?,
+            (key, _) => return Err(format!("unexpected value for \"{}\"", key)),
+        }
+    }
+    Ok(record)
+}
+
+/// Today's date in UTC as YYYY-MM-DD.
+pub fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() / 86400) as i64;
+    // Civil date from days since 1970-01-01, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_grid;
+
+    #[test]
+    fn round_trip() {
+        let grid = parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap();
+        let mut record = PuzzleRecord::new(grid);
+        record.title = Some("Easy \"one\"".to_string());
+        record.tags = vec!["sample".to_string(), "17 clues".to_string()];
+        record.rate(&Rules::classic());
+        let records = vec![record, PuzzleRecord::new(grid)];
+
+        for format in [RecordFormat::Sdm, RecordFormat::Json] {
+            let text = write_records(&records, format);
+            assert_eq!(parse_records(&text), Ok(records.clone()));
+        }
+        let sdm = to_sdm(&records);
+        assert!(
+            sdm.starts_with("# title: Easy \"one\"\n# rating: medium\n# tags: sample, 17 clues\n")
+        );
+        // Plain SDM without headers
+        assert_eq!(parse_sdm(&grid.to_line()).unwrap()[0], records[1]);
+        assert_eq!(today().len(), 10);
+    }
+}
@@ -0,0 +1,204 @@
+//! Suggestions for fixing grids with conflicting givens, e.g. from OCR or typos.
+
+use std::fmt::Display;
+use std::fmt::Error;
+use std::fmt::Formatter;
+
+use crate::{CellValue, Grid, Rules, Solver, EMPTY_CELL, NUM_CELLS};
+
+// Conflicting cells beyond this are covered greedily instead of trying every subset
+const MAX_EXACT_CELLS: usize = 16;
+// Caps the number of equally small repairs returned
+const MAX_REPAIRS: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fix {
+    /// Clear the given at (x, y).
+    Remove { cell: (usize, usize) },
+    /// Replace the given at (x, y), the value being the one of the unique solution once the
+    /// other fixes are made.
+    Change {
+        cell: (usize, usize),
+        value: CellValue,
+    },
+}
+
+impl Display for Fix {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            Fix::Remove { cell: (x, y) } => write!(f, "remove r{}c{}", y + 1, x + 1),
+            Fix::Change {
+                cell: (x, y),
+                value,
+            } => write!(f, "change r{}c{} to {}", y + 1, x + 1, value),
+        }
+    }
+}
+
+/// A smallest set of givens to clear or change so that no unit repeats a value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repair {
+    pub fixes: Vec<Fix>,
+    /// The grid with the fixes made.
+    pub grid: Grid,
+    /// Whether the repaired grid has exactly one solution.
+    pub unique: bool,
+}
+
+impl Display for Repair {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        let fixes: Vec<String> = self.fixes.iter().map(|f| f.to_string()).collect();
+        write!(f, "{}", fixes.join(", "))
+    }
+}
+
+/// The smallest repairs of `grid`, those leaving a unique solution first. Empty if no
+/// unit repeats a value.
+pub fn suggest_repairs(grid: &Grid, rules: &Rules) -> Vec<Repair> {
+    let conflicts = conflicts(grid, rules);
+    if conflicts.is_empty() {
+        return Vec::new();
+    }
+    let solver = Solver::new().with_rules(rules.clone());
+    let mut repairs: Vec<Repair> = smallest_covers(&conflicts)
+        .into_iter()
+        .map(|cover| {
+            let mut cleared = *grid;
+            for &i in &cover {
+                cleared.set(EMPTY_CELL, i % 9, i / 9);
+            }
+            let solutions = solver.solve_all(cleared, 2);
+            match solutions.as_slice() {
+                // Put back the values the solution forces
+                [solution] => Repair {
+                    fixes: cover
+                        .iter()
+                        .map(|&i| Fix::Change {
+                            cell: (i % 9, i / 9),
+                            value: solution.get(i % 9, i / 9),
+                        })
+                        .collect(),
+                    grid: {
+                        let mut repaired = cleared;
+                        for &i in &cover {
+                            repaired.set(solution.get(i % 9, i / 9), i % 9, i / 9);
+                        }
+                        repaired
+                    },
+                    unique: true,
+                },
+                _ => Repair {
+                    fixes: cover
+                        .iter()
+                        .map(|&i| Fix::Remove {
+                            cell: (i % 9, i / 9),
+                        })
+                        .collect(),
+                    grid: cleared,
+                    unique: false,
+                },
+            }
+        })
+        .collect();
+    repairs.sort_by_key(|r| !r.unique);
+    repairs
+}
+
+// Pairs of cells holding the same value in a unit
+fn conflicts(grid: &Grid, rules: &Rules) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    for unit in rules.units() {
+        let cells = unit.cells(rules);
+        for (n, &a) in cells.iter().enumerate() {
+            for &b in &cells[n + 1..] {
+                let value = grid.get(a % 9, a / 9);
+                let pair = (a.min(b), a.max(b));
+                if value != EMPTY_CELL && value == grid.get(b % 9, b / 9) && !pairs.contains(&pair)
+                {
+                    pairs.push(pair);
+                }
+            }
+        }
+    }
+    pairs
+}
+
+// The smallest sets of cells touching every conflict
+fn smallest_covers(conflicts: &[(usize, usize)]) -> Vec<Vec<usize>> {
+    let mut cells: Vec<usize> = conflicts.iter().flat_map(|&(a, b)| [a, b]).collect();
+    cells.sort_unstable();
+    cells.dedup();
+    if cells.len() > MAX_EXACT_CELLS {
+        return vec![greedy_cover(conflicts)];
+    }
+    let bit = |c: usize| 1u32 << cells.iter().position(|&x| x == c).unwrap_or_default();
+    let masks: Vec<u32> = conflicts.iter().map(|&(a, b)| bit(a) | bit(b)).collect();
+    let covers = |mask: u32| masks.iter().all(|m| m & mask != 0);
+    for size in 1..=cells.len() as u32 {
+        let found: Vec<Vec<usize>> = (0u32..1 << cells.len())
+            .filter(|mask| mask.count_ones() == size && covers(*mask))
+            .take(MAX_REPAIRS)
+            .map(|mask| {
+                (0..cells.len())
+                    .filter(|&i| mask & (1 << i) != 0)
+                    .map(|i| cells[i])
+                    .collect()
+            })
+            .collect();
+        if !found.is_empty() {
+            return found;
+        }
+    }
+    Vec::new()
+}
+
+// Repeatedly takes the cell in the most remaining conflicts
+fn greedy_cover(conflicts: &[(usize, usize)]) -> Vec<usize> {
+    let mut left = conflicts.to_vec();
+    let mut cover = Vec::new();
+    while !left.is_empty() {
+        let cell = (0..NUM_CELLS)
+            .max_by_key(|&c| left.iter().filter(|&&(a, b)| a == c || b == c).count())
+            .unwrap_or_default();
+        cover.push(cell);
+        left.retain(|&(a, b)| a != cell && b != cell);
+    }
+    cover
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_grid;
+
+    #[test]
+    fn repairs_typo() {
+        let rules = Rules::classic();
+        let puzzle = parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap();
+        assert!(suggest_repairs(&puzzle, &rules).is_empty());
+
+        // A misread clue left out of a sparse puzzle only leaves the removals
+        let mut typo = puzzle;
+        typo.set(8, 0, 0);
+        let repairs = suggest_repairs(&typo, &rules);
+        assert_eq!(repairs.len(), 2);
+        assert!(repairs.iter().all(|r| !r.unique));
+        assert_eq!(repairs[0].to_string(), "remove r1c1");
+        assert_eq!(repairs[1].to_string(), "remove r1c7");
+
+        // In a full grid the unique solution tells the value back
+        let solution = Solver::new().solve(puzzle).unwrap();
+        let mut typo = solution;
+        typo.set(typo.get(6, 0), 0, 0);
+        let repairs = suggest_repairs(&typo, &rules);
+        assert_eq!(repairs.len(), 1);
+        assert_eq!(
+            repairs[0].fixes,
+            [Fix::Change {
+                cell: (0, 0),
+                value: solution.get(0, 0)
+            }]
+        );
+        assert_eq!(repairs[0].grid, solution);
+    }
+}
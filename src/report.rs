@@ -0,0 +1,228 @@
+//! Which constraints do the work in a puzzle, counted as the candidates each one removes
+//! while the givens are placed and the logical solver runs.
+
+use std::fmt::Display;
+use std::fmt::Error;
+use std::fmt::Formatter;
+
+use crate::logic::{Deduction, LogicSolver};
+use crate::{CellValue, Grid, Rules, Solver, Unit, Variant, EMPTY_CELL, NUM_CELLS};
+
+/// A kind of unit, e.g. all rows or all regions of a variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Constraint {
+    Row,
+    Column,
+    Box,
+    Variant(Variant),
+}
+
+impl Constraint {
+    pub fn of(unit: Unit, rules: &Rules) -> Constraint {
+        match unit {
+            Unit::Row(_) => Constraint::Row,
+            Unit::Col(_) => Constraint::Column,
+            Unit::Box(_) => Constraint::Box,
+            Unit::Extra(i) => match rules.extra_unit_variant(i) {
+                Some((variant, _)) => Constraint::Variant(variant),
+                None => unreachable!("Every extra unit comes from a variant"),
+            },
+        }
+    }
+
+    /// Rows, columns and boxes followed by the variants of `rules`.
+    pub fn all(rules: &Rules) -> Vec<Constraint> {
+        [Constraint::Row, Constraint::Column, Constraint::Box]
+            .into_iter()
+            .chain(rules.variants().iter().map(|&v| Constraint::Variant(v)))
+            .collect()
+    }
+}
+
+impl Display for Constraint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            Constraint::Row => write!(f, "rows"),
+            Constraint::Column => write!(f, "columns"),
+            Constraint::Box => write!(f, "boxes"),
+            Constraint::Variant(variant) => write!(f, "{} regions", variant),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConstraintStats {
+    pub constraint: Constraint,
+    /// Candidates removed through units of this kind. A candidate ruled out by, say, both a
+    /// row and a box counts for each.
+    pub eliminations: usize,
+    /// Candidates no other kind of unit would have removed.
+    pub exclusive: usize,
+}
+
+/// The solution along with the work of every constraint, see [solve_with_report].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolveReport {
+    pub solution: Option<Grid>,
+    /// Whether the logical techniques solved the puzzle on their own. If not, the counts only
+    /// cover the deductions made before they got stuck.
+    pub logical: bool,
+    /// Every constraint of the rules, rows first.
+    pub constraints: Vec<ConstraintStats>,
+}
+
+impl SolveReport {
+    pub fn stats(&self, constraint: Constraint) -> Option<&ConstraintStats> {
+        self.constraints.iter().find(|s| s.constraint == constraint)
+    }
+}
+
+impl Display for SolveReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        writeln!(f, "Eliminations by constraint:")?;
+        for stats in &self.constraints {
+            writeln!(
+                f,
+                "  {}: {} ({} by {} alone)",
+                stats.constraint, stats.eliminations, stats.exclusive, stats.constraint
+            )?;
+        }
+        if !self.logical {
+            writeln!(
+                f,
+                "  (logical techniques got stuck, the rest needed guessing)"
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Solves `grid` with `solver` and counts the candidates each constraint of its rules removes,
+/// first while placing the givens on an empty grid and then in the steps of the logical solver.
+pub fn solve_with_report(solver: &Solver, grid: &Grid) -> SolveReport {
+    let rules = solver.rules();
+    let mut constraints: Vec<ConstraintStats> = Constraint::all(rules)
+        .into_iter()
+        .map(|constraint| ConstraintStats {
+            constraint,
+            eliminations: 0,
+            exclusive: 0,
+        })
+        .collect();
+
+    let mut logic = LogicSolver::new(&Grid::new(&[EMPTY_CELL; NUM_CELLS]), rules);
+    for i in 0..NUM_CELLS {
+        let value = grid.get(i % 9, i / 9);
+        if value != EMPTY_CELL {
+            place(&mut logic, &mut constraints, (i % 9, i / 9), value);
+        }
+    }
+    while let Some(step) = logic.next_step() {
+        match &step.deduction {
+            Deduction::Place(c) => place(&mut logic, &mut constraints, (c.x, c.y), c.value),
+            Deduction::Eliminate(eliminations) => {
+                let mut kinds: Vec<Constraint> = Vec::new();
+                for &unit in &step.units {
+                    let kind = Constraint::of(unit, rules);
+                    if !kinds.contains(&kind) {
+                        kinds.push(kind);
+                    }
+                }
+                for _ in eliminations {
+                    credit(&mut constraints, &kinds);
+                }
+                logic.apply(&step);
+            }
+        }
+    }
+
+    let logical = logic.is_solved();
+    SolveReport {
+        solution: match logical {
+            true => Some(*logic.grid()),
+            false => solver.solve(*grid),
+        },
+        logical,
+        constraints,
+    }
+}
+
+// Places the value, crediting the removal of each candidate from a peer
+fn place(
+    logic: &mut LogicSolver,
+    constraints: &mut [ConstraintStats],
+    (x, y): (usize, usize),
+    value: CellValue,
+) {
+    let rules = logic.rules();
+    let mut removals = Vec::new();
+    for (px, py) in rules.peers(x, y) {
+        if logic.candidates(px, py).contains(value) {
+            removals.push(linking(rules, (x, y), (px, py)));
+        }
+    }
+    for kinds in removals {
+        credit(constraints, &kinds);
+    }
+    logic.place(x, y, value);
+}
+
+// Counts one candidate removed through units of `kinds`
+fn credit(constraints: &mut [ConstraintStats], kinds: &[Constraint]) {
+    for stats in constraints
+        .iter_mut()
+        .filter(|s| kinds.contains(&s.constraint))
+    {
+        stats.eliminations += 1;
+        if kinds.len() == 1 {
+            stats.exclusive += 1;
+        }
+    }
+}
+
+// The kinds of units holding both cells
+fn linking(rules: &Rules, a: (usize, usize), b: (usize, usize)) -> Vec<Constraint> {
+    let mut kinds = Vec::new();
+    for unit in rules.units_at(a.1 * 9 + a.0) {
+        let kind = Constraint::of(unit, rules);
+        if unit.cells(rules).contains(&(b.1 * 9 + b.0)) && !kinds.contains(&kind) {
+            kinds.push(kind);
+        }
+    }
+    kinds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_grid;
+
+    #[test]
+    fn counts_variant_work() {
+        let easy = parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap();
+        let report = solve_with_report(&Solver::new(), &easy);
+        assert!(report.logical);
+        assert_eq!(report.solution, Solver::new().solve(easy));
+        assert_eq!(report.constraints.len(), 3);
+        assert!(report
+            .constraints
+            .iter()
+            .all(|s| s.exclusive > 0 && s.exclusive <= s.eliminations));
+
+        // Shifted rows also give every position region all values
+        let mut values = [EMPTY_CELL; NUM_CELLS];
+        for i in (0..NUM_CELLS).step_by(2) {
+            let (x, y) = (i % 9, i / 9);
+            values[i] = ((3 * (y % 3) + y / 3 + x) % 9) as CellValue + 1;
+        }
+        let grid = Grid::new(&values);
+        let solver = Solver::new().with_rules(Rules::new(&[Variant::Position]));
+        let report = solve_with_report(&solver, &grid);
+        let position = report
+            .stats(Constraint::Variant(Variant::Position))
+            .unwrap();
+        assert!(position.exclusive > 0);
+        assert!(report.solution.is_some());
+        assert!(report.to_string().contains("position regions: "));
+    }
+}
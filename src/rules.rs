@@ -0,0 +1,344 @@
+use std::fmt::Display;
+use std::fmt::Error;
+use std::fmt::Formatter;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
+
+/// Cell indices of a unit. A unit is a group of 9 cells which must contain each of the values 1..9 exactly once.
+/// Indices are row-major, i.e. `y * 9 + x`.
+pub type UnitCells = [usize; 9];
+
+/// A unit of the grid. Indices are 0-based.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Unit {
+    Row(usize),
+    Col(usize),
+    /// Boxes are numbered left to right, top to bottom.
+    Box(usize),
+    /// Index into [Rules::extra_units].
+    Extra(usize),
+}
+
+impl Unit {
+    /// The box containing (x, y).
+    pub fn box_of(x: usize, y: usize) -> Unit {
+        Unit::Box((y / 3) * 3 + x / 3)
+    }
+
+    /// The 27 rows, columns and boxes, interleaved as box i, row i, column i so
+    /// nearby units come first.
+    pub fn classic() -> impl Iterator<Item = Unit> {
+        (0..9).flat_map(|i| [Unit::Box(i), Unit::Row(i), Unit::Col(i)])
+    }
+
+    /// Cell indices of the unit. `rules` is only needed to look up extra units.
+    pub fn cells(&self, rules: &Rules) -> UnitCells {
+        match *self {
+            Unit::Extra(e) => rules.extra_units[e],
+            _ => self.classic_cells(),
+        }
+    }
+
+    fn classic_cells(&self) -> UnitCells {
+        match *self {
+            Unit::Row(y) => std::array::from_fn(|i| y * 9 + i),
+            Unit::Col(x) => std::array::from_fn(|i| i * 9 + x),
+            Unit::Box(b) => {
+                let (sx, sy) = ((b % 3) * 3, (b / 3) * 3);
+                std::array::from_fn(|i| (sy + i / 3) * 9 + sx + i % 3)
+            }
+            Unit::Extra(_) => panic!("Extra units depend on the rules"),
+        }
+    }
+
+    /// Human readable name, e.g. "row 3" or "position region 2".
+    pub fn describe(&self, rules: &Rules) -> String {
+        match self {
+            Unit::Row(i) => format!("row {}", i + 1),
+            Unit::Col(i) => format!("column {}", i + 1),
+            Unit::Box(i) => format!("box {}", i + 1),
+            Unit::Extra(i) => rules.extra_unit_name(*i),
+        }
+    }
+}
+
+/// Names extra units by index only, see [Unit::describe] for the variant specific name.
+impl Display for Unit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            Unit::Extra(i) => write!(f, "region {}", i + 1),
+            _ => write!(f, "{}", self.describe(&Rules::classic())),
+        }
+    }
+}
+
+/// A named Sudoku variant. Each variant adds a number of extra units on top of
+/// the classic rows, columns and boxes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Variant {
+    /// Position Sudoku: the i'th cell of every box forms a unit.
+    Position,
+}
+
+impl Variant {
+    pub const ALL: [Variant; 1] = [Variant::Position];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Variant::Position => "position",
+        }
+    }
+
+    /// The rule of this variant in one sentence, as stated to players.
+    pub fn rule(&self) -> &'static str {
+        match self {
+            Variant::Position => {
+                "Cells in the same position of their boxes may not repeat a digit either."
+            }
+        }
+    }
+
+    /// The extra units introduced by this variant.
+    pub fn units(&self) -> Vec<UnitCells> {
+        match self {
+            Variant::Position => (0..9)
+                .map(|pos| std::array::from_fn(|b| Unit::Box(b).classic_cells()[pos]))
+                .collect(),
+        }
+    }
+}
+
+impl Display for Variant {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Variant {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Variant::ALL
+            .iter()
+            .find(|v| v.name().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| format!("Unknown variant '{}'", s))
+    }
+}
+
+/// Lookup tables derived from the units, so the solver does not recompute them on every assignment.
+#[derive(Debug)]
+struct Tables {
+    /// Cells of every unit in the order of [Rules::units].
+    units: Vec<UnitCells>,
+    /// Sorted peers of every cell.
+    peers: Vec<Vec<usize>>,
+}
+
+impl Tables {
+    fn new(extra_units: &[UnitCells]) -> Tables {
+        let units: Vec<UnitCells> = Unit::classic()
+            .map(|u| u.classic_cells())
+            .chain(extra_units.iter().copied())
+            .collect();
+        let peers = (0..81)
+            .map(|index| {
+                let mut mask: u128 = 0;
+                for unit in units.iter().filter(|u| u.contains(&index)) {
+                    for &i in unit {
+                        mask |= 1 << i;
+                    }
+                }
+                mask &= !(1 << index);
+                (0..81).filter(|&i| mask & (1 << i) != 0).collect()
+            })
+            .collect();
+        Tables { units, peers }
+    }
+}
+
+/// The active constraint set for a grid: the classic rules plus the extra units of any variants.
+#[derive(Debug, Clone)]
+pub struct Rules {
+    variants: Vec<Variant>,
+    extra_units: Vec<UnitCells>,
+    tables: Arc<Tables>,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules::classic()
+    }
+}
+
+impl Rules {
+    /// Plain Sudoku: rows, columns and boxes only.
+    pub fn classic() -> Rules {
+        static CLASSIC: OnceLock<Arc<Tables>> = OnceLock::new();
+        Rules {
+            variants: Vec::new(),
+            extra_units: Vec::new(),
+            tables: CLASSIC.get_or_init(|| Arc::new(Tables::new(&[]))).clone(),
+        }
+    }
+
+    pub fn new(variants: &[Variant]) -> Rules {
+        variants
+            .iter()
+            .fold(Rules::classic(), |rules, &v| rules.with_variant(v))
+    }
+
+    pub fn with_variant(mut self, variant: Variant) -> Rules {
+        if !self.variants.contains(&variant) {
+            self.variants.push(variant);
+            self.extra_units.extend(variant.units());
+            self.tables = Arc::new(Tables::new(&self.extra_units));
+        }
+        self
+    }
+
+    pub fn variants(&self) -> &[Variant] {
+        &self.variants
+    }
+
+    pub fn is_classic(&self) -> bool {
+        self.variants.is_empty()
+    }
+
+    /// The rules as stated to players, normal Sudoku rules followed by the rule of each
+    /// variant, e.g. for the rules section of an exported puzzle.
+    pub fn summary(&self) -> String {
+        let mut summary = String::from(
+            "Normal Sudoku rules apply: every row, column and box contains the digits 1 to 9 once each.",
+        );
+        for variant in &self.variants {
+            summary.push(' ');
+            summary.push_str(variant.rule());
+        }
+        summary
+    }
+
+    /// All units on top of rows, columns and boxes.
+    pub fn extra_units(&self) -> &[UnitCells] {
+        &self.extra_units
+    }
+
+    /// Human readable name of the extra unit at `index` in [Rules::extra_units], e.g. "position region 3".
+    pub fn extra_unit_name(&self, index: usize) -> String {
+        match self.extra_unit_variant(index) {
+            Some((variant, n)) => format!("{} region {}", variant.name(), n + 1),
+            None => format!("region {}", index + 1),
+        }
+    }
+
+    /// The variant adding the extra unit at `index` in [Rules::extra_units], along with the
+    /// position of the unit among those of the variant.
+    pub fn extra_unit_variant(&self, index: usize) -> Option<(Variant, usize)> {
+        let mut offset = index;
+        for &variant in &self.variants {
+            let count = variant.units().len();
+            if offset < count {
+                return Some((variant, offset));
+            }
+            offset -= count;
+        }
+        None
+    }
+
+    /// Every unit: rows, columns and boxes as in [Unit::classic], then the extra units.
+    pub fn units(&self) -> impl Iterator<Item = Unit> + '_ {
+        Unit::classic().chain((0..self.extra_units.len()).map(Unit::Extra))
+    }
+
+    /// All units containing the cell at `index`: its row, column and box followed by any extra units.
+    pub fn units_at(&self, index: usize) -> impl Iterator<Item = Unit> + '_ {
+        let (x, y) = (index % 9, index / 9);
+        [Unit::Row(y), Unit::Col(x), Unit::box_of(x, y)]
+            .into_iter()
+            .chain(
+                (0..self.extra_units.len())
+                    .filter(move |&e| self.extra_units[e].contains(&index))
+                    .map(Unit::Extra),
+            )
+    }
+
+    /// The cells (x, y) sharing a unit with (x, y), excluding the cell itself, in row-major order.
+    pub fn peers(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.peer_indices(y * 9 + x).iter().map(|&i| (i % 9, i / 9))
+    }
+
+    /// Whether the cells `a` and `b`, given as (x, y), are distinct and share a unit,
+    /// so they cannot hold the same value.
+    pub fn sees(&self, a: (usize, usize), b: (usize, usize)) -> bool {
+        self.peer_indices(a.1 * 9 + a.0)
+            .binary_search(&(b.1 * 9 + b.0))
+            .is_ok()
+    }
+
+    /// Like [Rules::peers] but as cell indices.
+    pub(crate) fn peer_indices(&self, index: usize) -> &[usize] {
+        &self.tables.peers[index]
+    }
+
+    /// Cells of every unit in the order of [Rules::units].
+    pub(crate) fn unit_cells(&self) -> &[UnitCells] {
+        &self.tables.units
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn position_units_cover_grid() {
+        let units = Variant::Position.units();
+        assert_eq!(units.len(), 9);
+        let all: HashSet<usize> = units.iter().flatten().copied().collect();
+        assert_eq!(all.len(), 81);
+        // Top-left cell of every box
+        assert_eq!(units[0], [0, 3, 6, 27, 30, 33, 54, 57, 60]);
+
+        let rules = Rules::new(&[Variant::Position]);
+        assert_eq!(rules.units().count(), 27 + 9);
+        let at: Vec<Unit> = rules.units_at(40).collect();
+        assert_eq!(
+            at,
+            [Unit::Row(4), Unit::Col(4), Unit::Box(4), Unit::Extra(4)]
+        );
+        assert_eq!(Unit::Box(4).cells(&rules)[4], 40);
+        assert_eq!(Unit::Extra(4).describe(&rules), "position region 5");
+    }
+
+    #[test]
+    fn peers() {
+        let classic = Rules::classic();
+        assert_eq!(classic.peers(4, 4).count(), 20);
+        assert!(classic.sees((0, 0), (2, 2)));
+        assert!(!classic.sees((0, 0), (4, 4)));
+        assert!(!classic.sees((3, 3), (3, 3)));
+
+        // Centers of all boxes share a position region
+        let position = Rules::new(&[Variant::Position]);
+        assert_eq!(position.peers(4, 4).count(), 20 + 4);
+        assert!(position.sees((1, 1), (4, 4)));
+        assert!(position.peers(4, 4).all(|p| position.sees(p, (4, 4))));
+    }
+
+    #[test]
+    fn parse_variant() {
+        assert_eq!("Position".parse::<Variant>(), Ok(Variant::Position));
+        assert!("jigsaw".parse::<Variant>().is_err());
+    }
+
+    #[test]
+    fn summary() {
+        assert!(Rules::classic()
+            .summary()
+            .starts_with("Normal Sudoku rules apply"));
+        assert!(Rules::new(&Variant::ALL)
+            .summary()
+            .ends_with(Variant::Position.rule()));
+    }
+}
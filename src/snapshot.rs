@@ -0,0 +1,200 @@
+//! Snapshots of the candidates of a [LogicSolver] and what changed between two of them, to
+//! check a technique against the pencil marks of another solver.
+
+use std::fmt::Display;
+use std::fmt::Error;
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+use crate::logic::{Candidate, LogicSolver, Technique};
+use crate::{CellValue, ValueSet, EMPTY_CELL, NUM_CELLS};
+
+/// The candidates of every cell, a filled cell having its value as the only candidate like
+/// in the pencil mark grids of other tools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CandidateSnapshot {
+    cells: [ValueSet; NUM_CELLS],
+}
+
+impl CandidateSnapshot {
+    pub fn of(solver: &LogicSolver) -> CandidateSnapshot {
+        CandidateSnapshot {
+            cells: std::array::from_fn(|i| {
+                let (x, y) = (i % 9, i / 9);
+                match solver.grid().get(x, y) {
+                    EMPTY_CELL => solver.candidates(x, y),
+                    value => [value].into_iter().collect(),
+                }
+            }),
+        }
+    }
+
+    pub fn candidates(&self, x: usize, y: usize) -> ValueSet {
+        self.cells[y * 9 + x]
+    }
+
+    /// Candidates of this snapshot missing from `after`, row by row.
+    pub fn removed(&self, after: &CandidateSnapshot) -> Vec<Candidate> {
+        difference(self, after)
+    }
+
+    /// Candidates of `after` missing from this snapshot, which a sound technique never makes.
+    pub fn added(&self, after: &CandidateSnapshot) -> Vec<Candidate> {
+        difference(after, self)
+    }
+}
+
+fn difference(from: &CandidateSnapshot, to: &CandidateSnapshot) -> Vec<Candidate> {
+    let mut candidates = Vec::new();
+    for i in 0..NUM_CELLS {
+        let (x, y) = (i % 9, i / 9);
+        let kept = to.candidates(x, y);
+        for value in from.candidates(x, y) {
+            if !kept.contains(value) {
+                candidates.push(Candidate { x, y, value });
+            }
+        }
+    }
+    candidates
+}
+
+/// Nine rows of candidate lists, padded to line up in columns, with '.' for a cell without
+/// candidates.
+impl Display for CandidateSnapshot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        let text = |i: usize| -> String {
+            let values: String = self.cells[i].into_iter().map(|v| v.to_string()).collect();
+            if values.is_empty() {
+                ".".to_string()
+            } else {
+                values
+            }
+        };
+        let widths: Vec<usize> = (0..9)
+            .map(|x| (0..9).map(|y| text(y * 9 + x).len()).max().unwrap())
+            .collect();
+        for y in 0..9 {
+            let row: Vec<String> = (0..9)
+                .map(|x| format!("{:width$}", text(y * 9 + x), width = widths[x]))
+                .collect();
+            writeln!(f, "{}", row.join(" ").trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads 81 whitespace separated candidate lists, e.g. copied from another tool. Box borders
+/// made of '|', '+' and '-' are skipped and '.' stands for a cell without candidates.
+impl FromStr for CandidateSnapshot {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens: Vec<&str> = s
+            .split_whitespace()
+            .filter(|t| !t.chars().all(|c| matches!(c, '|' | '+' | '-')))
+            .collect();
+        if tokens.len() != NUM_CELLS {
+            return Err(format!(
+                "Expected {} cells of candidates, found {}",
+                NUM_CELLS,
+                tokens.len()
+            ));
+        }
+        let mut cells = [ValueSet::empty(); NUM_CELLS];
+        for (cell, token) in cells.iter_mut().zip(tokens) {
+            if token == "." {
+                continue;
+            }
+            for c in token.chars() {
+                match c.to_digit(10) {
+                    Some(d @ 1..=9) => cell.add(d as CellValue),
+                    _ => return Err(format!("Invalid candidates '{}'", token)),
+                }
+            }
+        }
+        Ok(CandidateSnapshot { cells })
+    }
+}
+
+/// What one pass of [elimination_pass] did: the candidates before and after, and which
+/// candidates each technique removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EliminationPass {
+    pub before: CandidateSnapshot,
+    pub after: CandidateSnapshot,
+    /// The techniques which removed anything, in the order they ran.
+    pub removed: Vec<(Technique, Vec<Candidate>)>,
+}
+
+impl EliminationPass {
+    pub fn removed_by(&self, technique: Technique) -> &[Candidate] {
+        self.removed
+            .iter()
+            .find(|(t, _)| *t == technique)
+            .map_or(&[], |(_, removed)| removed)
+    }
+}
+
+/// One line per technique, with eliminations written `r1c2<>5` as many tools do.
+impl Display for EliminationPass {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        for (technique, removed) in &self.removed {
+            let removed: Vec<String> = removed
+                .iter()
+                .map(|c| format!("r{}c{}<>{}", c.y + 1, c.x + 1, c.value))
+                .collect();
+            writeln!(f, "{}: {}", technique, removed.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs each technique allowed by `solver` in turn, easiest first, until it finds nothing
+/// more, and records which candidates it removed by comparing snapshots around it. Unlike
+/// [LogicSolver::run] no technique waits for an easier one to be exhausted again, so each
+/// technique's share can be compared on its own with another tool.
+pub fn elimination_pass(solver: &mut LogicSolver) -> EliminationPass {
+    let before = CandidateSnapshot::of(solver);
+    let mut removed = Vec::new();
+    for technique in solver.techniques().iter() {
+        let start = CandidateSnapshot::of(solver);
+        while let Some(step) = solver.find(technique) {
+            solver.apply(&step);
+        }
+        let candidates = start.removed(&CandidateSnapshot::of(solver));
+        if !candidates.is_empty() {
+            removed.push((technique, candidates));
+        }
+    }
+    EliminationPass {
+        before,
+        after: CandidateSnapshot::of(solver),
+        removed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::TechniqueSet;
+    use crate::{parse_grid, Rules};
+
+    #[test]
+    fn pass_reports_each_technique() {
+        let grid = parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap();
+        let rules = Rules::classic();
+        let techniques: TechniqueSet = [Technique::HiddenSingle, Technique::PointingPair]
+            .into_iter()
+            .collect();
+        let mut solver = LogicSolver::new(&grid, &rules).with_techniques(techniques);
+        let pass = elimination_pass(&mut solver);
+
+        assert_eq!(pass.after, CandidateSnapshot::of(&solver));
+        assert_eq!(pass.after.to_string().parse(), Ok(pass.after));
+        assert!(pass.before.added(&pass.after).is_empty());
+        let total: usize = pass.removed.iter().map(|(_, r)| r.len()).sum();
+        assert_eq!(total, pass.before.removed(&pass.after).len());
+        assert!(!pass.removed_by(Technique::HiddenSingle).is_empty());
+        assert!(pass.removed_by(Technique::NakedPair).is_empty());
+    }
+}
@@ -0,0 +1,130 @@
+use std::str::FromStr;
+
+use crate::{CellValue, EMPTY_CELL};
+
+/// The alphabet used to read and write cell values, e.g. the digits 1..9 or the letters A..I.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolSet {
+    // symbols[v - 1] is the symbol of value v
+    symbols: Vec<char>,
+    // The first one is used when printing
+    empty: Vec<char>,
+}
+
+impl SymbolSet {
+    /// Builds a symbol set from one symbol per value and the characters denoting an empty cell.
+    /// All characters must be distinct, non-whitespace and not among the box drawing and comment
+    /// characters `|`, `+`, `-` and `#` that the parsers skip.
+    pub fn new(symbols: &[char], empty: &[char]) -> Result<SymbolSet, String> {
+        if symbols.len() != 9 {
+            return Err(format!("Expected 9 symbols but got {}", symbols.len()));
+        }
+        if empty.is_empty() {
+            return Err("At least one empty cell symbol is required".to_string());
+        }
+        let all: Vec<char> = symbols.iter().chain(empty).copied().collect();
+        for (i, c) in all.iter().enumerate() {
+            if c.is_whitespace() {
+                return Err("Symbols cannot be whitespace".to_string());
+            }
+            if matches!(c, '|' | '+' | '-' | '#') {
+                return Err(format!("Symbol '{}' is reserved for the grid layout", c));
+            }
+            if all[..i].contains(c) {
+                return Err(format!("Symbol '{}' is used more than once", c));
+            }
+        }
+        Ok(SymbolSet {
+            symbols: symbols.to_vec(),
+            empty: empty.to_vec(),
+        })
+    }
+
+    /// The digits 1..9 with '.' or '0' for empty cells.
+    pub fn digits() -> SymbolSet {
+        SymbolSet {
+            symbols: ('1'..='9').collect(),
+            empty: vec!['.', '0'],
+        }
+    }
+
+    /// The letters A..I with '.' for empty cells.
+    pub fn letters() -> SymbolSet {
+        SymbolSet {
+            symbols: ('A'..='I').collect(),
+            empty: vec!['.'],
+        }
+    }
+
+    /// The symbol for `value`, or the empty cell symbol for [EMPTY_CELL].
+    pub fn symbol(&self, value: CellValue) -> char {
+        if value == EMPTY_CELL {
+            self.empty[0]
+        } else {
+            self.symbols[value as usize - 1]
+        }
+    }
+
+    /// The value denoted by `c`, [EMPTY_CELL] for an empty cell symbol, or [None] if `c` is not part of the set.
+    pub fn value(&self, c: char) -> Option<CellValue> {
+        if self.empty.contains(&c) {
+            return Some(EMPTY_CELL);
+        }
+        self.symbols
+            .iter()
+            .position(|&s| s == c)
+            .map(|i| i as CellValue + 1)
+    }
+}
+
+impl Default for SymbolSet {
+    fn default() -> Self {
+        SymbolSet::digits()
+    }
+}
+
+/// Parses "ABCDEFGHI" as the symbols for 1..9 with '.' as empty cell,
+/// or a 10 character string where the last character denotes the empty cell.
+impl FromStr for SymbolSet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = s.chars().collect();
+        match chars.len() {
+            9 => SymbolSet::new(&chars, &['.']),
+            10 => SymbolSet::new(&chars[..9], &chars[9..]),
+            n => Err(format!("Expected 9 or 10 symbols but got {}", n)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_grid, parse_grid_with_symbols, Grid};
+
+    #[test]
+    fn invalid_symbol_sets() {
+        assert!("ABCDEFGH".parse::<SymbolSet>().is_err());
+        assert!("ABCDEFGHA".parse::<SymbolSet>().is_err());
+        assert!("ABCDEFGHI ".parse::<SymbolSet>().is_err());
+        for reserved in ["ABCDEFGH|.", "ABCDEFGHI+", "ABCDEFGHI-", "#BCDEFGHI."] {
+            assert!(reserved.parse::<SymbolSet>().is_err());
+        }
+    }
+
+    #[test]
+    fn round_trip_unicode() {
+        let symbols: SymbolSet = "αβγδεζηθι_".parse().unwrap();
+        let values: Vec<u8> = (0..81).map(|i| ((i * 7) % 10) as u8).collect();
+        let grid = Grid::new(&values);
+        let text = grid.display_with(&symbols).to_string();
+        assert!(text.contains('α') && text.contains('_'));
+        let parsed = parse_grid_with_symbols(&text, &symbols).unwrap();
+        assert_eq!(parsed.to_string(), grid.to_string());
+        assert_eq!(
+            parse_grid(&grid.to_string()).unwrap().to_string(),
+            grid.to_string()
+        );
+    }
+}
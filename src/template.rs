@@ -0,0 +1,233 @@
+//! Clue templates: the positions of the givens of a puzzle, without their values.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fmt::Error;
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+use crate::{Grid, EMPTY_CELL, NUM_CELLS};
+
+/// The fewest clues any classic puzzle with a unique solution has.
+pub const MIN_CLUES: usize = 17;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Template {
+    // Bit i is set when cell i (row-major) holds a clue
+    cells: u128,
+}
+
+impl Template {
+    /// The positions of the givens of `grid`.
+    pub fn from_grid(grid: &Grid) -> Template {
+        let cells = (0..NUM_CELLS)
+            .filter(|&i| grid.get(i % 9, i / 9) != EMPTY_CELL)
+            .fold(0, |cells, i| cells | 1 << i);
+        Template { cells }
+    }
+
+    pub fn clues(&self) -> usize {
+        self.cells.count_ones() as usize
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        self.cells & (1 << (y * 9 + x)) != 0
+    }
+
+    /// Indices of the clue cells, row-major.
+    pub fn cells(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..NUM_CELLS).filter(move |&i| self.cells & (1 << i) != 0)
+    }
+
+    /// The template in all eight orientations of the square, the identity first: four quarter
+    /// turns, each plain and mirrored.
+    pub fn symmetries(&self) -> [Template; 8] {
+        let mut symmetries = [*self; 8];
+        for (k, symmetry) in symmetries.iter_mut().enumerate() {
+            symmetry.cells = self
+                .cells()
+                .map(|i| {
+                    let (mut x, mut y) = (i % 9, i / 9);
+                    if k % 2 == 1 {
+                        x = 8 - x;
+                    }
+                    for _ in 0..k / 2 {
+                        (x, y) = (8 - y, x);
+                    }
+                    y * 9 + x
+                })
+                .fold(0, |cells, i| cells | 1 << i);
+        }
+        symmetries
+    }
+
+    /// The smallest orientation, shared by all templates equal up to rotation and reflection.
+    pub fn canonical(&self) -> Template {
+        *self.symmetries().iter().min().unwrap()
+    }
+
+    /// How many of the eight orientations leave the template unchanged, 1 if it has no symmetry.
+    pub fn symmetry_count(&self) -> usize {
+        self.symmetries().iter().filter(|&t| t == self).count()
+    }
+
+    /// Rejects templates which no puzzle with a unique solution can have: those with fewer than
+    /// [MIN_CLUES] clues, and those leaving two rows of a band or two columns of a stack without
+    /// a clue, as swapping them turns any solution into another one.
+    pub fn check(&self) -> Result<(), String> {
+        if self.clues() < MIN_CLUES {
+            return Err(format!(
+                "A unique puzzle needs at least {} clues, the template has {}",
+                MIN_CLUES,
+                self.clues()
+            ));
+        }
+        for (name, columns) in [("Rows", false), ("Columns", true)] {
+            let empty = |n| {
+                (0..9).all(|m| match columns {
+                    false => !self.contains(m, n),
+                    true => !self.contains(n, m),
+                })
+            };
+            for band in 0..3 {
+                let empty_lines: Vec<usize> =
+                    (band * 3..band * 3 + 3).filter(|&n| empty(n)).collect();
+                if empty_lines.len() >= 2 {
+                    return Err(format!(
+                        "{} {} and {} have no clues, so swapping them gives a second solution",
+                        name,
+                        empty_lines[0] + 1,
+                        empty_lines[1] + 1
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How often a template occurs in a collection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TemplateCount {
+    /// The [Template::canonical] orientation.
+    pub template: Template,
+    pub count: usize,
+}
+
+/// The clue templates of `grids` up to rotation and reflection, most common first. Ties go to
+/// the more symmetric template, then to the one with fewer clues.
+pub fn common_templates<'a>(grids: impl IntoIterator<Item = &'a Grid>) -> Vec<TemplateCount> {
+    let mut counts: HashMap<Template, usize> = HashMap::new();
+    for grid in grids {
+        *counts
+            .entry(Template::from_grid(grid).canonical())
+            .or_default() += 1;
+    }
+    let mut templates: Vec<TemplateCount> = counts
+        .into_iter()
+        .map(|(template, count)| TemplateCount { template, count })
+        .collect();
+    templates.sort_by_key(|t| {
+        (
+            std::cmp::Reverse(t.count),
+            std::cmp::Reverse(t.template.symmetry_count()),
+            t.template.clues(),
+            t.template,
+        )
+    });
+    templates
+}
+
+/// Nine rows of 'x' for a clue and '.' for an empty cell.
+impl Display for Template {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        for y in 0..9 {
+            let row: String = (0..9)
+                .map(|x| if self.contains(x, y) { 'x' } else { '.' })
+                .collect();
+            writeln!(f, "{}", row)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads 81 cells in any layout, where 'x', '*' and the digits 1-9 mark a clue and '.', '0'
+/// and '_' an empty cell, so a puzzle is its own template. Whitespace, box borders and lines
+/// starting with '#' are skipped.
+impl FromStr for Template {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut cells = 0u128;
+        let mut count = 0;
+        let cells_text = s.lines().filter(|line| !line.trim_start().starts_with('#'));
+        for c in cells_text.flat_map(str::chars) {
+            let clue = match c {
+                'x' | 'X' | '*' | '1'..='9' => true,
+                '.' | '0' | '_' => false,
+                '|' | '+' | '-' => continue,
+                c if c.is_whitespace() => continue,
+                c => return Err(format!("Unexpected character '{}' in template", c)),
+            };
+            if count < NUM_CELLS && clue {
+                cells |= 1 << count;
+            }
+            count += 1;
+        }
+        if count != NUM_CELLS {
+            return Err(format!(
+                "A template has {} cells, found {}",
+                NUM_CELLS, count
+            ));
+        }
+        Ok(Template { cells })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_templates() {
+        let template: Template =
+            "x...x...x.x.x.x.x...x...x...x..x..x.x.x...x.x.x..x..x...x...x...x.x.x.x.x...x...x"
+                .parse()
+                .unwrap();
+        assert_eq!(template.clues(), 28);
+        assert!(template.check().is_ok());
+        assert_eq!(template.to_string().parse::<Template>(), Ok(template));
+
+        let two_empty_rows: Template = format!("{}{}", "x".repeat(63), ".".repeat(18))
+            .parse()
+            .unwrap();
+        assert!(two_empty_rows.check().unwrap_err().contains("Rows 8 and 9"));
+        let sparse: Template = format!("{}{}", "x".repeat(16), ".".repeat(65))
+            .parse()
+            .unwrap();
+        assert!(sparse.check().is_err());
+        assert!("x".repeat(16).parse::<Template>().is_err());
+    }
+
+    #[test]
+    fn ranks_common_templates() {
+        let grid = crate::parse_grid(
+            "000000010400000000020000000000050407008000300001090000300400200050100000000806000",
+        )
+        .unwrap();
+        let template = Template::from_grid(&grid);
+        let rotated = template.symmetries()[2];
+        assert_ne!(rotated, template);
+        assert_eq!(rotated.canonical(), template.canonical());
+        assert_eq!(rotated.symmetries()[6], template);
+
+        let full = crate::solve_recursive(grid).unwrap();
+        let counts = common_templates(&[grid, grid, full]);
+        assert_eq!(counts.len(), 2);
+        assert_eq!(
+            (counts[0].template, counts[0].count),
+            (template.canonical(), 2)
+        );
+        assert_eq!(counts[1].template.symmetry_count(), 8);
+    }
+}
@@ -0,0 +1,155 @@
+//! Competition sets: puzzles which each need a harder step than the one before.
+
+use std::fmt::Display;
+use std::fmt::Error;
+use std::fmt::Formatter;
+
+use crate::generator::{Difficulty, Generated};
+use crate::job::GenerationJob;
+use crate::logic::{solve_logical_with_rules, Technique};
+use crate::{Grid, Rules};
+
+/// The hardest step needed to solve a puzzle, from easiest to hardest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum HardestStep {
+    Technique(Technique),
+    /// The techniques are not enough, so solving needs guessing.
+    Guessing,
+}
+
+impl HardestStep {
+    /// One step for each technique and one for guessing.
+    pub const COUNT: usize = Technique::ALL.len() + 1;
+
+    pub fn of(grid: &Grid) -> HardestStep {
+        HardestStep::of_with_rules(grid, &Rules::classic())
+    }
+
+    pub fn of_with_rules(grid: &Grid, rules: &Rules) -> HardestStep {
+        match solve_logical_with_rules(grid, rules) {
+            (Some(_), steps) => steps
+                .iter()
+                .map(|s| HardestStep::Technique(s.technique))
+                .max()
+                .unwrap_or(HardestStep::Technique(Technique::HiddenSingle)),
+            (None, _) => HardestStep::Guessing,
+        }
+    }
+
+    pub fn difficulty(&self) -> Difficulty {
+        match self {
+            HardestStep::Technique(technique) => technique.difficulty(),
+            HardestStep::Guessing => Difficulty::Expert,
+        }
+    }
+}
+
+impl Display for HardestStep {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            HardestStep::Technique(technique) => write!(f, "{}", technique),
+            HardestStep::Guessing => write!(f, "guessing"),
+        }
+    }
+}
+
+/// A puzzle of a pack along with what makes it harder than the previous one.
+#[derive(Debug, Clone, Copy)]
+pub struct PackPuzzle {
+    pub generated: Generated,
+    pub hardest: HardestStep,
+}
+
+/// Generates a pack of `size` puzzles whose hardest steps all differ, easiest first, so
+/// every puzzle is strictly harder than the one before. Puzzles equivalent to an excluded
+/// one, e.g. from an archive, are never used.
+#[derive(Debug, Clone)]
+pub struct TournamentPack {
+    size: usize,
+    max_attempts: usize,
+    // One job per difficulty, in the order of Difficulty::ALL
+    jobs: Vec<GenerationJob>,
+}
+
+impl TournamentPack {
+    pub fn new(size: usize, seed: u64) -> TournamentPack {
+        TournamentPack {
+            size,
+            max_attempts: 200,
+            jobs: Difficulty::ALL
+                .iter()
+                .enumerate()
+                .map(|(n, &difficulty)| GenerationJob::new(difficulty, seed.wrapping_add(n as u64)))
+                .collect(),
+        }
+    }
+
+    /// Gives up after generating this many puzzles, 200 by default.
+    pub fn max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    pub fn exclude<'a>(&mut self, grids: impl IntoIterator<Item = &'a Grid> + Clone) {
+        for job in &mut self.jobs {
+            job.exclude(grids.clone());
+        }
+    }
+
+    /// Generates puzzles of each difficulty in turn, keeping the first one of each hardest step
+    /// until there are enough.
+    pub fn generate(&mut self) -> Result<Vec<PackPuzzle>, String> {
+        if self.size > HardestStep::COUNT {
+            return Err(format!(
+                "A pack has at most {} puzzles, one for each hardest step",
+                HardestStep::COUNT
+            ));
+        }
+        let mut pack: Vec<PackPuzzle> = Vec::new();
+        for attempt in 0..self.max_attempts {
+            if pack.len() == self.size {
+                break;
+            }
+            let job = &mut self.jobs[attempt % Difficulty::ALL.len()];
+            let generated = job.next().expect("Generation never ends");
+            let hardest = HardestStep::of(&generated.puzzle);
+            if pack.iter().all(|p| p.hardest != hardest) {
+                pack.push(PackPuzzle { generated, hardest });
+            }
+        }
+        if pack.len() < self.size {
+            return Err(format!(
+                "Found only {} distinct hardest steps in {} puzzles",
+                pack.len(),
+                self.max_attempts
+            ));
+        }
+        pack.sort_by_key(|p| p.hardest);
+        Ok(pack)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::canonical_hash;
+
+    #[test]
+    fn pack_is_strictly_harder() {
+        let mut previous = TournamentPack::new(2, 5);
+        let first = previous.generate().unwrap();
+        assert!(first.windows(2).all(|w| w[0].hardest < w[1].hardest));
+
+        let mut pack = TournamentPack::new(2, 5);
+        pack.exclude(first.iter().map(|p| &p.generated.puzzle));
+        let second = pack.generate().unwrap();
+        for p in &second {
+            assert_eq!(HardestStep::of(&p.generated.puzzle), p.hardest);
+            let hash = canonical_hash(&p.generated.puzzle);
+            assert!(first
+                .iter()
+                .all(|f| canonical_hash(&f.generated.puzzle) != hash));
+        }
+        assert!(TournamentPack::new(9, 5).generate().is_err());
+    }
+}
@@ -0,0 +1,336 @@
+//! Transformations of the Sudoku symmetry group, which turn a puzzle into an equivalent one
+//! with the same number of solutions and the same difficulty. Only valid for classic rules.
+
+use std::collections::HashSet;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::{CellValue, Grid, EMPTY_CELL, NUM_CELLS};
+
+/// Relabels the values, permutes bands, stacks and the rows and columns within them, and
+/// optionally transposes the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Transform {
+    // values[v - 1] is what v becomes
+    values: [CellValue; 9],
+    // Row y of the result is row rows[y] of the input, likewise for columns
+    rows: [usize; 9],
+    cols: [usize; 9],
+    transpose: bool,
+}
+
+impl Transform {
+    pub fn identity() -> Transform {
+        Transform {
+            values: std::array::from_fn(|i| i as CellValue + 1),
+            rows: std::array::from_fn(|i| i),
+            cols: std::array::from_fn(|i| i),
+            transpose: false,
+        }
+    }
+
+    /// A uniformly random element of the symmetry group.
+    pub fn random(rng: &mut impl Rng) -> Transform {
+        let mut values = Transform::identity().values;
+        values.shuffle(rng);
+        Transform {
+            values,
+            rows: random_lines(rng),
+            cols: random_lines(rng),
+            transpose: rng.gen(),
+        }
+    }
+
+    /// The transform undoing this one.
+    pub fn inverse(&self) -> Transform {
+        let invert = |p: &[usize; 9]| {
+            let mut inverse = [0; 9];
+            for (i, &j) in p.iter().enumerate() {
+                inverse[j] = i;
+            }
+            inverse
+        };
+        let mut values = [EMPTY_CELL; 9];
+        for (v, &to) in self.values.iter().enumerate() {
+            values[to as usize - 1] = v as CellValue + 1;
+        }
+        let (rows, cols) = match self.transpose {
+            false => (invert(&self.rows), invert(&self.cols)),
+            true => (invert(&self.cols), invert(&self.rows)),
+        };
+        Transform {
+            values,
+            rows,
+            cols,
+            transpose: self.transpose,
+        }
+    }
+
+    pub fn apply(&self, grid: &Grid) -> Grid {
+        let mut result = Grid::new(&[EMPTY_CELL; NUM_CELLS]);
+        for y in 0..9 {
+            for x in 0..9 {
+                let (sx, sy) = if self.transpose { (y, x) } else { (x, y) };
+                let value = grid.get(self.cols[sx], self.rows[sy]);
+                if value != EMPTY_CELL {
+                    result.set(self.values[value as usize - 1], x, y);
+                }
+            }
+        }
+        result
+    }
+}
+
+// A permutation of the 9 rows which keeps the rows of a band together
+fn random_lines(rng: &mut impl Rng) -> [usize; 9] {
+    let mut bands = [0, 1, 2];
+    bands.shuffle(rng);
+    let mut lines = [0; 9];
+    for (b, band) in bands.into_iter().enumerate() {
+        let mut within = [0, 1, 2];
+        within.shuffle(rng);
+        for (i, line) in within.into_iter().enumerate() {
+            lines[b * 3 + i] = band * 3 + line;
+        }
+    }
+    lines
+}
+
+/// Up to `n` distinct puzzles equivalent to `grid`, all different from it, from random
+/// transformations seeded by `seed`. Highly symmetric grids, e.g. an empty one, have fewer
+/// distinct variants, in which case fewer are returned.
+pub fn augment(grid: &Grid, n: usize, seed: u64) -> Vec<Grid> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut seen = HashSet::from([*grid]);
+    let mut variants = Vec::with_capacity(n);
+    // Give up after many repeats rather than loop forever on symmetric grids
+    let mut attempts = 0;
+    while variants.len() < n && attempts < 100 * n {
+        attempts += 1;
+        let variant = Transform::random(&mut rng).apply(grid);
+        if seen.insert(variant) {
+            variants.push(variant);
+        }
+    }
+    variants
+}
+
+/// The smallest grid equivalent to `grid`, reading the cells row by row with empty cells
+/// first and the values relabelled in order of appearance. Equivalent puzzles share it,
+/// which makes it the key for spotting duplicates.
+pub fn canonical(grid: &Grid) -> Grid {
+    canonical_transform(grid).apply(grid)
+}
+
+/// A transform turning `grid` into its [canonical] form. Its inverse takes results for the
+/// canonical form, e.g. the solution, back to `grid`.
+pub fn canonical_transform(grid: &Grid) -> Transform {
+    let mut search = CanonicalSearch {
+        rows: [[EMPTY_CELL; 9]; 9],
+        current: [EMPTY_CELL; NUM_CELLS],
+        order: [0; 9],
+        best: [CellValue::MAX; NUM_CELLS],
+        best_order: [0; 9],
+        best_labels: [EMPTY_CELL; 10],
+        improved: false,
+    };
+    let mut transform = Transform::identity();
+    let orders = line_orders();
+    for transpose in [false, true] {
+        for cols in &orders {
+            for y in 0..9 {
+                for x in 0..9 {
+                    search.rows[y][x] = if transpose {
+                        grid.get(y, cols[x])
+                    } else {
+                        grid.get(cols[x], y)
+                    };
+                }
+            }
+            search.improved = false;
+            search.rows(0, 0, 0, [EMPTY_CELL; 10], 1);
+            if search.improved {
+                let (rows, cols) = match transpose {
+                    false => (search.best_order, *cols),
+                    true => (*cols, search.best_order),
+                };
+                transform = Transform {
+                    values: search.values(),
+                    rows,
+                    cols,
+                    transpose,
+                };
+            }
+        }
+    }
+    transform
+}
+
+/// A hash of [canonical] which stays the same across builds and platforms, so it can be
+/// stored alongside an archive.
+pub fn canonical_hash(grid: &Grid) -> u64 {
+    // FNV-1a
+    let canonical = canonical(grid);
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for i in 0..NUM_CELLS {
+        hash ^= canonical.get(i % 9, i / 9) as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+// Every permutation of the 9 columns which keeps the columns of a stack together
+fn line_orders() -> Vec<[usize; 9]> {
+    const PERMUTATIONS: [[usize; 3]; 6] = [
+        [0, 1, 2],
+        [0, 2, 1],
+        [1, 0, 2],
+        [1, 2, 0],
+        [2, 0, 1],
+        [2, 1, 0],
+    ];
+    let mut orders = Vec::with_capacity(6 * 6 * 6 * 6);
+    for stacks in PERMUTATIONS {
+        for a in PERMUTATIONS {
+            for b in PERMUTATIONS {
+                for c in PERMUTATIONS {
+                    let within = [a, b, c];
+                    orders.push(std::array::from_fn(|i| {
+                        stacks[i / 3] * 3 + within[i / 3][i % 3]
+                    }));
+                }
+            }
+        }
+    }
+    orders
+}
+
+// Picks the rows of a grid with fixed columns one at a time, abandoning an order as soon
+// as it reads larger than the best found so far
+struct CanonicalSearch {
+    rows: [[CellValue; 9]; 9],
+    current: [CellValue; NUM_CELLS],
+    // The rows picked for `current`
+    order: [usize; 9],
+    best: [CellValue; NUM_CELLS],
+    best_order: [usize; 9],
+    // best_labels[v] is what value v became in `best`, 0 if it does not appear
+    best_labels: [CellValue; 10],
+    // Whether `best` changed for the current columns
+    improved: bool,
+}
+
+impl CanonicalSearch {
+    // The relabelling of the best grid, giving the values that do not appear the labels left
+    fn values(&self) -> [CellValue; 9] {
+        let mut values: [CellValue; 9] = std::array::from_fn(|v| self.best_labels[v + 1]);
+        let used = values;
+        let mut unused = (1..=9).filter(|l| !used.contains(l));
+        for value in values.iter_mut().filter(|v| **v == EMPTY_CELL) {
+            *value = unused.next().unwrap_or_default();
+        }
+        values
+    }
+
+    fn rows(
+        &mut self,
+        depth: usize,
+        used: u16,
+        band: usize,
+        labels: [CellValue; 10],
+        next: CellValue,
+    ) {
+        if depth == 9 {
+            if self.current < self.best {
+                self.best = self.current;
+                self.best_order = self.order;
+                self.best_labels = labels;
+                self.improved = true;
+            }
+            return;
+        }
+        let band_used = |b: usize| (0..3).any(|i| used & (1 << (b * 3 + i)) != 0);
+        for r in 0..9 {
+            let allowed = match depth % 3 {
+                0 => !band_used(r / 3),
+                _ => r / 3 == band,
+            };
+            // Identical rows of a band give the same orders
+            let repeat =
+                (r / 3 * 3..r).any(|o| used & (1 << o) == 0 && self.rows[o] == self.rows[r]);
+            if !allowed || used & (1 << r) != 0 || repeat {
+                continue;
+            }
+            let mut labels = labels;
+            let mut next = next;
+            for x in 0..9 {
+                let value = self.rows[r][x];
+                if value != EMPTY_CELL && labels[value as usize] == EMPTY_CELL {
+                    labels[value as usize] = next;
+                    next += 1;
+                }
+                self.current[depth * 9 + x] = labels[value as usize];
+            }
+            self.order[depth] = r;
+            let end = (depth + 1) * 9;
+            if self.current[..end] <= self.best[..end] {
+                self.rows(depth + 1, used | 1 << r, r / 3, labels, next);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_grid, solve_recursive};
+
+    #[test]
+    fn variants_are_equivalent() {
+        let puzzle = parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap();
+        let solution = solve_recursive(puzzle).unwrap();
+        let mut rng = StdRng::seed_from_u64(3);
+        let transform = Transform::random(&mut rng);
+        let variant = transform.apply(&puzzle);
+        assert_ne!(variant, puzzle);
+        assert_eq!(solve_recursive(variant), Some(transform.apply(&solution)));
+        assert_eq!(Transform::identity().apply(&puzzle), puzzle);
+
+        let variants = augment(&puzzle, 20, 1);
+        assert_eq!(variants.len(), 20);
+        assert_eq!(variants, augment(&puzzle, 20, 1));
+        assert_eq!(variants.iter().collect::<HashSet<_>>().len(), 20);
+
+        let empty = Grid::new(&[EMPTY_CELL; NUM_CELLS]);
+        assert!(augment(&empty, 5, 1).is_empty());
+    }
+
+    #[test]
+    fn canonical_form() {
+        let puzzle = parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap();
+        let expected = canonical(&puzzle);
+        for variant in augment(&puzzle, 3, 2) {
+            assert_eq!(canonical(&variant), expected);
+            assert_eq!(variant.fingerprint(), puzzle.fingerprint());
+        }
+        // Fingerprints are stored in archives and bug reports, so they must never change
+        assert_eq!(puzzle.fingerprint(), "34aeee9a576e");
+        assert_eq!(canonical(&expected), expected);
+        let transform = canonical_transform(&puzzle);
+        assert_eq!(transform.inverse().apply(&expected), puzzle);
+        let solution = solve_recursive(puzzle).unwrap();
+        assert_eq!(
+            transform
+                .inverse()
+                .apply(&solve_recursive(expected).unwrap()),
+            solution
+        );
+        let other = parse_grid(include_str!("../sample_files/hard.sudoku")).unwrap();
+        assert_ne!(canonical_hash(&other), canonical_hash(&puzzle));
+
+        let empty = Grid::new(&[EMPTY_CELL; NUM_CELLS]);
+        assert_eq!(canonical(&empty), empty);
+    }
+}
@@ -0,0 +1,140 @@
+//! Unavoidable sets of a solved grid.
+//!
+//! An unavoidable set is a set of cells whose values can be permuted into another valid solution.
+//! Any puzzle with that solution as its unique solution must therefore have a clue in every unavoidable set.
+
+use crate::{CellValue, Grid, Rules, EMPTY_CELL, NUM_CELLS};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UnavoidableSet {
+    // Bit i is set when cell i (row-major) is part of the set
+    cells: u128,
+}
+
+impl UnavoidableSet {
+    pub fn len(&self) -> usize {
+        self.cells.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells == 0
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        self.cells & (1 << (y * 9 + x)) != 0
+    }
+
+    /// Coordinates (x, y) of the cells in the set.
+    pub fn cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..NUM_CELLS)
+            .filter(|&i| self.cells & (1 << i) != 0)
+            .map(|i| (i % 9, i / 9))
+    }
+
+    /// Whether `puzzle` has a clue in this set.
+    pub fn is_hit_by(&self, puzzle: &Grid) -> bool {
+        self.cells().any(|(x, y)| puzzle.get(x, y) != EMPTY_CELL)
+    }
+}
+
+/// Finds unavoidable sets of at most `max_size` cells in the complete grid `solution`,
+/// smallest first. No returned set contains another.
+///
+/// The sets are found by taking two or three values and the cells holding them. Within those,
+/// any group of cells that contains all or none of them in each row, column and box can have its values
+/// rotated into another solution; the smallest such groups are the connected components below.
+pub fn unavoidable_sets(solution: &Grid, max_size: usize) -> Vec<UnavoidableSet> {
+    let mut found: Vec<UnavoidableSet> = Vec::new();
+    for a in 1..=9 {
+        for b in a + 1..=9 {
+            found.extend(value_components(solution, &[a, b]));
+            for c in b + 1..=9 {
+                found.extend(value_components(solution, &[a, b, c]));
+            }
+        }
+    }
+    found.retain(|set| set.len() <= max_size);
+    found.sort_by_key(|set| (set.len(), set.cells));
+    found.dedup();
+
+    let mut minimal: Vec<UnavoidableSet> = Vec::new();
+    for set in found {
+        if !minimal.iter().any(|m| m.cells & set.cells == m.cells) {
+            minimal.push(set);
+        }
+    }
+    minimal
+}
+
+/// Whether `puzzle` has a clue in every set. Necessary, but not sufficient, for a unique solution.
+pub fn hits_all(puzzle: &Grid, sets: &[UnavoidableSet]) -> bool {
+    sets.iter().all(|set| set.is_hit_by(puzzle))
+}
+
+fn value_components(solution: &Grid, values: &[CellValue]) -> Vec<UnavoidableSet> {
+    let cells: Vec<usize> = (0..NUM_CELLS)
+        .filter(|&i| values.contains(&solution.get(i % 9, i / 9)))
+        .collect();
+    let classic = Rules::classic();
+    let shares_unit = |i: usize, j: usize| classic.sees((i % 9, i / 9), (j % 9, j / 9));
+
+    let mut components = Vec::new();
+    let mut visited: u128 = 0;
+    for &start in &cells {
+        if visited & (1 << start) != 0 {
+            continue;
+        }
+        let mut component: u128 = 1 << start;
+        let mut stack = vec![start];
+        while let Some(i) = stack.pop() {
+            for &j in &cells {
+                if component & (1 << j) == 0 && shares_unit(i, j) {
+                    component |= 1 << j;
+                    stack.push(j);
+                }
+            }
+        }
+        visited |= component;
+        components.push(UnavoidableSet { cells: component });
+    }
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_candidates, parse_grid, solve_recursive};
+
+    #[test]
+    fn sets_are_unavoidable() {
+        let solution =
+            solve_recursive(parse_grid(include_str!("../sample_files/easy.sudoku")).unwrap())
+                .unwrap();
+        let sets = unavoidable_sets(&solution, 12);
+        assert!(!sets.is_empty());
+        assert!(sets.windows(2).all(|w| w[0].len() <= w[1].len()));
+
+        // Rotating the values within a set gives another solution
+        for set in &sets {
+            let mut values: Vec<CellValue> = set.cells().map(|(x, y)| solution.get(x, y)).collect();
+            values.sort_unstable();
+            values.dedup();
+            let mut other = solution;
+            for (x, y) in set.cells() {
+                let pos = values
+                    .iter()
+                    .position(|&v| v == solution.get(x, y))
+                    .unwrap();
+                other.set(values[(pos + 1) % values.len()], x, y);
+            }
+            assert_ne!(other.to_string(), solution.to_string());
+            for i in 0..NUM_CELLS {
+                let (x, y) = (i % 9, i / 9);
+                let mut cleared = other;
+                cleared.set(EMPTY_CELL, x, y);
+                assert!(get_candidates(&cleared, x, y).contains(other.get(x, y)));
+            }
+        }
+        assert!(hits_all(&solution, &sets));
+    }
+}
@@ -0,0 +1,202 @@
+//! Checking claimed solutions against their puzzles, e.g. the output of another solver.
+
+use std::fmt::Display;
+use std::fmt::Error;
+use std::fmt::Formatter;
+
+use rayon::prelude::*;
+
+use crate::batch::grid_lines;
+use crate::{
+    parse_grid_checked_with_symbols, Grid, GridError, Rules, SymbolSet, EMPTY_CELL, NUM_CELLS,
+};
+
+/// Why a claimed solution is wrong. Cells are (x, y).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyError {
+    /// The solutions file has no line for the puzzle.
+    Missing,
+    /// The solutions file has more lines than there are puzzles.
+    Unexpected,
+    /// The solution could not be parsed or breaks the rules.
+    Rejected(GridError),
+    /// The solution has an empty cell.
+    Incomplete { cell: (usize, usize) },
+    /// The solution changes a given of the puzzle.
+    ChangedGiven { cell: (usize, usize) },
+}
+
+impl Display for VerifyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            VerifyError::Missing => write!(f, "no solution given"),
+            VerifyError::Unexpected => write!(f, "solution without a puzzle"),
+            VerifyError::Rejected(error) => write!(f, "{}", error),
+            VerifyError::Incomplete { cell } => {
+                write!(f, "empty cell at row {}, column {}", cell.1 + 1, cell.0 + 1)
+            }
+            VerifyError::ChangedGiven { cell } => write!(
+                f,
+                "changes the given at row {}, column {}",
+                cell.1 + 1,
+                cell.0 + 1
+            ),
+        }
+    }
+}
+
+/// A wrong solution. Line numbers are 1-based and [None] where the file has no such line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mismatch {
+    pub puzzle_line: Option<usize>,
+    pub solution_line: Option<usize>,
+    pub error: VerifyError,
+}
+
+/// Result of [verify_batch].
+#[derive(Debug, Clone, Default)]
+pub struct VerifyReport {
+    /// Number of puzzle and solution pairs checked.
+    pub checked: usize,
+    /// In the order of the files.
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl VerifyReport {
+    pub fn is_ok(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+impl Display for VerifyReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        let line = |n: Option<usize>| n.map_or("-".to_string(), |n| n.to_string());
+        for m in &self.mismatches {
+            writeln!(
+                f,
+                "Puzzle line {}, solution line {}: {}",
+                line(m.puzzle_line),
+                line(m.solution_line),
+                m.error
+            )?;
+        }
+        writeln!(f, "Checked: {}", self.checked)?;
+        writeln!(f, "Mismatches: {}", self.mismatches.len())
+    }
+}
+
+/// Checks that the solution is complete, follows `rules` and keeps every given of `puzzle`.
+pub fn verify_solution(puzzle: &Grid, solution: &Grid, rules: &Rules) -> Result<(), VerifyError> {
+    solution
+        .validate_with_rules(rules)
+        .map_err(VerifyError::Rejected)?;
+    for i in 0..NUM_CELLS {
+        let (x, y) = (i % 9, i / 9);
+        let value = solution.get(x, y);
+        if value == EMPTY_CELL {
+            return Err(VerifyError::Incomplete { cell: (x, y) });
+        }
+        let given = puzzle.get(x, y);
+        if given != EMPTY_CELL && given != value {
+            return Err(VerifyError::ChangedGiven { cell: (x, y) });
+        }
+    }
+    Ok(())
+}
+
+/// Pairs the puzzles and solutions, one per line in the format of [crate::batch::parse_batch],
+/// and checks every solution in parallel. Fails only if a puzzle cannot be parsed.
+pub fn verify_batch(
+    puzzles: &str,
+    solutions: &str,
+    symbols: &SymbolSet,
+    rules: &Rules,
+) -> Result<VerifyReport, String> {
+    let puzzles = grid_lines(puzzles)
+        .map(|(n, line)| {
+            parse_grid_checked_with_symbols(line, symbols)
+                .map(|grid| (n, grid))
+                .map_err(|e| format!("Puzzle line {}: {}", n, e))
+        })
+        .collect::<Result<Vec<(usize, Grid)>, String>>()?;
+    let solutions: Vec<(usize, &str)> = grid_lines(solutions).collect();
+    let checked = puzzles.len().min(solutions.len());
+
+    let mut mismatches: Vec<Mismatch> = puzzles
+        .par_iter()
+        .zip(solutions.par_iter())
+        .filter_map(|(&(puzzle_line, puzzle), &(solution_line, text))| {
+            let result = parse_grid_checked_with_symbols(text, symbols)
+                .map_err(VerifyError::Rejected)
+                .and_then(|solution| verify_solution(&puzzle, &solution, rules));
+            result.err().map(|error| Mismatch {
+                puzzle_line: Some(puzzle_line),
+                solution_line: Some(solution_line),
+                error,
+            })
+        })
+        .collect();
+    mismatches.extend(puzzles[checked..].iter().map(|&(n, _)| Mismatch {
+        puzzle_line: Some(n),
+        solution_line: None,
+        error: VerifyError::Missing,
+    }));
+    mismatches.extend(solutions[checked..].iter().map(|&(n, _)| Mismatch {
+        puzzle_line: None,
+        solution_line: Some(n),
+        error: VerifyError::Unexpected,
+    }));
+    Ok(VerifyReport {
+        checked,
+        mismatches,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::batch::solve_batch;
+    use crate::parse_grid;
+
+    #[test]
+    fn finds_mismatches() {
+        let text = include_str!("../sample_files/easy.sudoku");
+        let puzzle_line: String = text
+            .chars()
+            .filter(|c| c.is_ascii_digit() || *c == '.')
+            .collect();
+        let report = solve_batch([parse_grid(text).unwrap()].into_iter());
+        let solution = report.to_lines().trim().to_string();
+        let rules = Rules::classic();
+        let symbols = SymbolSet::digits();
+
+        let puzzles = format!("{0}\n{0}\n{0}\n{0}\n", puzzle_line);
+        // Swapping every 1 and 4 keeps the grid valid but changes the given 4 in the top left
+        let changed: String = solution
+            .chars()
+            .map(|c| match c {
+                '1' => '4',
+                '4' => '1',
+                c => c,
+            })
+            .collect();
+        let incomplete = format!(".{}", &solution[1..]);
+        let solutions = format!("{}\n# comment\n{}\n{}\n", solution, changed, incomplete);
+        let report = verify_batch(&puzzles, &solutions, &symbols, &rules).unwrap();
+        assert_eq!(report.checked, 3);
+        let errors: Vec<(Option<usize>, Option<usize>, VerifyError)> = report
+            .mismatches
+            .iter()
+            .map(|m| (m.puzzle_line, m.solution_line, m.error))
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                (Some(2), Some(3), VerifyError::ChangedGiven { cell: (0, 0) }),
+                (Some(3), Some(4), VerifyError::Incomplete { cell: (0, 0) }),
+                (Some(4), None, VerifyError::Missing),
+            ]
+        );
+        assert!(report.to_string().contains("Mismatches: 3"));
+    }
+}